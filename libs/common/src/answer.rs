@@ -0,0 +1,163 @@
+use num_bigint::BigInt;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnswerValue {
+    Int(i128),
+    Big(BigInt),
+    Text(String),
+}
+
+impl fmt::Display for AnswerValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswerValue::Int(val) => write!(f, "{val}"),
+            AnswerValue::Big(val) => write!(f, "{val}"),
+            AnswerValue::Text(val) => write!(f, "{val}"),
+        }
+    }
+}
+
+impl Serialize for AnswerValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // big integers go out as strings so JSON consumers don't lose precision
+        match self {
+            AnswerValue::Int(val) => serializer.serialize_i128(*val),
+            AnswerValue::Big(val) => serializer.serialize_str(&val.to_string()),
+            AnswerValue::Text(val) => serializer.serialize_str(val),
+        }
+    }
+}
+
+macro_rules! int_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for AnswerValue {
+                fn from(val: $t) -> Self {
+                    AnswerValue::Int(val.into())
+                }
+            }
+        )*
+    };
+}
+
+int_answer!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+impl From<usize> for AnswerValue {
+    fn from(val: usize) -> Self {
+        AnswerValue::Int(val as i128)
+    }
+}
+
+impl From<isize> for AnswerValue {
+    fn from(val: isize) -> Self {
+        AnswerValue::Int(val as i128)
+    }
+}
+
+impl From<u128> for AnswerValue {
+    fn from(val: u128) -> Self {
+        match i128::try_from(val) {
+            Ok(val) => AnswerValue::Int(val),
+            Err(_) => AnswerValue::Big(val.into()),
+        }
+    }
+}
+
+impl From<BigInt> for AnswerValue {
+    fn from(val: BigInt) -> Self {
+        match i128::try_from(&val) {
+            Ok(val) => AnswerValue::Int(val),
+            Err(_) => AnswerValue::Big(val),
+        }
+    }
+}
+
+impl From<String> for AnswerValue {
+    fn from(val: String) -> Self {
+        AnswerValue::Text(val)
+    }
+}
+
+impl From<&str> for AnswerValue {
+    fn from(val: &str) -> Self {
+        AnswerValue::Text(val.to_string())
+    }
+}
+
+/// The result of running a day's solver, a missing part means it was not computed
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Answer {
+    pub part1: Option<AnswerValue>,
+    pub part2: Option<AnswerValue>,
+}
+
+impl Answer {
+    pub fn new<A, B>(part1: A, part2: B) -> Self
+    where
+        A: Into<AnswerValue>,
+        B: Into<AnswerValue>,
+    {
+        Self {
+            part1: Some(part1.into()),
+            part2: Some(part2.into()),
+        }
+    }
+
+    pub fn part1_only<A: Into<AnswerValue>>(part1: A) -> Self {
+        Self {
+            part1: Some(part1.into()),
+            part2: None,
+        }
+    }
+
+    /// Whichever parts were computed
+    pub fn from_parts<A, B>(part1: Option<A>, part2: Option<B>) -> Self
+    where
+        A: Into<AnswerValue>,
+        B: Into<AnswerValue>,
+    {
+        Self {
+            part1: part1.map(Into::into),
+            part2: part2.map(Into::into),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [&self.part1, &self.part2]
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, part)| part.as_ref().map(|val| format!("Part {}: {val}", idx + 1)))
+            .collect();
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::new(1u32, 2usize).to_string(), "Part 1: 1 Part 2: 2");
+        assert_eq!(Answer::part1_only("abc").to_string(), "Part 1: abc");
+        assert_eq!(
+            Answer::from_parts(None::<u8>, Some(7u8)).to_string(),
+            "Part 2: 7"
+        );
+    }
+
+    #[test]
+    fn big_values() {
+        let small: AnswerValue = BigInt::from(42).into();
+        assert_eq!(small, AnswerValue::Int(42));
+
+        let big: AnswerValue = u128::MAX.into();
+        assert!(matches!(big, AnswerValue::Big(_)));
+        assert_eq!(big.to_string(), u128::MAX.to_string());
+    }
+}
@@ -0,0 +1,106 @@
+//! Find where a sequence `x, f(x), f(f(x)), ...` starts repeating, by Brent's
+//! algorithm. Only a couple of states are kept at a time, so big states like
+//! whole grids are fine.
+
+use std::fmt;
+
+/// The states from `start` on repeat every `period` steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// How many steps before the first state that repeats
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as step `n`
+    pub fn reduce(&self, n: usize) -> usize {
+        match n < self.start {
+            true => n,
+            false => self.start + (n - self.start) % self.period,
+        }
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "repeats every {} after {}", self.period, self.start)
+    }
+}
+
+/// The cycle that repeatedly applying `step` to `initial` falls into, which
+/// must exist or this never returns
+pub fn find<S: PartialEq>(initial: &S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // find the period: the hare runs ahead in doubling stretches and the
+    // tortoise waits at the start of each until the hare comes round to it
+    let (mut power, mut period) = (1, 1);
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare;
+            hare = step(&tortoise);
+            (power, period) = (power * 2, 1);
+        } else {
+            hare = step(&hare);
+            period += 1;
+        }
+    }
+
+    // then the start: walk two states `period` apart from the beginning until
+    // they meet
+    let mut hare = step(initial);
+    for _ in 1..period {
+        hare = step(&hare);
+    }
+    if *initial == hare {
+        return Cycle { start: 0, period };
+    }
+    let (mut tortoise, mut hare) = (step(initial), step(&hare));
+    let mut start = 1;
+    while tortoise != hare {
+        (tortoise, hare) = (step(&tortoise), step(&hare));
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(cycle: Cycle) -> (usize, usize) {
+        (cycle.start, cycle.period)
+    }
+
+    #[test]
+    fn tail_then_loop() {
+        // 0 1 2 3 4 5 6 | 3 4 5 6 | ...
+        let cycle = find(&0, |n| if *n == 6 { 3 } else { n + 1 });
+        assert_eq!(parts(cycle), (3, 4));
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(7), 3);
+        assert_eq!(cycle.reduce(1_000_000_000), 3 + (1_000_000_000 - 3) % 4);
+    }
+
+    #[test]
+    fn loops_from_the_start() {
+        assert_eq!(parts(find(&0, |n| (n + 1) % 5)), (0, 5));
+        assert_eq!(parts(find(&7, |n| *n)), (0, 1));
+        assert_eq!(parts(find(&1u64, |n| n * 3 % 7)), (0, 6));
+    }
+
+    #[test]
+    fn every_shape() {
+        for start in 0..20 {
+            for period in 1..20 {
+                let next = |n: &usize| match *n + 1 == start + period {
+                    true => start,
+                    false => n + 1,
+                };
+                assert_eq!(parts(find(&0, next)), (start, period));
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use grid::GridError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A malformed piece of puzzle input, pinpointed to where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: &'static str,
+    /// Zero based position in the input stream, a line or a block depending on the day
+    pub line: usize,
+    /// Zero based character offset into `text`, when it is known
+    pub column: Option<usize>,
+    pub text: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}, line {}", self.day, self.line + 1)?;
+        if let Some(column) = self.column {
+            write!(f, ", column {}", column + 1)?;
+        }
+        write!(f, ": {}", self.message)?;
+        // a row that never arrived has no text to show
+        if !self.text.is_empty() {
+            write!(f, " in {:?}", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// A grid that wouldn't go together, on the row the problem was found
+    /// on, `text` being that row's line
+    pub fn grid(day: &'static str, err: GridError, text: &str) -> Self {
+        InputLine::new(day, err.row(), text).error(err.to_string())
+    }
+}
+
+/// A line of input being parsed, used to build errors that point back into it
+#[derive(Debug, Clone, Copy)]
+pub struct InputLine<'a> {
+    pub day: &'static str,
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> InputLine<'a> {
+    pub fn new(day: &'static str, line: usize, text: &'a str) -> Self {
+        Self { day, line, text }
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            day: self.day,
+            line: self.line,
+            column: None,
+            text: self.text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// An error pointing at `part`, which should be a slice of this line
+    pub fn error_at(&self, part: &str, message: impl Into<String>) -> ParseError {
+        let offset = (part.as_ptr() as usize).wrapping_sub(self.text.as_ptr() as usize);
+        let column = (offset <= self.text.len())
+            .then(|| self.text.get(..offset).map(|before| before.chars().count()))
+            .flatten();
+
+        ParseError {
+            column,
+            ..self.error(message)
+        }
+    }
+
+    /// Parse a trimmed field of this line, usually a number
+    pub fn parse<T: FromStr>(&self, part: &str) -> Result<T, ParseError> {
+        let part = part.trim();
+        part.parse()
+            .map_err(|_| self.error_at(part, format!("can't parse {part:?}")))
+    }
+
+    /// Check a grid row only uses the given characters
+    pub fn only_chars(&self, allowed: &str) -> Result<(), ParseError> {
+        match self.text.find(|c| !allowed.contains(c)) {
+            Some(idx) => {
+                let bad = &self.text[idx..];
+                let chr = bad.chars().next().unwrap_or_default();
+                Err(self.error_at(bad, format!("unexpected {chr:?}")))
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn split_once(&self, part: &'a str, sep: &str) -> Result<(&'a str, &'a str), ParseError> {
+        part.split_once(sep)
+            .ok_or_else(|| self.error_at(part, format!("expected {sep:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_the_bad_field() {
+        let text = "Game 12: 3 blue, x red";
+        let line = InputLine::new("02", 4, text);
+        let err = line.parse::<u32>(&text[17..18]).unwrap_err();
+
+        assert_eq!(err.column, Some(17));
+        assert_eq!(
+            err.to_string(),
+            "day 02, line 5, column 18: can't parse \"x\" in \"Game 12: 3 blue, x red\""
+        );
+    }
+
+    #[test]
+    fn grid_characters() {
+        assert!(InputLine::new("14", 0, "O.#").only_chars(".#O").is_ok());
+        let err = InputLine::new("14", 0, "O.x#")
+            .only_chars(".#O")
+            .unwrap_err();
+        assert_eq!(err.column, Some(2));
+    }
+
+    #[test]
+    fn grid_rows_are_lines() {
+        let ragged = GridError::Ragged {
+            row: 2,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(
+            ParseError::grid("14", ragged, "O.").to_string(),
+            "day 14, line 3: 2 wide, the other rows are 3 in \"O.\""
+        );
+        assert_eq!(
+            ParseError::grid("14", GridError::MissingRow(0), "").to_string(),
+            "day 14, line 1: no row here"
+        );
+    }
+
+    #[test]
+    fn foreign_slices_have_no_column() {
+        let line = InputLine::new("02", 0, "abc");
+        let other = String::from("abc");
+        assert_eq!(line.error_at(&other, "oops").column, None);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::Solver;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// Size knobs for generated inputs, each day documents how it reads them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenSize {
+    pub lines: Option<usize>,
+    pub width: Option<usize>,
+}
+
+impl GenSize {
+    pub fn lines_or(&self, default: usize) -> usize {
+        self.lines.unwrap_or(default)
+    }
+
+    pub fn width_or(&self, default: usize) -> usize {
+        self.width.unwrap_or(default)
+    }
+}
+
+pub trait Generator {
+    /// Produce the full text of a valid, solvable puzzle input
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String;
+}
+
+/// A day that can both solve and generate its input
+pub trait Puzzle: Solver + Generator {}
+
+impl<T: Solver + Generator> Puzzle for T {}
+
+/// Grow a random hole-free blob of cells with no diagonal-only contacts,
+/// so that its outline is a single simple closed loop
+pub fn random_region(rng: &mut StdRng, rows: usize, cols: usize, fill: f64) -> Vec<Vec<bool>> {
+    assert!(rows >= 2 && cols >= 2, "region needs at least a 2x2 area");
+
+    let mut region = vec![vec![false; cols]; rows];
+    let target = ((rows * cols) as f64 * fill).max(1.0) as usize;
+    let start = (rng.gen_range(0..rows), rng.gen_range(0..cols));
+    region[start.0][start.1] = true;
+
+    let mut frontier = vec![start];
+    let mut filled = 1;
+    let mut attempts = 0;
+
+    while filled < target && attempts < rows * cols * 20 {
+        attempts += 1;
+        let (row, col) = frontier[rng.gen_range(0..frontier.len())];
+        let (next_row, next_col) = match rng.gen_range(0..4) {
+            0 if row > 0 => (row - 1, col),
+            1 if col > 0 => (row, col - 1),
+            2 if row + 1 < rows => (row + 1, col),
+            3 if col + 1 < cols => (row, col + 1),
+            _ => continue,
+        };
+
+        if region[next_row][next_col] {
+            continue;
+        }
+        region[next_row][next_col] = true;
+        if makes_pinch(&region, next_row, next_col) {
+            region[next_row][next_col] = false;
+            continue;
+        }
+
+        frontier.push((next_row, next_col));
+        filled += 1;
+    }
+
+    fill_holes(&mut region);
+    region
+}
+
+fn makes_pinch(region: &[Vec<bool>], row: usize, col: usize) -> bool {
+    // any 2x2 window with only a diagonal pair set would touch the outline twice
+    let rows = region.len();
+    let cols = region[0].len();
+    for top in row.saturating_sub(1)..=row.min(rows - 2) {
+        for left in col.saturating_sub(1)..=col.min(cols - 2) {
+            let a = region[top][left];
+            let b = region[top][left + 1];
+            let c = region[top + 1][left];
+            let d = region[top + 1][left + 1];
+            if (a == d) && (b == c) && (a != b) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn fill_holes(region: &mut [Vec<bool>]) {
+    let rows = region.len();
+    let cols = region[0].len();
+    let mut outside = vec![vec![false; cols]; rows];
+    let mut stack: Vec<(usize, usize)> = (0..rows)
+        .flat_map(|r| [(r, 0), (r, cols - 1)])
+        .chain((0..cols).flat_map(|c| [(0, c), (rows - 1, c)]))
+        .filter(|&(r, c)| !region[r][c])
+        .collect();
+
+    while let Some((r, c)) = stack.pop() {
+        if outside[r][c] || region[r][c] {
+            continue;
+        }
+        outside[r][c] = true;
+        if r > 0 {
+            stack.push((r - 1, c));
+        }
+        if c > 0 {
+            stack.push((r, c - 1));
+        }
+        if r + 1 < rows {
+            stack.push((r + 1, c));
+        }
+        if c + 1 < cols {
+            stack.push((r, c + 1));
+        }
+    }
+
+    for (r, row) in region.iter_mut().enumerate() {
+        for (c, cell) in row.iter_mut().enumerate() {
+            if !outside[r][c] {
+                *cell = true;
+            }
+        }
+    }
+}
+
+/// Walk the outline of a region from `random_region`, returning the corner
+/// points (row, col) of the cell lattice in traversal order
+pub fn region_outline(region: &[Vec<bool>]) -> Vec<(usize, usize)> {
+    let rows = region.len();
+    let cols = region[0].len();
+    let inside = |r: isize, c: isize| -> bool {
+        (r >= 0)
+            && (c >= 0)
+            && (r < rows as isize)
+            && (c < cols as isize)
+            && region[r as usize][c as usize]
+    };
+
+    // lattice edges leaving a corner point, as (d_row, d_col)
+    let edges = |r: usize, c: usize| -> Vec<(isize, isize)> {
+        let (r, c) = (r as isize, c as isize);
+        let mut out = Vec::new();
+        if inside(r - 1, c - 1) != inside(r - 1, c) {
+            out.push((-1, 0));
+        }
+        if inside(r, c - 1) != inside(r, c) {
+            out.push((1, 0));
+        }
+        if inside(r - 1, c - 1) != inside(r, c - 1) {
+            out.push((0, -1));
+        }
+        if inside(r - 1, c) != inside(r, c) {
+            out.push((0, 1));
+        }
+        out
+    };
+
+    let start = (0..=rows)
+        .flat_map(|r| (0..=cols).map(move |c| (r, c)))
+        .find(|&(r, c)| !edges(r, c).is_empty())
+        .expect("region is empty");
+
+    let mut outline = vec![start];
+    let mut previous = start;
+    let mut current = {
+        let (dr, dc) = edges(start.0, start.1)[0];
+        (
+            (start.0 as isize + dr) as usize,
+            (start.1 as isize + dc) as usize,
+        )
+    };
+
+    while current != start {
+        outline.push(current);
+        let next = edges(current.0, current.1)
+            .into_iter()
+            .map(|(dr, dc)| {
+                (
+                    (current.0 as isize + dr) as usize,
+                    (current.1 as isize + dc) as usize,
+                )
+            })
+            .find(|&p| p != previous)
+            .expect("outline is not a simple loop");
+        previous = current;
+        current = next;
+    }
+
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn outline_is_simple_loop() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let region = random_region(&mut rng, 12, 9, 0.4);
+            let outline = region_outline(&region);
+            let mut unique = outline.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(unique.len(), outline.len());
+            // consecutive points, including the wrap around, are one step apart
+            for (a, b) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+                assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ParseError;
+use serde::Serialize;
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::task::{JoinError, JoinHandle};
+use tracing::Instrument;
+
+/// What a solve should do when one of its per-line tasks panics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnPanic {
+    /// Fail the whole solve, listing every line that failed
+    #[default]
+    Abort,
+    /// Carry on with the lines that worked, the failures are reported alongside the answer
+    Continue,
+}
+
+/// A per-line task that panicked, `line` is its position in the input stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskFailure {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TaskFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.message)
+    }
+}
+
+/// Why a day's solve produced no answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Panicked(Vec<TaskFailure>),
+    /// The task working out a whole part panicked, there's no line to carry on past
+    PartPanicked {
+        part: u8,
+        message: String,
+    },
+    /// The input parsed but breaks an assumption the solution relies on
+    Unsolvable {
+        day: &'static str,
+        message: String,
+    },
+    /// A knob was set to something the day can't use
+    Knob {
+        day: &'static str,
+        message: String,
+    },
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(err) => write!(f, "{err}"),
+            SolveError::Panicked(failures) => {
+                let lines: Vec<String> = failures.iter().map(|fail| fail.to_string()).collect();
+                write!(
+                    f,
+                    "{} input task(s) panicked, {}",
+                    failures.len(),
+                    lines.join("; ")
+                )
+            }
+            SolveError::PartPanicked { part, message } => {
+                write!(f, "part {part} panicked, {message}")
+            }
+            SolveError::Unsolvable { day, message } | SolveError::Knob { day, message } => {
+                write!(f, "day {day}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<ParseError> for SolveError {
+    fn from(err: ParseError) -> Self {
+        SolveError::Parse(err)
+    }
+}
+
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn failure_message(e: JoinError) -> String {
+    if e.is_panic() {
+        panic_message(e.into_panic())
+    } else {
+        format!("cancelled: {e}")
+    }
+}
+
+struct JoinContext {
+    on_panic: OnPanic,
+    failures: Arc<Mutex<Vec<TaskFailure>>>,
+}
+
+tokio::task_local! {
+    static CONTEXT: JoinContext;
+}
+
+/// Run a solve with the given panic policy, returning what it produced along
+/// with any line failures it carried on past
+pub async fn with_policy<F: Future>(on_panic: OnPanic, solve: F) -> (F::Output, Vec<TaskFailure>) {
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let context = JoinContext {
+        on_panic,
+        failures: Arc::clone(&failures),
+    };
+
+    let output = CONTEXT.scope(context, solve).await;
+    let failures = std::mem::take(&mut *failures.lock().unwrap());
+    (output, failures)
+}
+
+/// Spawn the work for one input line inside its own `parse` span, tagged with
+/// the line so `join_tasks` can say where a panic came from
+pub fn spawn_line<F>(line: usize, task: F) -> (usize, JoinHandle<F::Output>)
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let span = tracing::debug_span!("parse", line);
+    (line, tokio::spawn(task.instrument(span)))
+}
+
+/// Await per-line tasks in order, keeping the line each one came from so a
+/// panic can be traced back to its input. Outside `with_policy` this aborts.
+#[tracing::instrument(name = "join", skip_all, fields(tasks = tasks.len()))]
+pub async fn join_tasks<T>(tasks: Vec<(usize, JoinHandle<T>)>) -> Result<Vec<T>, SolveError> {
+    let mut values = Vec::with_capacity(tasks.len());
+    let mut failures = Vec::new();
+
+    for (line, task) in tasks {
+        match task.await {
+            Ok(value) => values.push(value),
+            Err(e) => {
+                let message = failure_message(e);
+                tracing::error!(line, %message, "input task failed");
+                failures.push(TaskFailure { line, message });
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        return Ok(values);
+    }
+
+    let recorded = CONTEXT.try_with(|context| {
+        if context.on_panic == OnPanic::Continue {
+            context.failures.lock().unwrap().append(&mut failures);
+            true
+        } else {
+            false
+        }
+    });
+
+    match recorded {
+        Ok(true) => Ok(values),
+        _ => Err(SolveError::Panicked(failures)),
+    }
+}
+
+/// Await the task working out one part, if that part was asked for. Unlike a
+/// line a part can't be skipped over, so a panic fails the solve whatever the policy.
+#[tracing::instrument(name = "join", skip(task))]
+pub async fn join_part<T>(part: u8, task: Option<JoinHandle<T>>) -> Result<Option<T>, SolveError> {
+    let Some(task) = task else {
+        return Ok(None);
+    };
+
+    task.await.map(Some).map_err(|e| {
+        let message = failure_message(e);
+        tracing::error!(%message, "part task failed");
+        SolveError::PartPanicked { part, message }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_lines() -> Vec<(usize, JoinHandle<usize>)> {
+        (0..4)
+            .map(|line| {
+                spawn_line(line, async move {
+                    if line == 2 {
+                        panic!("bad line");
+                    }
+                    line * 10
+                })
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn abort_lists_failed_lines() {
+        let err = join_tasks(spawn_lines()).await.unwrap_err();
+        assert_eq!(
+            err,
+            SolveError::Panicked(vec![TaskFailure {
+                line: 2,
+                message: "bad line".to_string()
+            }])
+        );
+        assert_eq!(
+            err.to_string(),
+            "1 input task(s) panicked, line 3: bad line"
+        );
+    }
+
+    #[tokio::test]
+    async fn continue_keeps_partial_result() {
+        let (values, failures) =
+            with_policy(OnPanic::Continue, async { join_tasks(spawn_lines()).await }).await;
+
+        assert_eq!(values, Ok(vec![0, 10, 30]));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].line, 2);
+    }
+
+    #[tokio::test]
+    async fn part_panic_fails_whatever_the_policy() {
+        let (result, failures) = with_policy(OnPanic::Continue, async {
+            let task = tokio::spawn(async { panic!("no route") });
+            join_part::<u32>(2, Some(task)).await
+        })
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "part 2 panicked, no route");
+        assert!(failures.is_empty());
+        assert_eq!(join_part::<u32>(1, None).await, Ok(None));
+    }
+}
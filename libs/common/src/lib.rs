@@ -0,0 +1,15 @@
+mod answer;
+pub mod cycle;
+mod error;
+pub mod generate;
+pub mod join;
+pub mod pairs;
+mod solver;
+
+pub use answer::{Answer, AnswerValue};
+pub use error::{InputLine, ParseError};
+pub use generate::{GenSize, Generator, Puzzle};
+pub use join::{join_part, join_tasks, spawn_line, OnPanic, SolveError, TaskFailure};
+pub use solver::{
+    split_blocks, Framing, InputShape, InputStream, Knob, Knobs, Parts, SolveFuture, Solver,
+};
@@ -0,0 +1,54 @@
+//! Serialize a map as a list of `(key, value)` pairs, for maps keyed by
+//! tuples or other values that formats like JSON can't take as keys.
+//!
+//! ```ignore
+//! #[serde(with = "common::pairs")]
+//! rocks: HashMap<(usize, usize), Rock>,
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Serialize + 'a,
+    V: Serialize + 'a,
+    S: Serializer,
+{
+    serializer.collect_seq(map)
+}
+
+pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+where
+    M: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Ok(Vec::<(K, V)>::deserialize(deserializer)?
+        .into_iter()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Tiles {
+        #[serde(with = "super")]
+        tiles: HashMap<(usize, usize), char>,
+    }
+
+    #[test]
+    fn tuple_keys_round_trip() {
+        let tiles = Tiles {
+            tiles: HashMap::from([((0, 1), '#')]),
+        };
+
+        let json = serde_json::to_string(&tiles).unwrap();
+        assert_eq!(json, r##"{"tiles":[[[0,1],"#"]]}"##);
+        assert_eq!(serde_json::from_str::<Tiles>(&json).unwrap(), tiles);
+    }
+}
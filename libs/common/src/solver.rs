@@ -0,0 +1,184 @@
+use crate::{Answer, SolveError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use tokio::sync::mpsc::Receiver;
+
+pub type SolveFuture = Pin<Box<dyn Future<Output = Result<Answer, SolveError>> + Send>>;
+
+/// How a day wants its input lines delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputShape {
+    Lines,
+    Enumerated,
+}
+
+/// How units are cut out of input that arrives a line at a time, the live
+/// counterpart of `Solver::split_input`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Every line is a unit
+    Lines,
+    /// Runs of lines separated by blank lines
+    Blocks,
+    /// Pieces between separators, line breaks are ignored
+    Separator(char),
+    /// Nothing can be sent until the whole input is in, it goes through `split_input`
+    Whole,
+}
+
+/// Which parts of the puzzle a run should answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Parts {
+    #[default]
+    Both,
+    Part1,
+    Part2,
+}
+
+impl Parts {
+    pub fn part1(&self) -> bool {
+        !matches!(self, Parts::Part2)
+    }
+
+    pub fn part2(&self) -> bool {
+        !matches!(self, Parts::Part1)
+    }
+}
+
+impl From<Option<u8>> for Parts {
+    /// `Some(1)` or `Some(2)` picks a part, anything else runs both
+    fn from(part: Option<u8>) -> Self {
+        match part {
+            Some(1) => Parts::Part1,
+            Some(2) => Parts::Part2,
+            _ => Parts::Both,
+        }
+    }
+}
+
+/// A value a day would otherwise hard code, which a run can change with
+/// `--set NAME=VALUE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Knob {
+    pub name: &'static str,
+    pub help: &'static str,
+    /// Other values `check` runs each input with, which must give the same answers
+    pub check: &'static [&'static str],
+}
+
+/// Knob values for a run by name, anything not set keeps the day's default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Knobs(BTreeMap<String, String>);
+
+impl Knobs {
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.0.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|name| name.as_str())
+    }
+
+    /// The knob's value as it was given, for knobs that are off unless set
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|value| value.as_str())
+    }
+
+    /// The knob's value for `day`, or `default` when it isn't set
+    pub fn get_or<T>(&self, day: &'static str, name: &str, default: T) -> Result<T, SolveError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.0.get(name) {
+            None => Ok(default),
+            Some(value) => value.parse().map_err(|e| SolveError::Knob {
+                day,
+                message: format!("can't set {name} to '{value}': {e}"),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Knobs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self.0.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        f.pad(&pairs.join(", "))
+    }
+}
+
+/// The receiving end of a simulated input stream, in the shape a solver asked for
+pub enum InputStream {
+    Lines(Receiver<String>),
+    Enumerated(Receiver<(usize, String)>),
+}
+
+impl InputStream {
+    pub fn shape(&self) -> InputShape {
+        match self {
+            InputStream::Lines(_) => InputShape::Lines,
+            InputStream::Enumerated(_) => InputShape::Enumerated,
+        }
+    }
+
+    pub fn into_lines(self) -> Receiver<String> {
+        match self {
+            InputStream::Lines(rx) => rx,
+            InputStream::Enumerated(_) => panic!("expected a plain line stream"),
+        }
+    }
+
+    pub fn into_enumerated(self) -> Receiver<(usize, String)> {
+        match self {
+            InputStream::Enumerated(rx) => rx,
+            InputStream::Lines(_) => panic!("expected an enumerated line stream"),
+        }
+    }
+}
+
+pub trait Solver: Send + Sync {
+    /// Two digit day number, as used in the problems directory names
+    fn day(&self) -> &'static str;
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Lines
+    }
+
+    /// Break the raw input file up into the units that get streamed to `solve`
+    fn split_input(&self, raw: &str) -> Vec<String> {
+        raw.lines().map(|s| s.to_string()).collect()
+    }
+
+    /// Break live input up the same way `split_input` does, as lines arrive
+    fn framing(&self) -> Framing {
+        Framing::Lines
+    }
+
+    /// Parse the streamed input, then work out the parts asked for
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture;
+
+    /// The knobs `solve_with` reads, none for most days
+    fn knobs(&self) -> &'static [Knob] {
+        &[]
+    }
+
+    /// As `solve`, with knobs in place of the day's defaults. Names have
+    /// already been checked against `knobs`, the values are up to the day.
+    fn solve_with(&self, input: InputStream, parts: Parts, _knobs: &Knobs) -> SolveFuture {
+        self.solve(input, parts)
+    }
+}
+
+/// Split on blank lines, for days where each unit spans several lines
+pub fn split_blocks(raw: &str) -> Vec<String> {
+    raw.split("\n\n").map(|s| s.to_string()).collect()
+}
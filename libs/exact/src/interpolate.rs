@@ -0,0 +1,41 @@
+use crate::Rational;
+
+/// The value at `x` of the lowest degree polynomial through all of `points`,
+/// by Lagrange's formula. The `x`s of the points must all differ.
+pub fn interpolate(points: &[(i128, i128)], x: i128) -> Rational {
+    points
+        .iter()
+        .enumerate()
+        .map(|(idx, &(xi, yi))| {
+            let basis = points
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != idx)
+                .fold(Rational::ONE, |basis, (_, &(xj, _))| {
+                    assert!(xi != xj, "two points at x = {xi}");
+                    basis * Rational::new(x - xj, xi - xj)
+                });
+            basis * Rational::from(yi)
+        })
+        .fold(Rational::ZERO, |sum, term| sum + term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn through_a_quadratic() {
+        let f = |x: i128| 14_871 * x * x + 14_976 * x + 3_738;
+        let points: Vec<_> = (0..3).map(|x| (x, f(x))).collect();
+        assert_eq!(interpolate(&points, 202_300), Rational::from(f(202_300)));
+        assert_eq!(interpolate(&points, 1), Rational::from(f(1)));
+    }
+
+    #[test]
+    fn fractional_between_points() {
+        let points = [(0, 0), (2, 4)];
+        assert_eq!(interpolate(&points, 1), Rational::from(2));
+        assert_eq!(interpolate(&[(0, 0), (2, 1)], 1), Rational::new(1, 2));
+    }
+}
@@ -0,0 +1,7 @@
+mod interpolate;
+mod linear;
+mod rational;
+
+pub use interpolate::interpolate;
+pub use linear::{det2, det3, solve2, solve3};
+pub use rational::Rational;
@@ -0,0 +1,80 @@
+use crate::Rational;
+
+pub fn det2(m: [[Rational; 2]; 2]) -> Rational {
+    m[0][0] * m[1][1] - m[0][1] * m[1][0]
+}
+
+pub fn det3(m: [[Rational; 3]; 3]) -> Rational {
+    m[0][0] * det2([[m[1][1], m[1][2]], [m[2][1], m[2][2]]])
+        - m[0][1] * det2([[m[1][0], m[1][2]], [m[2][0], m[2][2]]])
+        + m[0][2] * det2([[m[1][0], m[1][1]], [m[2][0], m[2][1]]])
+}
+
+/// `m` with column `col` swapped for `b`
+fn with_column<const N: usize>(
+    mut m: [[Rational; N]; N],
+    col: usize,
+    b: [Rational; N],
+) -> [[Rational; N]; N] {
+    for (row, value) in m.iter_mut().zip(b) {
+        row[col] = value;
+    }
+    m
+}
+
+/// The `x` with `m x = b` by Cramer's rule, `None` when `m` is singular
+pub fn solve2(m: [[Rational; 2]; 2], b: [Rational; 2]) -> Option<[Rational; 2]> {
+    let det = det2(m);
+    if det.is_zero() {
+        return None;
+    }
+    Some(std::array::from_fn(|col| {
+        det2(with_column(m, col, b)) / det
+    }))
+}
+
+/// The `x` with `m x = b` by Cramer's rule, `None` when `m` is singular
+pub fn solve3(m: [[Rational; 3]; 3], b: [Rational; 3]) -> Option<[Rational; 3]> {
+    let det = det3(m);
+    if det.is_zero() {
+        return None;
+    }
+    Some(std::array::from_fn(|col| {
+        det3(with_column(m, col, b)) / det
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints<const N: usize>(rows: [[i128; N]; N]) -> [[Rational; N]; N] {
+        rows.map(|row| row.map(Rational::from))
+    }
+
+    #[test]
+    fn determinants() {
+        assert_eq!(det2(ints([[3, 8], [4, 6]])), Rational::from(-14));
+        assert_eq!(
+            det3(ints([[6, 1, 1], [4, -2, 5], [2, 8, 7]])),
+            Rational::from(-306)
+        );
+    }
+
+    #[test]
+    fn solves() {
+        // x + y = 3, x - y = 0
+        let [x, y] = solve2(ints([[1, 1], [1, -1]]), [3.into(), 0.into()]).unwrap();
+        assert_eq!((x, y), (Rational::new(3, 2), Rational::new(3, 2)));
+        assert_eq!(solve2(ints([[1, 2], [2, 4]]), [1.into(), 2.into()]), None);
+
+        // values big enough that f64 would have to round them
+        let big = 400_000_000_000_000i128;
+        let m = ints([[2, 1, 0], [0, 3, 1], [1, 0, 5]]);
+        let answer = [big + 1, big + 3, big + 7].map(Rational::from);
+        let b: [Rational; 3] = std::array::from_fn(|row| {
+            (0..3).fold(Rational::ZERO, |sum, col| sum + m[row][col] * answer[col])
+        });
+        assert_eq!(solve3(m, b), Some(answer));
+    }
+}
@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Every step is checked, an answer that doesn't fit is a bug, not a rounding
+fn checked(value: Option<i128>) -> i128 {
+    value.expect("exact arithmetic overflowed i128")
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+/// A fraction of two `i128`s, always in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Self = Self { num: 0, den: 1 };
+    pub const ONE: Self = Self { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "{num}/0 is not a number");
+        let divisor = gcd(num, den) * den.signum();
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// The value as a whole number, `None` if it has a fractional part
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    /// The largest whole number not above the value
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            num: checked(self.num.checked_abs()),
+            den: self.den,
+        }
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.num.signum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.den, self.num)
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Rational {
+            fn from(value: $t) -> Self {
+                Self {
+                    num: value as i128,
+                    den: 1,
+                }
+            }
+        })*
+    };
+}
+
+from_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, i128, isize);
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        // work over the lowest common denominator to keep the terms small
+        let divisor = gcd(self.den, other.den);
+        let (left, right) = (self.den / divisor, other.den / divisor);
+        Self::new(
+            checked(
+                checked(self.num.checked_mul(right))
+                    .checked_add(checked(other.num.checked_mul(left))),
+            ),
+            checked(self.den.checked_mul(right)),
+        )
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            num: checked(self.num.checked_neg()),
+            den: self.den,
+        }
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        // cancel across before multiplying so nothing grows that needn't
+        let a = gcd(self.num, other.den).max(1);
+        let b = gcd(other.num, self.den).max(1);
+        Self::new(
+            checked((self.num / a).checked_mul(other.num / b)),
+            checked((self.den / b).checked_mul(other.den / a)),
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self.mul(other.recip())
+    }
+}
+
+/// Orders `a / b` against `c / d`, both denominators positive, without ever
+/// multiplying: peel off the whole parts and, while they match, compare the
+/// flipped remainders the other way round, as Euclid's algorithm would
+fn cmp_fractions(mut a: i128, mut b: i128, mut c: i128, mut d: i128) -> Ordering {
+    loop {
+        let (left, right) = (a.div_euclid(b), c.div_euclid(d));
+        if left != right {
+            return left.cmp(&right);
+        }
+        match (a.rem_euclid(b), c.rem_euclid(d)) {
+            (0, 0) => return Ordering::Equal,
+            (0, _) => return Ordering::Less,
+            (_, 0) => return Ordering::Greater,
+            (a_rem, c_rem) => (a, b, c, d) = (d, c_rem, b, a_rem),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // cross-multiplying is quickest, but only when neither side overflows
+        match (
+            self.num.checked_mul(other.den),
+            other.num.checked_mul(self.den),
+        ) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            _ => cmp_fractions(self.num, self.den, other.num, other.den),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            den => write!(f, "{}/{den}", self.num),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn lowest_terms() {
+        let half = Rational::new(-3, -6);
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(Rational::new(4, -6).to_string(), "-2/3");
+        assert_eq!(Rational::new(-7, 2).floor(), -4);
+        assert_eq!(Rational::new(6, 3).to_integer(), Some(2));
+        assert_eq!(half.to_integer(), None);
+        assert!(Rational::new(1, 3) < half);
+    }
+
+    #[test]
+    fn big_but_exact() {
+        // 2^100 / 3 times 3 is back to a whole number with no rounding
+        let big = Rational::from(1i128 << 100);
+        let third = big / Rational::from(3);
+        assert_eq!((third * Rational::from(3)).to_integer(), Some(1 << 100));
+        assert_eq!(third - third, Rational::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflowed")]
+    fn overflow_is_loud() {
+        let big = Rational::from(i128::MAX / 2);
+        let _ = big * Rational::from(4);
+    }
+
+    #[test]
+    fn ordered_past_i128() {
+        let (max, min) = (Rational::from(i128::MAX), Rational::from(i128::MIN));
+        assert!(min < max);
+        assert!(-max < Rational::ONE);
+        // cross-multiplying any of these overflows
+        assert!(Rational::new(i128::MAX - 2, 2) < Rational::new(i128::MAX, 2));
+        assert!(
+            Rational::new(i128::MAX, i128::MAX - 1) < Rational::new(i128::MAX - 1, i128::MAX - 2)
+        );
+        assert!(Rational::new(-i128::MAX, 2) < Rational::new(i128::MAX, 3));
+        assert_eq!(
+            Rational::new(i128::MAX, 5).cmp(&Rational::new(i128::MAX, 5)),
+            Ordering::Equal
+        );
+    }
+
+    fn arb() -> impl Strategy<Value = Rational> {
+        (-1000i128..1000, 1i128..1000).prop_map(|(num, den)| Rational::new(num, den))
+    }
+
+    proptest! {
+        #[test]
+        fn field_laws(a in arb(), b in arb(), c in arb()) {
+            prop_assert_eq!(a + b, b + a);
+            prop_assert_eq!(a * (b + c), a * b + a * c);
+            prop_assert_eq!((a - b) + b, a);
+            if !b.is_zero() {
+                prop_assert_eq!((a / b) * b, a);
+            }
+            let as_float = |r: Rational| r.numer() as f64 / r.denom() as f64;
+            prop_assert_eq!(a < b, as_float(a) < as_float(b));
+        }
+
+        #[test]
+        fn orders_like_cross_multiplying(a in any::<i64>(), b in 1..i64::MAX, c in any::<i64>(), d in 1..i64::MAX) {
+            let expected = (a as i128 * d as i128).cmp(&(c as i128 * b as i128));
+            prop_assert_eq!(cmp_fractions(a.into(), b.into(), c.into(), d.into()), expected);
+        }
+    }
+}
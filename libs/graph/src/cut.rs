@@ -0,0 +1,130 @@
+use crate::{Graph, NodeId};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::ops::Add;
+
+/// The lightest set of edges whose removal splits a graph in two
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut<E> {
+    /// Total weight of the edges crossing the cut
+    pub weight: E,
+    /// The nodes on one side, the rest are on the other
+    pub side: Vec<NodeId>,
+}
+
+impl<E: Copy + Ord + Add<Output = E> + Default> Graph<E> {
+    /// Global minimum cut of an undirected graph, by Stoer-Wagner. Ties go to
+    /// the lowest node ids, so the same graph always gives the same cut.
+    /// `None` for graphs of fewer than two nodes.
+    pub fn min_cut(&self) -> Option<Cut<E>> {
+        if self.len() < 2 {
+            return None;
+        }
+
+        // each node stands for a group of merged originals
+        let mut members: Vec<Vec<NodeId>> = self.nodes().map(|node| vec![node]).collect();
+        let mut adjacent: Vec<HashMap<usize, E>> = vec![HashMap::new(); self.len()];
+        for node in self.nodes() {
+            for &(next, weight) in self.edges(node) {
+                if next != node {
+                    let total = adjacent[node.index()].entry(next.index()).or_default();
+                    *total = *total + weight;
+                }
+            }
+        }
+        let mut active: Vec<usize> = (0..self.len()).collect();
+
+        let mut best: Option<Cut<E>> = None;
+        while active.len() > 1 {
+            let (s, t, weight) = Self::phase(&active, &adjacent);
+            if best.as_ref().is_none_or(|cut| weight < cut.weight) {
+                let mut side = members[t].clone();
+                side.sort_unstable();
+                best = Some(Cut { weight, side });
+            }
+
+            // merge t into s
+            let moved = std::mem::take(&mut members[t]);
+            members[s].extend(moved);
+            for (next, weight) in std::mem::take(&mut adjacent[t]) {
+                adjacent[next].remove(&t);
+                if next != s {
+                    let total = adjacent[s].entry(next).or_default();
+                    *total = *total + weight;
+                    let total = adjacent[next].entry(s).or_default();
+                    *total = *total + weight;
+                }
+            }
+            active.retain(|node| *node != t);
+        }
+
+        best
+    }
+
+    /// Add nodes one at a time, always the one most tightly joined to those
+    /// already added. Returns the last two and how tightly the last was joined.
+    fn phase(active: &[usize], adjacent: &[HashMap<usize, E>]) -> (usize, usize, E) {
+        let mut added = vec![false; adjacent.len()];
+        let mut joined: Vec<E> = vec![E::default(); adjacent.len()];
+        let mut queue: BinaryHeap<(E, Reverse<usize>)> = active
+            .iter()
+            .map(|node| (E::default(), Reverse(*node)))
+            .collect();
+
+        let (mut last, mut before_last) = (active[0], active[0]);
+        let mut count = 0;
+        while let Some((weight, Reverse(node))) = queue.pop() {
+            if added[node] || weight != joined[node] {
+                continue;
+            }
+            added[node] = true;
+            (before_last, last) = (last, node);
+            count += 1;
+            if count == active.len() {
+                break;
+            }
+
+            for (&next, &edge) in adjacent[node].iter() {
+                if !added[next] {
+                    joined[next] = joined[next] + edge;
+                    queue.push((joined[next], Reverse(next)));
+                }
+            }
+        }
+
+        (before_last, last, joined[last])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_triangles() {
+        // triangles 0-1-2 and 3-4-5, joined by 2-3
+        let mut graph = Graph::with_nodes(6);
+        for (a, b) in [(0, 1), (1, 2), (0, 2), (3, 4), (4, 5), (3, 5), (2, 3)] {
+            graph.add_undirected_edge(NodeId::from_index(a), NodeId::from_index(b), 1u32);
+        }
+
+        let cut = graph.min_cut().unwrap();
+        assert_eq!(cut.weight, 1);
+        let side: Vec<_> = cut.side.iter().map(|node| node.index()).collect();
+        assert!(side == [0, 1, 2] || side == [3, 4, 5]);
+        assert_eq!(graph.min_cut(), Some(cut));
+    }
+
+    #[test]
+    fn weighted_and_disconnected() {
+        let mut graph = Graph::with_nodes(3);
+        graph.add_undirected_edge(NodeId::from_index(0), NodeId::from_index(1), 5u32);
+        graph.add_undirected_edge(NodeId::from_index(1), NodeId::from_index(2), 2);
+        graph.add_undirected_edge(NodeId::from_index(0), NodeId::from_index(2), 1);
+        assert_eq!(graph.min_cut().unwrap().weight, 3);
+
+        graph.add_node();
+        assert_eq!(graph.min_cut().unwrap().weight, 0);
+        assert_eq!(Graph::<u32>::with_nodes(1).min_cut(), None);
+    }
+}
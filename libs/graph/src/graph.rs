@@ -0,0 +1,307 @@
+use crate::search;
+use std::ops::Add;
+
+/// A node in a [`Graph`], handed out in the order nodes are added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub(crate) fn from_index(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Directed adjacency lists with an `E` on every edge. Undirected graphs
+/// store each edge once in each direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph<E = ()> {
+    edges: Vec<Vec<(NodeId, E)>>,
+}
+
+impl<E> Default for Graph<E> {
+    fn default() -> Self {
+        Self { edges: Vec::new() }
+    }
+}
+
+impl<E> Graph<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_nodes(count: usize) -> Self {
+        Self {
+            edges: (0..count).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    pub fn add_node(&mut self) -> NodeId {
+        self.edges.push(Vec::new());
+        NodeId(self.edges.len() - 1)
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) {
+        self.edges[from.0].push((to, weight));
+    }
+
+    /// Remove every edge from `from` to `to`, returning whether there were any
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) -> bool {
+        let before = self.edges[from.0].len();
+        self.edges[from.0].retain(|(next, _)| *next != to);
+        self.edges[from.0].len() != before
+    }
+
+    pub fn remove_undirected_edge(&mut self, a: NodeId, b: NodeId) -> bool {
+        self.remove_edge(a, b) | self.remove_edge(b, a)
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.edges.len()).map(NodeId)
+    }
+
+    /// Outgoing edges in the order they were added
+    pub fn edges(&self, node: NodeId) -> &[(NodeId, E)] {
+        &self.edges[node.0]
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[node.0].iter().map(|(next, _)| *next)
+    }
+
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.edges[node.0].len()
+    }
+
+    /// A route with the fewest edges, both ends included
+    pub fn shortest_path(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        search::bfs(from, |node| self.neighbours(*node), |node| *node == to)
+    }
+
+    /// Groups of nodes that can reach each other, following edges either way
+    /// only where they were added both ways. Each group starts with its lowest
+    /// id, and groups are ordered by that id.
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        let mut seen = vec![false; self.len()];
+        let mut components = Vec::new();
+
+        for node in self.nodes() {
+            if seen[node.0] {
+                continue;
+            }
+            seen[node.0] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![node];
+            while let Some(current) = stack.pop() {
+                component.push(current);
+                for next in self.neighbours(current) {
+                    if !seen[next.0] {
+                        seen[next.0] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            components.push(component);
+        }
+
+        components
+    }
+}
+
+impl<E: Clone> Graph<E> {
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: E) {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+}
+
+impl<E: Copy + Ord + Add<Output = E> + Default> Graph<E> {
+    /// Squash every run of nodes failing `keep` into a single edge between
+    /// the kept nodes at either end, weighted with the total along the run.
+    /// Runs have to be corridors, one way in and one way on; runs that stop
+    /// at a dead end are dropped. Returns the new graph and, for each of its
+    /// nodes, the node it was here.
+    pub fn contract(&self, keep: impl Fn(NodeId) -> bool) -> (Graph<E>, Vec<NodeId>) {
+        let kept: Vec<_> = self.nodes().filter(|node| keep(*node)).collect();
+        let mut new_ids = vec![None; self.len()];
+        for (idx, node) in kept.iter().enumerate() {
+            new_ids[node.0] = Some(NodeId(idx));
+        }
+
+        let mut contracted = Graph::with_nodes(kept.len());
+        for &start in kept.iter() {
+            'edges: for &(first, weight) in self.edges(start) {
+                let (mut prev, mut current, mut total) = (start, first, weight);
+                while new_ids[current.0].is_none() {
+                    let Some(&(next, weight)) =
+                        self.edges(current).iter().find(|(next, _)| *next != prev)
+                    else {
+                        continue 'edges;
+                    };
+                    (prev, current, total) = (current, next, total + weight);
+                }
+                if current != start {
+                    contracted.add_edge(
+                        new_ids[start.0].unwrap(),
+                        new_ids[current.0].unwrap(),
+                        total,
+                    );
+                }
+            }
+        }
+
+        (contracted, kept)
+    }
+
+    /// Heaviest route from `from` to `to`. `None` when `to` can't be reached,
+    /// or when a cycle can be reached from `from` and there is no longest route.
+    pub fn dag_longest_path(&self, from: NodeId, to: NodeId) -> Option<E> {
+        // depth first post order from `from`, reversed, is a topological order
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Open,
+            Done,
+        }
+        let mut marks = vec![Mark::New; self.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![(from, 0)];
+        marks[from.0] = Mark::Open;
+        while let Some((node, edge)) = stack.pop() {
+            match self.edges(node).get(edge) {
+                Some(&(next, _)) => {
+                    stack.push((node, edge + 1));
+                    match marks[next.0] {
+                        Mark::Open => return None,
+                        Mark::New => {
+                            marks[next.0] = Mark::Open;
+                            stack.push((next, 0));
+                        }
+                        Mark::Done => (),
+                    }
+                }
+                None => {
+                    marks[node.0] = Mark::Done;
+                    order.push(node);
+                }
+            }
+        }
+
+        let mut longest: Vec<Option<E>> = vec![None; self.len()];
+        longest[from.0] = Some(E::default());
+        for &node in order.iter().rev() {
+            let Some(so_far) = longest[node.0] else {
+                continue;
+            };
+            for &(next, weight) in self.edges(node) {
+                let through = so_far + weight;
+                if longest[next.0].is_none_or(|known| known < through) {
+                    longest[next.0] = Some(through);
+                }
+            }
+        }
+
+        longest[to.0]
+    }
+
+    /// Heaviest route from `from` to `to` that never revisits a node. This
+    /// tries every route, so keep it to small graphs, contracted if need be.
+    pub fn longest_simple_path(&self, from: NodeId, to: NodeId) -> Option<E> {
+        let mut visited = vec![false; self.len()];
+        visited[from.0] = true;
+        self.longest_from(from, to, &mut visited)
+    }
+
+    fn longest_from(&self, node: NodeId, to: NodeId, visited: &mut [bool]) -> Option<E> {
+        if node == to {
+            return Some(E::default());
+        }
+
+        let mut best = None;
+        for &(next, weight) in self.edges(node) {
+            if visited[next.0] {
+                continue;
+            }
+            visited[next.0] = true;
+            if let Some(rest) = self.longest_from(next, to, visited) {
+                best = best.max(Some(weight + rest));
+            }
+            visited[next.0] = false;
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 - 1 - 2 - 3, with 1 also joined straight to 3 and 4 off on its own
+    fn sample() -> Graph<usize> {
+        let mut graph = Graph::with_nodes(5);
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (1, 3)] {
+            graph.add_undirected_edge(NodeId(a), NodeId(b), 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn paths_and_components() {
+        let mut graph = sample();
+        assert_eq!(
+            graph.shortest_path(NodeId(0), NodeId(3)),
+            Some(vec![NodeId(0), NodeId(1), NodeId(3)])
+        );
+        assert_eq!(graph.shortest_path(NodeId(0), NodeId(4)), None);
+
+        assert!(graph.remove_undirected_edge(NodeId(0), NodeId(1)));
+        assert!(!graph.remove_undirected_edge(NodeId(0), NodeId(1)));
+        let sizes: Vec<_> = graph.components().iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![1, 3, 1]);
+    }
+
+    #[test]
+    fn corridors_contract() {
+        let graph = sample();
+        let (contracted, kept) = graph.contract(|node| node != NodeId(2));
+        assert_eq!(kept, vec![NodeId(0), NodeId(1), NodeId(3), NodeId(4)]);
+        // 1 - 3 directly, and 1 - 2 - 3 as one edge of weight 2
+        assert_eq!(
+            contracted.edges(NodeId(1)),
+            &[(NodeId(0), 1), (NodeId(2), 2), (NodeId(2), 1)]
+        );
+        assert_eq!(
+            contracted.longest_simple_path(NodeId(0), NodeId(2)),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn longest_routes() {
+        let mut dag = Graph::with_nodes(4);
+        dag.add_edge(NodeId(0), NodeId(1), 5);
+        dag.add_edge(NodeId(0), NodeId(2), 1);
+        dag.add_edge(NodeId(1), NodeId(3), 1);
+        dag.add_edge(NodeId(2), NodeId(3), 10);
+        assert_eq!(dag.dag_longest_path(NodeId(0), NodeId(3)), Some(11));
+        assert_eq!(dag.dag_longest_path(NodeId(3), NodeId(0)), None);
+
+        dag.add_edge(NodeId(3), NodeId(0), 1);
+        assert_eq!(dag.dag_longest_path(NodeId(0), NodeId(3)), None);
+        assert_eq!(sample().longest_simple_path(NodeId(0), NodeId(3)), Some(3));
+    }
+}
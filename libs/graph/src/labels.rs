@@ -0,0 +1,120 @@
+use crate::NodeId;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Gives each distinct key a [`NodeId`], in the order the keys are first seen
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "Vec<K>",
+        into = "Vec<K>",
+        bound(
+            serialize = "K: Clone + serde::Serialize",
+            deserialize = "K: Clone + Eq + Hash + serde::Deserialize<'de>"
+        )
+    )
+)]
+pub struct Labels<K> {
+    keys: Vec<K>,
+    ids: HashMap<K, NodeId>,
+}
+
+impl<K> Default for Labels<K> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+/// Keys and ids go together, so comparing the keys in order is enough
+impl<K: PartialEq> PartialEq for Labels<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keys == other.keys
+    }
+}
+
+impl<K: Eq> Eq for Labels<K> {}
+
+impl<K: Clone + Eq + Hash> Labels<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id for `key`, giving it the next one if it is new
+    pub fn intern(&mut self, key: K) -> NodeId {
+        if let Some(id) = self.ids.get(&key) {
+            return *id;
+        }
+        let id = NodeId::from_index(self.keys.len());
+        self.keys.push(key.clone());
+        self.ids.insert(key, id);
+        id
+    }
+
+    pub fn id<Q>(&self, key: &Q) -> Option<NodeId>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.ids.get(key).copied()
+    }
+
+    pub fn key(&self, id: NodeId) -> &K {
+        &self.keys[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Every key with its id, in id order
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &K)> {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (NodeId::from_index(idx), key))
+    }
+}
+
+impl<K: Clone + Eq + Hash> From<Vec<K>> for Labels<K> {
+    fn from(keys: Vec<K>) -> Self {
+        let mut labels = Self::new();
+        for key in keys {
+            labels.intern(key);
+        }
+        labels
+    }
+}
+
+impl<K> From<Labels<K>> for Vec<K> {
+    fn from(labels: Labels<K>) -> Self {
+        labels.keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_seen_first_numbered() {
+        let mut labels = Labels::new();
+        let aaa = labels.intern("AAA".to_string());
+        let zzz = labels.intern("ZZZ".to_string());
+        assert_eq!(labels.intern("AAA".to_string()), aaa);
+        assert_eq!((aaa.index(), zzz.index()), (0, 1));
+        assert_eq!(labels.id("ZZZ"), Some(zzz));
+        assert_eq!(labels.id("BBB"), None);
+        assert_eq!(labels.key(zzz), "ZZZ");
+        assert_eq!(Labels::from(Vec::from(labels.clone())), labels);
+    }
+}
@@ -0,0 +1,9 @@
+mod cut;
+mod graph;
+mod labels;
+mod search;
+
+pub use cut::Cut;
+pub use graph::{Graph, NodeId};
+pub use labels::Labels;
+pub use search::{astar, bfs, dijkstra};
@@ -0,0 +1,170 @@
+//! Searches over implicit state spaces, where `successors` works out the
+//! moves from a state on the fly rather than reading them from a [`Graph`].
+//!
+//! [`Graph`]: crate::Graph
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Fewest moves from `start` to a state matching `goal`, as the states
+/// visited along the way, `start` and the goal included
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut parents: HashMap<S, Option<S>> = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            let mut path = vec![state];
+            while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+                path.push(parent.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for next in successors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// A state waiting in the queue, ordered so the heap pops the lowest estimate first
+struct Entry<S, C> {
+    estimate: C,
+    cost: C,
+    state: S,
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+/// Cheapest route from `start` to a state matching `goal`, returning that
+/// state and the cost. `heuristic` must never overestimate the remaining cost.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<(S, C)>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut best = HashMap::from([(start.clone(), C::default())]);
+    let mut queue = BinaryHeap::from([Entry {
+        estimate: heuristic(&start),
+        cost: C::default(),
+        state: start,
+    }]);
+
+    while let Some(Entry { cost, state, .. }) = queue.pop() {
+        if best.get(&state).is_some_and(|known| *known < cost) {
+            // already reached more cheaply
+            continue;
+        }
+        if goal(&state) {
+            return Some((state, cost));
+        }
+
+        for (next, step) in successors(&state) {
+            let next_cost = cost + step;
+            if best.get(&next).is_some_and(|known| *known <= next_cost) {
+                continue;
+            }
+            best.insert(next.clone(), next_cost);
+            queue.push(Entry {
+                estimate: next_cost + heuristic(&next),
+                cost: next_cost,
+                state: next,
+            });
+        }
+    }
+
+    None
+}
+
+/// [`astar`] without a heuristic
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<(S, C)>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), goal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Moves on a number line: +1 costs 1, *2 costs 3
+    fn moves(n: &u32) -> Vec<(u32, u32)> {
+        vec![(n + 1, 1), (n * 2, 3)]
+    }
+
+    #[test]
+    fn fewest_moves() {
+        let path = bfs(1u32, |n| [n + 1, n * 2], |n| *n == 10).unwrap();
+        assert_eq!(path, vec![1, 2, 4, 5, 10]);
+        assert_eq!(
+            bfs(1u32, |n| (*n < 100).then_some(n * 2), |n| *n == 3),
+            None
+        );
+    }
+
+    #[test]
+    fn cheapest_route() {
+        // 1 -> 2 -> 3 -> 4 -> 8 -> 16 costs 9, beating 15 single steps
+        assert_eq!(dijkstra(1u32, moves, |n| *n == 16), Some((16, 9)));
+        assert_eq!(
+            astar(
+                1u32,
+                moves,
+                |n| 16u32.saturating_sub(*n).min(1),
+                |n| *n == 16
+            ),
+            Some((16, 9))
+        );
+        assert_eq!(
+            dijkstra(20u32, |n| (*n < 30).then_some((n + 1, 1u32)), |n| *n == 10),
+            None
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::Point;
+
+/// A compass heading on a grid, north being towards row 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    N,
+    E,
+    S,
+    W,
+}
+
+impl Direction {
+    /// Clockwise from north
+    pub const ALL: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::N => Direction::S,
+            Direction::E => Direction::W,
+            Direction::S => Direction::N,
+            Direction::W => Direction::E,
+        }
+    }
+
+    /// A quarter turn clockwise
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::N => Direction::E,
+            Direction::E => Direction::S,
+            Direction::S => Direction::W,
+            Direction::W => Direction::N,
+        }
+    }
+
+    /// A quarter turn anticlockwise
+    pub fn turn_left(self) -> Self {
+        self.turn_right().opposite()
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::N | Direction::S)
+    }
+
+    /// Change in (row, col) for one step
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::N => (-1, 0),
+            Direction::E => (0, 1),
+            Direction::S => (1, 0),
+            Direction::W => (0, -1),
+        }
+    }
+
+    /// One step from `point`, `None` when that would go above row 0 or left of column 0
+    pub fn step(self, point: Point) -> Option<Point> {
+        let (drow, dcol) = self.offset();
+        Some((
+            point.0.checked_add_signed(drow)?,
+            point.1.checked_add_signed(dcol)?,
+        ))
+    }
+
+    /// One step on an unbounded plane
+    pub fn step_signed(self, point: (isize, isize)) -> (isize, isize) {
+        let (drow, dcol) = self.offset();
+        (point.0 + drow, point.1 + dcol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+        }
+        assert_eq!(Direction::N.turn_left(), Direction::W);
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(Direction::N.step((0, 3)), None);
+        assert_eq!(Direction::W.step((2, 3)), Some((2, 2)));
+        assert_eq!(Direction::S.step_signed((-1, 0)), (0, 0));
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{Direction, Point};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Why rows couldn't be put together into a grid, rows are zero based and
+/// left out of the message so it can sit on the row's own line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingRow(usize),
+    Empty,
+}
+
+impl GridError {
+    /// The row the problem was found on
+    pub fn row(&self) -> usize {
+        match self {
+            GridError::Ragged { row, .. } | GridError::MissingRow(row) => *row,
+            GridError::Empty => 0,
+        }
+    }
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Ragged {
+                expected, found, ..
+            } => write!(f, "{found} wide, the other rows are {expected}"),
+            GridError::MissingRow(_) => write!(f, "no row here"),
+            GridError::Empty => write!(f, "no rows"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A dense rectangle of cells, addressed by (row, col)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            rows: 0,
+            cols: 0,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Self {
+        Self {
+            cells: vec![fill; rows * cols],
+            rows,
+            cols,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows<I: IntoIterator<Item = Vec<T>>>(rows: I) -> Result<Self, GridError> {
+        let mut grid = Self::default();
+        for row in rows {
+            grid.push_row(row)?;
+        }
+        Ok(grid)
+    }
+
+    /// Add a row to the bottom, the first one sets the width
+    pub fn push_row(&mut self, cells: Vec<T>) -> Result<(), GridError> {
+        if self.rows > 0 && cells.len() != self.cols {
+            return Err(GridError::Ragged {
+                row: self.rows,
+                expected: self.cols,
+                found: cells.len(),
+            });
+        }
+        self.cols = cells.len();
+        self.cells.extend(cells);
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.0 < self.rows && point.1 < self.cols
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point)
+            .then(|| &self.cells[point.0 * self.cols + point.1])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        match self.contains(point) {
+            true => Some(&mut self.cells[point.0 * self.cols + point.1]),
+            false => None,
+        }
+    }
+
+    /// Like `get`, for points that may have wandered off the top or left
+    pub fn get_signed(&self, point: (isize, isize)) -> Option<&T> {
+        self.get((
+            usize::try_from(point.0).ok()?,
+            usize::try_from(point.1).ok()?,
+        ))
+    }
+
+    /// The cell at `point` on an infinite plane tiled with copies of this grid
+    pub fn get_wrapped(&self, point: (isize, isize)) -> &T {
+        let row = point.0.rem_euclid(self.rows as isize) as usize;
+        let col = point.1.rem_euclid(self.cols as isize) as usize;
+        &self[(row, col)]
+    }
+
+    /// One step from `point`, if that stays on the grid
+    pub fn step(&self, point: Point, dir: Direction) -> Option<Point> {
+        dir.step(point).filter(|next| self.contains(*next))
+    }
+
+    /// The up to four points sharing an edge with `point`, clockwise from north
+    pub fn neighbours(&self, point: Point) -> impl Iterator<Item = (Direction, Point)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(point, dir).map(|next| (dir, next)))
+    }
+
+    /// The up to eight points touching `point`, diagonals included
+    pub fn adjacent(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        (-1..=1isize)
+            .flat_map(|drow| (-1..=1isize).map(move |dcol| (drow, dcol)))
+            .filter(|offset| *offset != (0, 0))
+            .filter_map(move |(drow, dcol)| {
+                let next = (
+                    point.0.checked_add_signed(drow)?,
+                    point.1.checked_add_signed(dcol)?,
+                );
+                self.contains(next).then_some(next)
+            })
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Every cell with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| ((idx / self.cols, idx % self.cols), cell))
+    }
+
+    /// Positions of the cells matching `pred`, row by row
+    pub fn positions<'a>(
+        &'a self,
+        pred: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Point> + 'a {
+        self.iter()
+            .filter(move |(_, cell)| pred(cell))
+            .map(|(point, _)| point)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        assert!(
+            self.contains(point),
+            "{point:?} is outside the {}x{} grid",
+            self.rows,
+            self.cols
+        );
+        &self.cells[point.0 * self.cols + point.1]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        assert!(
+            self.contains(point),
+            "{point:?} is outside the {}x{} grid",
+            self.rows,
+            self.cols
+        );
+        &mut self.cells[point.0 * self.cols + point.1]
+    }
+}
+
+/// Collects rows as they arrive, in any order, until the grid is complete
+#[derive(Debug, Clone)]
+pub struct GridBuilder<T> {
+    rows: Vec<Option<Vec<T>>>,
+    cols: Option<usize>,
+}
+
+impl<T> Default for GridBuilder<T> {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            cols: None,
+        }
+    }
+}
+
+impl<T> GridBuilder<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put in row number `row`, which has to be as wide as the rows before it
+    pub fn insert_row(&mut self, row: usize, cells: Vec<T>) -> Result<(), GridError> {
+        match self.cols {
+            Some(cols) if cols != cells.len() => {
+                return Err(GridError::Ragged {
+                    row,
+                    expected: cols,
+                    found: cells.len(),
+                })
+            }
+            _ => self.cols = Some(cells.len()),
+        }
+
+        if self.rows.len() <= row {
+            self.rows.resize_with(row + 1, || None);
+        }
+        self.rows[row] = Some(cells);
+        Ok(())
+    }
+
+    /// A cell of a row that has already arrived
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.rows.get(point.0)?.as_ref()?.get(point.1)
+    }
+
+    pub fn build(self) -> Result<Grid<T>, GridError> {
+        if self.rows.is_empty() {
+            return Err(GridError::Empty);
+        }
+
+        let mut grid = Grid::default();
+        for (row, cells) in self.rows.into_iter().enumerate() {
+            grid.push_row(cells.ok_or(GridError::MissingRow(row))?)?;
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::from_rows(["ab".chars().collect(), "cd".chars().collect()]).unwrap()
+    }
+
+    #[test]
+    fn bounds() {
+        let grid = sample();
+        assert_eq!(grid.get((1, 0)), Some(&'c'));
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid.get_signed((-1, 0)), None);
+        assert_eq!(grid.step((0, 0), Direction::E), Some((0, 1)));
+        assert_eq!(grid.step((0, 1), Direction::E), None);
+    }
+
+    #[test]
+    fn tiled() {
+        let grid = sample();
+        assert_eq!(*grid.get_wrapped((-1, -1)), 'd');
+        assert_eq!(*grid.get_wrapped((4, 3)), 'b');
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let grid = Grid::new(3, 3, 0);
+        let edge: Vec<_> = grid.neighbours((0, 1)).collect();
+        assert_eq!(
+            edge,
+            vec![
+                (Direction::E, (0, 2)),
+                (Direction::S, (1, 1)),
+                (Direction::W, (0, 0))
+            ]
+        );
+        assert_eq!(grid.adjacent((1, 1)).count(), 8);
+        assert_eq!(grid.adjacent((2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn rows_in_any_order() {
+        let mut builder = GridBuilder::new();
+        builder.insert_row(1, vec![3, 4]).unwrap();
+        assert_eq!(builder.get((1, 1)), Some(&4));
+        assert_eq!(builder.get((0, 0)), None);
+        assert_eq!(
+            builder.insert_row(2, vec![5]),
+            Err(GridError::Ragged {
+                row: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+        builder.insert_row(0, vec![1, 2]).unwrap();
+
+        let grid = builder.build().unwrap();
+        assert_eq!(grid.row(0), &[1, 2]);
+        assert_eq!(
+            grid.positions(|v| v % 2 == 0).collect::<Vec<_>>(),
+            [(0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn gaps_are_reported() {
+        let mut builder = GridBuilder::new();
+        builder.insert_row(1, vec!['x']).unwrap();
+        assert_eq!(builder.build(), Err(GridError::MissingRow(0)));
+        assert_eq!(GridBuilder::<u8>::new().build(), Err(GridError::Empty));
+    }
+}
@@ -0,0 +1,8 @@
+mod direction;
+mod grid;
+
+pub use direction::Direction;
+pub use grid::{Grid, GridBuilder, GridError};
+
+/// A position on a grid as (row, col)
+pub type Point = (usize, usize);
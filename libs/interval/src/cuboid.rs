@@ -0,0 +1,79 @@
+use crate::{Coord, Interval};
+
+/// A box with an [`Interval`] along each of its `N` axes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<T, const N: usize> {
+    axes: [Interval<T>; N],
+}
+
+impl<T: Coord, const N: usize> Cuboid<T, N> {
+    pub fn new(axes: [Interval<T>; N]) -> Self {
+        Self { axes }
+    }
+
+    pub fn axis(&self, axis: usize) -> Interval<T> {
+        self.axes[axis]
+    }
+
+    /// How many points are inside
+    pub fn volume(&self) -> T {
+        self.axes
+            .iter()
+            .fold(T::ONE, |volume, interval| volume * interval.len())
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.axes
+            .iter()
+            .zip(other.axes.iter())
+            .all(|(a, b)| a.overlaps(b))
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.axes;
+        for (axis, interval) in axes.iter_mut().enumerate() {
+            *interval = interval.intersect(&other.axes[axis])?;
+        }
+        Some(Self { axes })
+    }
+
+    /// The part below `at` along `axis` and the part from `at` on
+    pub fn split_at(&self, axis: usize, at: T) -> (Option<Self>, Option<Self>) {
+        let (below, above) = self.axes[axis].split_at(at);
+        let with = |interval: Interval<T>| {
+            let mut axes = self.axes;
+            axes[axis] = interval;
+            Self { axes }
+        };
+        (below.map(with), above.map(with))
+    }
+
+    /// Slide along `axis` so that `from` lands on `to`
+    pub fn translate(&self, axis: usize, from: T, to: T) -> Self {
+        let mut axes = self.axes;
+        axes[axis] = axes[axis].translate(from, to);
+        Self { axes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyper_rectangles() {
+        let cube = Cuboid::new([Interval::inclusive(1usize, 4000); 4]);
+        assert_eq!(cube.volume(), 4000usize.pow(4));
+
+        let (low, high) = cube.split_at(2, 1001);
+        let (low, high) = (low.unwrap(), high.unwrap());
+        assert_eq!(low.volume() + high.volume(), cube.volume());
+        assert_eq!(low.axis(2), Interval::inclusive(1, 1000));
+        assert!(!low.overlaps(&high));
+        assert_eq!(cube.intersect(&high), Some(high));
+
+        let moved = low.translate(2, 1, 5000);
+        assert_eq!(moved.axis(2).start(), 5000);
+        assert_eq!(moved.intersect(&cube), None);
+    }
+}
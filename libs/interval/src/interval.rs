@@ -0,0 +1,146 @@
+use std::fmt::Debug;
+use std::ops::{Add, Mul, Sub};
+
+/// Integer types an [`Interval`] can be made of
+pub trait Coord:
+    Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+macro_rules! coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+coord!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The values from `start` up to but not including `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+impl<T: Coord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "interval ends at {end:?} before {start:?}");
+        Self { start, end }
+    }
+
+    /// From `first` to `last`, both included
+    pub fn inclusive(first: T, last: T) -> Self {
+        Self::new(first, last + T::ONE)
+    }
+
+    pub fn with_len(start: T, len: T) -> Self {
+        Self::new(start, start + len)
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    /// The highest value in the interval, which mustn't be empty
+    pub fn last(&self) -> T {
+        assert!(!self.is_empty(), "an empty interval has no last value");
+        self.end - T::ONE
+    }
+
+    pub fn len(&self) -> T {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        (self.start <= value) & (value < self.end)
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        (self.start < other.end) & (other.start < self.end)
+    }
+
+    /// The values in both, `None` if there are none
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start < end).then_some(Self { start, end })
+    }
+
+    /// What is left of this interval below and above `other`
+    pub fn subtract(&self, other: &Self) -> (Option<Self>, Option<Self>) {
+        if !self.overlaps(other) {
+            return match self.end <= other.start {
+                true => (self.non_empty(), None),
+                false => (None, self.non_empty()),
+            };
+        }
+        (
+            Self::new(self.start, other.start.max(self.start)).non_empty(),
+            Self::new(other.end.min(self.end), self.end).non_empty(),
+        )
+    }
+
+    /// The values below `at` and those from `at` on
+    pub fn split_at(&self, at: T) -> (Option<Self>, Option<Self>) {
+        let at = at.clamp(self.start, self.end);
+        (
+            Self::new(self.start, at).non_empty(),
+            Self::new(at, self.end).non_empty(),
+        )
+    }
+
+    /// Slide the interval so that `from` lands on `to`. Takes both ends of
+    /// the move rather than an offset so unsigned types can move down.
+    pub fn translate(&self, from: T, to: T) -> Self {
+        match to >= from {
+            true => Self::new(self.start + (to - from), self.end + (to - from)),
+            false => Self::new(self.start - (from - to), self.end - (from - to)),
+        }
+    }
+
+    fn non_empty(self) -> Option<Self> {
+        (!self.is_empty()).then_some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pieces() {
+        let a = Interval::with_len(1u64, 5);
+        let b = Interval::inclusive(2, 3);
+
+        assert_eq!(a.intersect(&b), Some(Interval::new(2, 4)));
+        assert_eq!(
+            a.subtract(&b),
+            (Some(Interval::new(1, 2)), Some(Interval::new(4, 6)))
+        );
+        assert_eq!(b.subtract(&a), (None, None));
+        assert_eq!(a.split_at(0), (None, Some(a)));
+        assert_eq!(a.last(), 5);
+        assert!(!Interval::new(1, 2).overlaps(&Interval::new(2, 3)));
+    }
+
+    #[test]
+    fn translated() {
+        let a = Interval::new(10usize, 20);
+        assert_eq!(a.translate(12, 2), Interval::new(0, 10));
+        assert_eq!(a.translate(2, 12), Interval::new(20, 30));
+    }
+}
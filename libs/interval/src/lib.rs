@@ -0,0 +1,7 @@
+mod cuboid;
+mod interval;
+mod set;
+
+pub use cuboid::Cuboid;
+pub use interval::{Coord, Interval};
+pub use set::IntervalSet;
@@ -0,0 +1,233 @@
+use crate::{Coord, Interval};
+
+/// A set of values kept as sorted intervals that neither overlap nor touch
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T: Coord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        *self = self.union(&Self::from(interval));
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many values are in the set
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::ZERO, |total, interval| total + interval.len())
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(Interval::start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self
+            .intervals
+            .partition_point(|interval| interval.end() <= value);
+        self.intervals
+            .get(idx)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (0, 0);
+        while let (Some(x), Some(y)) = (self.intervals.get(a), other.intervals.get(b)) {
+            intervals.extend(x.intersect(y));
+            // whichever ends first can't meet anything further on
+            match x.end() <= y.end() {
+                true => a += 1,
+                false => b += 1,
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn subtract(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let mut b = 0;
+        for x in self.intervals.iter() {
+            let mut rest = Some(*x);
+            // skip what ends before this interval starts
+            while other.intervals.get(b).is_some_and(|y| y.end() <= x.start()) {
+                b += 1;
+            }
+            let mut idx = b;
+            while let (Some(left), Some(y)) = (rest, other.intervals.get(idx)) {
+                if y.start() >= left.end() {
+                    break;
+                }
+                let (below, above) = left.subtract(y);
+                intervals.extend(below);
+                rest = above;
+                idx += 1;
+            }
+            intervals.extend(rest);
+        }
+        Self { intervals }
+    }
+
+    /// Slide every interval so that `from` lands on `to`
+    pub fn translate(&self, from: T, to: T) -> Self {
+        Self {
+            intervals: self
+                .intervals
+                .iter()
+                .map(|interval| interval.translate(from, to))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Coord> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        std::iter::once(interval).collect()
+    }
+}
+
+impl<T: Coord> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect();
+        sorted.sort_unstable_by_key(Interval::start);
+
+        let mut intervals: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.start() <= last.end() => {
+                    *last = Interval::new(last.start(), last.end().max(interval.end()));
+                }
+                _ => intervals.push(interval),
+            }
+        }
+        Self { intervals }
+    }
+}
+
+impl<T: Coord> Extend<Interval<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = Interval<T>>>(&mut self, iter: I) {
+        *self = self.intervals.iter().copied().chain(iter).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    /// Every value in the set, one by one
+    fn values(set: &IntervalSet<i32>) -> BTreeSet<i32> {
+        set.intervals()
+            .iter()
+            .flat_map(|interval| interval.start()..interval.end())
+            .collect()
+    }
+
+    fn normalised(set: &IntervalSet<i32>) -> bool {
+        set.intervals().iter().all(|interval| !interval.is_empty())
+            && set
+                .intervals()
+                .windows(2)
+                .all(|pair| pair[0].end() < pair[1].start())
+    }
+
+    fn arb_set() -> impl Strategy<Value = IntervalSet<i32>> {
+        prop::collection::vec((-20i32..20, 0i32..10), 0..6).prop_map(|pieces| {
+            pieces
+                .into_iter()
+                .map(|(start, len)| Interval::with_len(start, len))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn merges_touching() {
+        let merged = set(&[(5, 7), (0, 2), (2, 3), (6, 9), (4, 4)]);
+        assert_eq!(merged.intervals(), set(&[(0, 3), (5, 9)]).intervals());
+        assert_eq!(merged.len(), 7);
+        assert!(merged.contains(2) && !merged.contains(3) && merged.contains(8));
+        assert_eq!(merged.subtract(&set(&[(1, 6)])), set(&[(0, 1), (6, 9)]));
+    }
+
+    proptest! {
+        #[test]
+        fn algebra_matches_values(a in arb_set(), b in arb_set()) {
+            let (va, vb) = (values(&a), values(&b));
+
+            let union = a.union(&b);
+            let intersection = a.intersect(&b);
+            let difference = a.subtract(&b);
+            prop_assert!(normalised(&union) && normalised(&intersection) && normalised(&difference));
+            prop_assert_eq!(values(&union), &va | &vb);
+            prop_assert_eq!(values(&intersection), &va & &vb);
+            prop_assert_eq!(values(&difference), &va - &vb);
+            prop_assert_eq!(union.len() as usize, (&va | &vb).len());
+        }
+
+        #[test]
+        fn laws(a in arb_set(), b in arb_set(), c in arb_set()) {
+            prop_assert_eq!(a.union(&b), b.union(&a));
+            prop_assert_eq!(a.intersect(&b), b.intersect(&a));
+            prop_assert_eq!(
+                a.intersect(&b.union(&c)),
+                a.intersect(&b).union(&a.intersect(&c))
+            );
+            // what is taken away and what is left add back up to the whole
+            prop_assert_eq!(a.subtract(&b).union(&a.intersect(&b)), a.clone());
+            prop_assert!(a.subtract(&b).intersect(&b).is_empty());
+        }
+
+        #[test]
+        fn translate_round_trips(a in arb_set(), from in -50i32..50, to in -50i32..50) {
+            let moved = a.translate(from, to);
+            prop_assert_eq!(moved.len(), a.len());
+            prop_assert_eq!(moved.translate(to, from), a);
+        }
+
+        #[test]
+        fn membership(a in arb_set(), value in -30i32..40) {
+            prop_assert_eq!(a.contains(value), values(&a).contains(&value));
+        }
+    }
+}
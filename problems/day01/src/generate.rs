@@ -0,0 +1,43 @@
+use crate::Day01;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const SPELLED: [&str; 12] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "1", "2", "7",
+];
+const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// `lines` calibration lines of up to `width` random characters, with
+/// spelled out numbers sprinkled in
+impl Generator for Day01 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let lines = size.lines_or(100_000);
+        let width = size.width_or(500).max(2);
+        let mut out = String::new();
+
+        for _ in 0..lines {
+            let length = rng.gen_range(2..=width);
+            let mut line: String = (0..length - 1)
+                .map(|_| *CHARS.choose(rng).unwrap() as char)
+                .collect();
+
+            // every line needs at least one real digit for part 1
+            let pos = rng.gen_range(0..=line.len());
+            line.insert(pos, char::from(b'0' + rng.gen_range(0..10)));
+
+            for number in SPELLED {
+                if rng.gen_bool(0.5) {
+                    let pos = rng.gen_range(0..=line.len());
+                    line.insert_str(pos, number);
+                }
+            }
+
+            out.push_str(&line);
+            out.push('\n');
+        }
+
+        out
+    }
+}
@@ -1,6 +1,14 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
 use rustc_hash::FxHashMap;
 use tokio::sync::mpsc::Receiver;
 
+const DAY: &str = "01";
+
 struct WordTrace<'a> {
     start_pos: usize,
     word: &'a str,
@@ -8,19 +16,14 @@ struct WordTrace<'a> {
     length: usize,
 }
 
-async fn find_number_part1<I>(iter: I) -> Option<char>
+fn find_number_part1<I>(mut iter: I) -> Option<char>
 where
     I: Iterator<Item = char>,
 {
-    for chr in iter {
-        if chr.is_ascii_digit() {
-            return Some(chr);
-        }
-    }
-    None
+    iter.find(|chr| chr.is_ascii_digit())
 }
 
-async fn find_number_part2<I>(
+fn find_number_part2<I>(
     iter: I,
     numbers: &FxHashMap<&str, char>,
     reverse_words: bool,
@@ -76,8 +79,15 @@ where
     None
 }
 
-async fn calc_line(line: String) -> (u32, u32) {
-    let number_pairs = [
+/// The calibration lines, each known to hold at least one digit or spelled out
+/// number. Part 1 only reads digits, so it checks for them itself.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Document {
+    lines: Vec<String>,
+}
+
+fn number_words() -> FxHashMap<&'static str, char> {
+    [
         ("one", '1'),
         ("two", '2'),
         ("three", '3'),
@@ -97,48 +107,88 @@ async fn calc_line(line: String) -> (u32, u32) {
         ("8", '8'),
         ("9", '9'),
         ("0", '0'),
-    ];
-
-    let numbers: FxHashMap<&str, char> = number_pairs.into_iter().collect();
-
-    let first_digit_part1 = find_number_part1(line.chars()).await.unwrap();
-    let last_digit_part1 = find_number_part1(line.chars().rev()).await.unwrap();
-
-    let first_digit_part2 = find_number_part2(line.chars(), &numbers, false)
-        .await
-        .unwrap();
-    let last_digit_part2 = find_number_part2(line.chars().rev(), &numbers, true)
-        .await
-        .unwrap();
-
-    (
-        format!("{first_digit_part1}{last_digit_part1}")
-            .parse::<u32>()
-            .unwrap(),
-        format!("{first_digit_part2}{last_digit_part2}")
-            .parse::<u32>()
-            .unwrap(),
-    )
+    ]
+    .into_iter()
+    .collect()
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
-    let mut total_part1 = 0;
-    let mut total_part2 = 0;
+async fn check_line(line_no: usize, line: String) -> Result<String, ParseError> {
+    match find_number_part2(line.chars(), &number_words(), false) {
+        Some(_) => Ok(line),
+        None => Err(InputLine::new(DAY, line_no, &line).error("no digit or number on line")),
+    }
+}
+
+fn calibration_value(first: char, last: char) -> u32 {
+    format!("{first}{last}").parse::<u32>().unwrap()
+}
 
+pub async fn parse(mut rx: Receiver<String>) -> Result<Document, SolveError> {
     let mut tasks = Vec::new();
+    let mut line_no = 0;
 
     while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(line).await });
+        tasks.push(spawn_line(line_no, async move {
+            check_line(line_no, line).await
+        }));
+        line_no += 1;
+    }
 
-        tasks.push(task);
+    let mut lines = Vec::new();
+    for result in join_tasks(tasks).await? {
+        lines.push(result?);
     }
 
-    for task in tasks {
-        if let Ok((delta1, delta2)) = task.await {
-            total_part1 += delta1;
-            total_part2 += delta2;
-        }
+    Ok(Document { lines })
+}
+
+pub fn part1(document: &Document) -> Result<u32, SolveError> {
+    let mut total = 0;
+    for (line_no, line) in document.lines.iter().enumerate() {
+        let (Some(first), Some(last)) = (
+            find_number_part1(line.chars()),
+            find_number_part1(line.chars().rev()),
+        ) else {
+            let input = InputLine::new(DAY, line_no, line);
+            return Err(input
+                .error("no digit on line, part 1 doesn't read spelled out numbers")
+                .into());
+        };
+        total += calibration_value(first, last);
+    }
+    Ok(total)
+}
+
+pub fn part2(document: &Document) -> u32 {
+    let numbers = number_words();
+    document
+        .lines
+        .iter()
+        .map(|line| {
+            let first = find_number_part2(line.chars(), &numbers, false).unwrap();
+            let last = find_number_part2(line.chars().rev(), &numbers, true).unwrap();
+            calibration_value(first, last)
+        })
+        .sum()
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let document = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&document)).transpose()?,
+        parts.part2().then(|| part2(&document)),
+    ))
+}
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    println!("Part 1: {total_part1} Part 2: {total_part2}");
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
@@ -0,0 +1,33 @@
+use crate::Day02;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// `lines` games of up to `width` draws each
+impl Generator for Day02 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let games = size.lines_or(100_000);
+        let max_draws = size.width_or(10).max(1);
+        let mut colours = ["green", "red", "blue"];
+        let mut out = String::new();
+
+        for game in 1..=games {
+            let draws: Vec<String> = (0..rng.gen_range(1..=max_draws))
+                .map(|_| {
+                    colours.shuffle(rng);
+                    let count = rng.gen_range(1..=colours.len());
+                    colours[..count]
+                        .iter()
+                        .map(|colour| format!("{} {colour}", rng.gen_range(1..=15)))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect();
+
+            out.push_str(&format!("Game {game}: {}\n", draws.join("; ")));
+        }
+
+        out
+    }
+}
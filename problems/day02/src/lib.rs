@@ -1,6 +1,15 @@
-use std::str::FromStr;
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
 use tokio::sync::mpsc::Receiver;
 
+const DAY: &str = "02";
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct CubeTally {
     red: usize,
     green: usize,
@@ -13,23 +22,21 @@ impl CubeTally {
     }
 }
 
-impl FromStr for CubeTally {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl CubeTally {
+    fn parse(input: &InputLine, draw: &str) -> Result<Self, ParseError> {
         let mut tally = CubeTally {
             red: 0,
             green: 0,
             blue: 0,
         };
 
-        for colour_pair in s.split(", ") {
-            let (count, colour) = colour_pair.trim().split_once(' ').unwrap();
+        for colour_pair in draw.split(", ") {
+            let (count, colour) = input.split_once(colour_pair.trim(), " ")?;
             match colour {
-                "red" => tally.red = count.parse::<usize>().unwrap(),
-                "green" => tally.green = count.parse::<usize>().unwrap(),
-                "blue" => tally.blue = count.parse::<usize>().unwrap(),
-                _ => panic!("invalid colour name input"),
+                "red" => tally.red = input.parse(count)?,
+                "green" => tally.green = input.parse(count)?,
+                "blue" => tally.blue = input.parse(count)?,
+                _ => return Err(input.error_at(colour, "invalid colour name")),
             };
         }
 
@@ -37,62 +44,110 @@ impl FromStr for CubeTally {
     }
 }
 
-async fn calc_line(line: String) -> (u32, usize) {
+/// One game and the handfuls of cubes drawn in it
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    id: u32,
+    draws: Vec<CubeTally>,
+}
+
+impl Game {
+    fn power(&self) -> usize {
+        let mut min_red = 0;
+        let mut min_blue = 0;
+        let mut min_green = 0;
+
+        for tally in self.draws.iter() {
+            min_red = std::cmp::max(min_red, tally.red);
+            min_blue = std::cmp::max(min_blue, tally.blue);
+            min_green = std::cmp::max(min_green, tally.green);
+        }
+
+        min_red * min_blue * min_green
+    }
+}
+
+async fn parse_line(line_no: usize, line: String) -> Result<Game, ParseError> {
+    let input = InputLine::new(DAY, line_no, &line);
+    let (game, draws) = input.split_once(&line, ":")?;
+
+    let draws: Vec<CubeTally> = draws
+        .split(';')
+        .map(|s| CubeTally::parse(&input, s))
+        .collect::<Result<_, _>>()?;
+    let id = input.parse(input.split_once(game.trim(), " ")?.1)?;
+
+    Ok(Game { id, draws })
+}
+
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Game>, SolveError> {
+    let mut tasks = Vec::new();
+    let mut line_no = 0;
+
+    while let Some(line) = rx.recv().await {
+        tasks.push(spawn_line(line_no, async move {
+            parse_line(line_no, line).await
+        }));
+        line_no += 1;
+    }
+
+    let mut games = Vec::new();
+    for result in join_tasks(tasks).await? {
+        games.push(result?);
+    }
+    Ok(games)
+}
+
+pub fn part1(games: &[Game]) -> u32 {
     let ref_cubes = CubeTally {
         red: 12,
         green: 13,
         blue: 14,
     };
 
-    let (game, draws) = line.split_once(':').unwrap();
-
-    let cube_tallies: Vec<CubeTally> = draws
-        .split(';')
-        .map(|s| CubeTally::from_str(s).expect("couldn't parse tally"))
-        .collect();
-
-    let all_valid = cube_tallies.iter().all(|c| ref_cubes.sufficient(c));
-
-    let score = if all_valid {
-        game.trim()
-            .split_once(' ')
-            .unwrap()
-            .1
-            .parse::<u32>()
-            .unwrap()
-    } else {
-        0
-    };
+    games
+        .iter()
+        .filter(|game| game.draws.iter().all(|c| ref_cubes.sufficient(c)))
+        .map(|game| game.id)
+        .sum()
+}
 
-    let mut min_red = 0;
-    let mut min_blue = 0;
-    let mut min_green = 0;
+pub fn part2(games: &[Game]) -> usize {
+    games.iter().map(Game::power).sum()
+}
 
-    for tally in cube_tallies.iter() {
-        min_red = std::cmp::max(min_red, tally.red);
-        min_blue = std::cmp::max(min_blue, tally.blue);
-        min_green = std::cmp::max(min_green, tally.green);
-    }
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let games = parse(rx).await?;
 
-    (score, min_red * min_blue * min_green)
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&games)),
+        parts.part2().then(|| part2(&games)),
+    ))
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
-    let mut tasks = Vec::new();
+pub struct Day02;
 
-    while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(line).await });
-        tasks.push(task);
+impl Solver for Day02 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    let mut valid_score_part1 = 0u32;
-    let mut total_power_part2 = 0usize;
-    for task in tasks {
-        if let Ok((score, power)) = task.await {
-            valid_score_part1 += score;
-            total_power_part2 += power;
-        }
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Part 1: {valid_score_part1} Part 2: {total_power_part2}");
+    #[tokio::test]
+    async fn bad_colour_is_pinpointed() {
+        let line = "Game 3: 1 red, 2 purple; 3 blue".to_string();
+        let err = parse_line(7, line).await.unwrap_err();
+
+        assert_eq!((err.day, err.line, err.column), ("02", 7, Some(17)));
+        assert_eq!(err.message, "invalid colour name");
+    }
 }
@@ -0,0 +1,71 @@
+use crate::Day03;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const SYMBOLS: &[u8] = b"*%#@$+-";
+const NUMBER_FREQ: f64 = 0.05;
+const SYMBOL_FREQ: f64 = 0.03;
+
+/// Write `12*34` somewhere, clearing round the `*` so it's a gear with
+/// exactly those two numbers next to it
+fn plant_gear(rng: &mut StdRng, lines: &mut [Vec<u8>]) {
+    let (rows, cols) = (lines.len(), lines.first().map_or(0, |line| line.len()));
+    if rows == 0 || cols < 5 {
+        return;
+    }
+    let (row, col) = (rng.gen_range(0..rows), rng.gen_range(0..=cols - 5));
+    let gear = format!("{}*{}", rng.gen_range(10..100), rng.gen_range(10..100));
+    lines[row][col..col + 5].copy_from_slice(gear.as_bytes());
+
+    // nothing may run on into the numbers, or touch the gear from above or below
+    for edge in [col.checked_sub(1), Some(col + 5).filter(|&c| c < cols)]
+        .into_iter()
+        .flatten()
+    {
+        lines[row][edge] = b'.';
+    }
+    for next in [row.checked_sub(1), Some(row + 1).filter(|&r| r < rows)]
+        .into_iter()
+        .flatten()
+    {
+        lines[next][col + 1..col + 4].fill(b'.');
+    }
+}
+
+/// A `lines` x `width` engine schematic of 2-4 digit part numbers and symbols,
+/// with at least one gear once it's 5 wide
+impl Generator for Day03 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(1_400);
+        let cols = size.width_or(1_400).max(1);
+        let mut lines = Vec::with_capacity(rows);
+
+        for _ in 0..rows {
+            let mut line = String::new();
+            while line.len() < cols {
+                let roll = rng.gen::<f64>();
+                if roll < NUMBER_FREQ {
+                    let digits = rng.gen_range(2..=4);
+                    let number = rng.gen_range(10usize.pow(digits - 1)..10usize.pow(digits));
+                    line.push_str(&number.to_string());
+                } else if roll < NUMBER_FREQ + SYMBOL_FREQ {
+                    line.push(*SYMBOLS.choose(rng).unwrap() as char);
+                } else {
+                    line.push('.');
+                }
+            }
+            line.truncate(cols);
+            lines.push(line.into_bytes());
+        }
+        plant_gear(rng, &mut lines);
+
+        let mut out = String::new();
+        for line in lines {
+            out.push_str(&String::from_utf8(line).unwrap());
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -1,40 +1,45 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputShape, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use grid::{Grid, GridBuilder, Point};
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
+
+const DAY: &str = "03";
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct CandidatePart {
     part_id: String,
     line_no: usize,
     positions: Vec<usize>,
-    confirmed_part: bool,
-}
-
-struct Symbol {
-    chr: char,
-    loc: (usize, usize),
 }
 
 impl CandidatePart {
-    fn touching(&self, loc: &(usize, usize)) -> bool {
-        (self.line_no.abs_diff(loc.0) <= 1) & self.positions.iter().any(|p| p.abs_diff(loc.1) <= 1)
+    fn cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.positions.iter().map(|col| (self.line_no, *col))
     }
 }
 
-async fn calc_line(line: String, line_no: usize) -> (Vec<CandidatePart>, Vec<Symbol>) {
+fn is_symbol(chr: char) -> bool {
+    (chr != '.') & !chr.is_ascii_digit()
+}
+
+async fn calc_line(line: String, line_no: usize) -> (Vec<CandidatePart>, Vec<char>) {
     let mut building_part = false;
 
-    let mut candidate_parts = Vec::new();
-    let mut symbols = Vec::new();
+    let mut candidate_parts: Vec<CandidatePart> = Vec::new();
 
     for (col_no, chr) in line.chars().enumerate() {
         match (chr, building_part) {
-            ('.', false) => (),
-            ('.', true) => building_part = false,
             ('0'..='9', false) => {
                 candidate_parts.push(CandidatePart {
                     part_id: format!("{chr}"),
                     line_no,
                     positions: vec![col_no],
-                    confirmed_part: false,
                 });
                 building_part = true;
             }
@@ -43,73 +48,116 @@ async fn calc_line(line: String, line_no: usize) -> (Vec<CandidatePart>, Vec<Sym
                 candidate_parts[last].part_id.push(chr);
                 candidate_parts[last].positions.push(col_no);
             }
-            (_, true) => {
-                symbols.push(Symbol {
-                    chr,
-                    loc: (line_no, col_no),
-                });
-                building_part = false;
-            }
-            (_, false) => {
-                symbols.push(Symbol {
-                    chr,
-                    loc: (line_no, col_no),
-                });
-            }
+            _ => building_part = false,
         }
     }
 
-    (candidate_parts, symbols)
+    (candidate_parts, line.chars().collect())
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The engine schematic, and every number on it with where it sits
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schematic {
+    grid: Grid<char>,
+    parts: Vec<CandidatePart>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Schematic, SolveError> {
     let mut tasks = Vec::new();
 
     while let Some((line_no, line)) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(line, line_no).await });
-        tasks.push(task);
+        if line.is_empty() {
+            continue;
+        }
+        tasks.push(spawn_line(line_no, async move {
+            (line_no, calc_line(line, line_no).await)
+        }));
     }
 
+    let mut builder = GridBuilder::new();
     let mut all_parts = Vec::new();
-    let mut all_symbols = Vec::new();
 
-    for task in tasks {
-        if let Ok((mut parts, mut symbols)) = task.await {
-            all_parts.append(&mut parts);
-            all_symbols.append(&mut symbols);
-        }
+    for (line_no, (mut parts, row)) in join_tasks(tasks).await? {
+        let text: String = row.iter().collect();
+        builder
+            .insert_row(line_no, row)
+            .map_err(|e| ParseError::grid(DAY, e, &text))?;
+        all_parts.append(&mut parts);
     }
 
-    for symbol in all_symbols.iter() {
-        for part in all_parts.iter_mut().filter(|p| p.touching(&symbol.loc)) {
-            part.confirmed_part = true;
-        }
-    }
+    let grid = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
+
+    Ok(Schematic {
+        grid,
+        parts: all_parts,
+    })
+}
 
-    let part1 = all_parts
+pub fn part1(schematic: &Schematic) -> usize {
+    let grid = &schematic.grid;
+    schematic
+        .parts
         .iter()
-        .filter(|p| p.confirmed_part)
+        .filter(|p| {
+            p.cells()
+                .any(|cell| grid.adjacent(cell).any(|next| is_symbol(grid[next])))
+        })
         .map(|p| p.part_id.parse::<usize>().unwrap())
-        .sum::<usize>();
+        .sum::<usize>()
+}
 
-    let mut part2 = 0usize;
-    for symbol in all_symbols {
-        if symbol.chr != '*' {
-            continue;
+pub fn part2(schematic: &Schematic) -> usize {
+    let grid = &schematic.grid;
+
+    // which part, if any, each cell belongs to
+    let mut owners = grid.map(|_| None);
+    for (idx, part) in schematic.parts.iter().enumerate() {
+        for cell in part.cells() {
+            owners[cell] = Some(idx);
         }
-        let parts = all_parts
-            .iter()
-            .filter(|p| p.touching(&symbol.loc))
-            .map(|p| p.part_id.clone())
-            .collect::<Vec<String>>();
+    }
+
+    let mut part2 = 0usize;
+    for gear in grid.positions(|chr| *chr == '*') {
+        let mut parts: Vec<usize> = grid.adjacent(gear).filter_map(|c| owners[c]).collect();
+        parts.sort_unstable();
+        parts.dedup();
 
         if parts.len() == 2 {
             part2 += parts
                 .into_iter()
-                .map(|s| s.parse::<usize>().unwrap())
+                .map(|idx| schematic.parts[idx].part_id.parse::<usize>().unwrap())
                 .product::<usize>();
         }
     }
+    part2
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let schematic = parse(rx).await?;
+
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&schematic)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&schematic)));
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day03;
+
+impl Solver for Day03 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
 
-    println!("Part 1: {part1} Part 2: {part2}");
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
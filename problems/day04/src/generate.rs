@@ -0,0 +1,50 @@
+use crate::Day04;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const WINNING: usize = 10;
+const PLAYED: usize = 25;
+const MAX_NUMBER: usize = 99;
+
+/// `lines` scratchcards, no card wins copies of cards past the end of the table
+impl Generator for Day04 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let cards = size.lines_or(213_000);
+        let mut out = String::new();
+
+        for card in 1..=cards {
+            let max_matches = std::cmp::min(WINNING, cards - card);
+            let matches = rng.gen_range(0..=max_matches);
+
+            // distinct numbers: the winning set, then the misses
+            let numbers: Vec<usize> = sample(rng, MAX_NUMBER, WINNING + PLAYED - matches)
+                .into_iter()
+                .map(|n| n + 1)
+                .collect();
+            let winning = &numbers[..WINNING];
+            let mut played: Vec<usize> = winning[..matches]
+                .iter()
+                .chain(numbers[WINNING..].iter())
+                .copied()
+                .collect();
+            played.shuffle(rng);
+
+            let fmt = |nums: &[usize]| {
+                nums.iter()
+                    .map(|n| format!("{n:2}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            };
+            out.push_str(&format!(
+                "Card {card:3}: {} | {}\n",
+                fmt(winning),
+                fmt(&played)
+            ));
+        }
+
+        out
+    }
+}
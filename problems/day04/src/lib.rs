@@ -1,9 +1,19 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
 use std::collections::HashMap;
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
+
+const DAY: &str = "04";
 
-async fn calc_line(line: String, line_no: usize) -> (usize, usize) {
-    let (_card, numbers) = line.split_once(':').expect("incorrect input format");
-    let (winning, played) = numbers.split_once('|').expect("incorrect input format");
+async fn calc_line(line: String, line_no: usize) -> Result<(usize, usize), ParseError> {
+    let input = InputLine::new(DAY, line_no, &line);
+    let (_card, numbers) = input.split_once(&line, ":")?;
+    let (winning, played) = input.split_once(numbers, "|")?;
 
     let winning: Vec<&str> = winning.trim().split(' ').collect();
     let matches = played
@@ -13,44 +23,77 @@ async fn calc_line(line: String, line_no: usize) -> (usize, usize) {
         .filter(|s| !s.is_empty())
         .count();
 
-    (line_no, matches)
+    Ok((line_no, matches))
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// How many winning numbers each card matched, in card order
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cards {
+    matches: Vec<usize>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Cards, SolveError> {
     let mut tasks = Vec::new();
 
     while let Some((line_no, line)) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(line, line_no).await });
-        tasks.push(task);
+        tasks.push(spawn_line(line_no, async move {
+            calc_line(line, line_no).await
+        }));
     }
 
-    let mut total_score = 0;
     let mut match_map = Vec::new();
-
-    for task in tasks {
-        if let Ok((card_no, matches)) = task.await {
-            if match_map.len() < (card_no + 1) {
-                match_map.resize(card_no + 1, 0);
-            }
-            match_map[card_no] = matches;
-
-            if matches == 0 {
-                continue;
-            }
-
-            total_score += 2usize.pow((matches - 1).try_into().unwrap());
+    for result in join_tasks(tasks).await? {
+        let (card_no, matches) = result?;
+        if match_map.len() < (card_no + 1) {
+            match_map.resize(card_no + 1, 0);
         }
+        match_map[card_no] = matches;
     }
 
-    let cards = match_map.len();
+    Ok(Cards { matches: match_map })
+}
+
+pub fn part1(cards: &Cards) -> usize {
+    cards
+        .matches
+        .iter()
+        .filter(|&&matches| matches > 0)
+        .map(|&matches| 2usize.pow((matches - 1).try_into().unwrap()))
+        .sum()
+}
+
+pub fn part2(cards: &Cards) -> usize {
     let mut memo = HashMap::new();
-    let mut card_count = 0usize;
+    (0..cards.matches.len())
+        .map(|card| card_recurse(card, &cards.matches, &mut memo))
+        .sum::<usize>()
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let cards = parse(rx).await?;
+
+    let part1 = parts.part1().then(|| part1(&cards));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&cards)));
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day04;
 
-    for card in 0..=cards {
-        card_count += card_recurse(card, &match_map, &mut memo);
+impl Solver for Day04 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    println!("Part 1: {total_score} Part 2: {card_count}");
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
 
 fn card_recurse(idx: usize, matches: &[usize], memo: &mut HashMap<usize, usize>) -> usize {
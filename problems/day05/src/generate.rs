@@ -0,0 +1,55 @@
+use crate::Day05;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const STAGES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+const SPACE: usize = 4_000_000_000;
+
+/// An almanac with `width` seed ranges and `lines` ranges per map, the
+/// source ranges of a map never overlap
+impl Generator for Day05 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let seed_ranges = size.width_or(10).max(1);
+        let map_ranges = size.lines_or(40).max(1);
+
+        let seeds: Vec<String> = (0..seed_ranges)
+            .map(|_| {
+                let start = rng.gen_range(0..SPACE / 2);
+                let len = rng.gen_range(1..SPACE / (seed_ranges * 8));
+                format!("{start} {len}")
+            })
+            .collect();
+        let mut blocks = vec![format!("seeds: {}", seeds.join(" "))];
+
+        for pair in STAGES.windows(2) {
+            let mut cuts: Vec<usize> = (0..map_ranges * 2)
+                .map(|_| rng.gen_range(0..SPACE))
+                .collect();
+            cuts.sort_unstable();
+            cuts.dedup();
+
+            let mut block = format!("{}-to-{} map:", pair[0], pair[1]);
+            for window in cuts.chunks_exact(2) {
+                let (source, len) = (window[0], window[1] - window[0]);
+                if len == 0 {
+                    continue;
+                }
+                let destination = rng.gen_range(0..SPACE - len);
+                block.push_str(&format!("\n{destination} {source} {len}"));
+            }
+            blocks.push(block);
+        }
+
+        blocks.join("\n\n") + "\n"
+    }
+}
@@ -1,51 +1,17 @@
-use std::str::FromStr;
-use tokio::sync::mpsc::Receiver;
-
-#[derive(Debug, Clone, PartialEq)]
-struct Interval {
-    start: usize,
-    len: usize,
-}
-
-impl Interval {
-    fn compare(&self, other: &Self) -> (Option<Self>, Vec<Self>) {
-        // calculate overlap if any and left over intervals
-        let x1 = self.start;
-        let x2 = self.start + self.len - 1;
-        let y1 = other.start;
-        let y2 = other.start + other.len - 1;
-
-        if (y2 >= x1) & (y1 <= x2) {
-            // has overlap
-            let overlap1 = std::cmp::max(x1, y1);
-            let overlap2 = std::cmp::min(x2, y2);
-            let overlap = Some(Interval {
-                start: overlap1,
-                len: overlap2 + 1 - overlap1,
-            });
-            let mut left_over = Vec::new();
-
-            if x1 < overlap1 {
-                left_over.push(Interval {
-                    start: x1,
-                    len: overlap1 - x1,
-                });
-            }
-            if x2 > overlap2 {
-                left_over.push(Interval {
-                    start: overlap2 + 1,
-                    len: x2 - overlap2,
-                });
-            }
+mod generate;
 
-            return (overlap, left_over);
-        }
+use common::{
+    join_tasks, spawn_line, split_blocks, Answer, Framing, InputLine, InputStream, ParseError,
+    Parts, SolveError, SolveFuture, Solver,
+};
+use interval::{Interval, IntervalSet};
+use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
 
-        (None, Vec::new())
-    }
-}
+const DAY: &str = "05";
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Commodity {
     Seed,
     Soil,
@@ -57,10 +23,8 @@ enum Commodity {
     Location,
 }
 
-impl FromStr for Commodity {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl Commodity {
+    fn parse(input: &InputLine, s: &str) -> Result<Self, ParseError> {
         let variant = match s {
             "seed" => Self::Seed,
             "soil" => Self::Soil,
@@ -70,41 +34,53 @@ impl FromStr for Commodity {
             "temperature" => Self::Temperature,
             "humidity" => Self::Humidity,
             "location" => Self::Location,
-            _ => panic!("invalid commodity input"),
+            _ => return Err(input.error_at(s, "invalid commodity")),
         };
 
         Ok(variant)
     }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Seed => "seed",
+            Self::Soil => "soil",
+            Self::Fertilizer => "fertilizer",
+            Self::Water => "water",
+            Self::Light => "light",
+            Self::Temperature => "temperature",
+            Self::Humidity => "humidity",
+            Self::Location => "location",
+        }
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Stage {
     comm: Commodity,
     values: Vec<usize>,
-    intervals: Vec<Interval>,
+    intervals: IntervalSet<usize>,
 }
 
-impl FromStr for Stage {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (comm_str, values_str) = s.split_once("s: ").unwrap();
-        let values: Vec<_> = values_str
+impl Stage {
+    fn parse(input: &InputLine, s: &str) -> Result<Self, ParseError> {
+        let (comm_str, values_str) = input.split_once(s, "s: ")?;
+        let values: Vec<usize> = values_str
             .trim()
             .split(' ')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+            .map(|s| input.parse(s))
+            .collect::<Result<_, _>>()?;
+        if !values.len().is_multiple_of(2) {
+            return Err(input.error_at(values_str, "expected pairs of start and length"));
+        }
 
         let intervals = values
             .chunks(2)
-            .map(|chunk| Interval {
-                start: chunk[0],
-                len: chunk[1],
-            })
+            .map(|chunk| Interval::with_len(chunk[0], chunk[1]))
             .collect();
 
         Ok(Stage {
-            comm: Commodity::from_str(comm_str).unwrap(),
+            comm: Commodity::parse(input, comm_str)?,
             values,
             intervals,
         })
@@ -112,92 +88,72 @@ impl FromStr for Stage {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct MapTable {
     in_: Commodity,
     out: Commodity,
-    interchange: Vec<(usize, usize, usize)>,
+    /// Each source range with where its start is sent to
+    interchange: Vec<(Interval<usize>, usize)>,
 }
 
 impl MapTable {
-    fn next_stage(&self, stage: &Stage) -> Option<Stage> {
-        if self.in_ != stage.comm {
-            return None;
-        }
-
-        let values = stage
-            .values
+    fn map_values(&self, values: &[usize]) -> Vec<usize> {
+        values
             .iter()
             .map(|v| {
-                for (out_start, int_start, int_len) in self.interchange.iter() {
-                    if v >= int_start {
-                        let diff = v.abs_diff(*int_start);
-                        if diff < *int_len {
-                            return out_start + diff;
-                        }
-                    }
-                }
-                *v
+                self.interchange
+                    .iter()
+                    .find(|(source, _)| source.contains(*v))
+                    .map_or(*v, |(source, out_start)| out_start + (v - source.start()))
             })
-            .collect::<Vec<usize>>();
-
-        let mut queue = stage.intervals.clone();
-        let mut to_add = Vec::new();
-        let mut intervals = Vec::new();
-
-        while !queue.is_empty() {
-            'a: for interval in &queue {
-                for (out_start, start, len) in self.interchange.iter() {
-                    let (overlap, mut offcuts) = interval.compare(&Interval {
-                        start: *start,
-                        len: *len,
-                    });
-                    if let Some(overlap_) = overlap {
-                        let mut new_interval = overlap_.clone();
-                        if out_start > start {
-                            new_interval.start += out_start - start;
-                        } else {
-                            new_interval.start -= start - out_start;
-                        }
-                        intervals.push(new_interval);
-                        to_add.append(&mut offcuts);
-                        continue 'a;
-                    }
-                }
-                // default, no overlap found
-                intervals.push(interval.clone());
+            .collect::<Vec<usize>>()
+    }
+
+    fn map_intervals(&self, intervals: &IntervalSet<usize>) -> IntervalSet<usize> {
+        let mut remaining = intervals.clone();
+        let mut mapped = IntervalSet::new();
+
+        for (source, out_start) in self.interchange.iter() {
+            let source = IntervalSet::from(*source);
+            if let Some(start) = source.min() {
+                mapped = mapped.union(&remaining.intersect(&source).translate(start, *out_start));
             }
-            queue.clear();
-            queue.append(&mut to_add);
+            remaining = remaining.subtract(&source);
         }
 
-        Some(Stage {
-            comm: self.out.clone(),
-            values,
-            intervals,
-        })
+        // anything no range covers keeps its number
+        mapped.union(&remaining)
     }
 }
 
-impl FromStr for MapTable {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        let header = lines.next().unwrap();
+impl MapTable {
+    /// Parse a whole map block, `line_no` is the line its header is on
+    fn parse(line_no: usize, block: &str) -> Result<Self, ParseError> {
+        let mut lines = block.lines();
+        let header = lines.next().unwrap_or_default();
+        let input = InputLine::new(DAY, line_no, header);
 
-        let (comm2comm, _) = header.split_once(' ').unwrap();
-        let (comm1, comm2) = comm2comm.split_once("-to-").unwrap();
+        let (comm2comm, _) = input.split_once(header, " ")?;
+        let (comm1, comm2) = input.split_once(comm2comm, "-to-")?;
 
         let interchange = lines
-            .map(|l| {
-                let vals: Vec<usize> = l.split(' ').map(|v| v.parse::<usize>().unwrap()).collect();
-                (vals[0], vals[1], vals[2])
+            .enumerate()
+            .map(|(idx, l)| {
+                let range = InputLine::new(DAY, line_no + 1 + idx, l);
+                let vals: Vec<usize> = l
+                    .split(' ')
+                    .map(|v| range.parse(v))
+                    .collect::<Result<_, _>>()?;
+                match vals[..] {
+                    [out_start, start, len] => Ok((Interval::with_len(start, len), out_start)),
+                    _ => Err(range.error("expected three numbers")),
+                }
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         Ok(MapTable {
-            in_: Commodity::from_str(comm1).unwrap(),
-            out: Commodity::from_str(comm2).unwrap(),
+            in_: Commodity::parse(&input, comm1)?,
+            out: Commodity::parse(&input, comm2)?,
             interchange,
         })
     }
@@ -209,77 +165,306 @@ enum Input {
     MapTable(MapTable),
 }
 
-impl FromStr for Input {
-    type Err = Box<dyn std::error::Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        let header = lines.next().unwrap();
+impl Input {
+    fn parse(line_no: usize, block: &str) -> Result<Self, ParseError> {
+        let header = block.lines().next().unwrap_or_default();
 
         if header.contains("map") {
-            Ok(Input::MapTable(MapTable::from_str(s).unwrap()))
+            Ok(Input::MapTable(MapTable::parse(line_no, block)?))
         } else {
-            Ok(Input::Stage(Stage::from_str(header).unwrap()))
+            let input = InputLine::new(DAY, line_no, header);
+            Ok(Input::Stage(Stage::parse(&input, header)?))
+        }
+    }
+}
+
+/// The seeds and the map tables that lead from them to locations
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Almanac {
+    seeds: Stage,
+    /// In the order they're applied, see `chain_tables`
+    tables: Vec<MapTable>,
+}
+
+impl Almanac {
+    /// Carry `items` from the seeds' commodity through the tables to locations
+    fn to_location<T>(&self, items: T, step: impl Fn(&MapTable, &T) -> T) -> T {
+        info_span!("map_stages", tables = self.tables.len())
+            .in_scope(|| self.tables.iter().fold(items, |items, mt| step(mt, &items)))
+    }
+}
+
+fn unsolvable(message: String) -> SolveError {
+    SolveError::Unsolvable { day: DAY, message }
+}
+
+/// Line up the tables that carry `from` through to locations, dropping any
+/// the chain doesn't pass through
+fn chain_tables(
+    from: &Commodity,
+    tables: Vec<(usize, String, MapTable)>,
+) -> Result<Vec<MapTable>, SolveError> {
+    let mut by_input: Vec<Option<MapTable>> = Vec::new();
+    let mut inputs = Vec::new();
+    for (line_no, header, mt) in tables {
+        if inputs.contains(&mt.in_) {
+            let name = mt.in_.name();
+            return Err(InputLine::new(DAY, line_no, &header)
+                .error(format!(
+                    "a second map from {name}, each commodity maps one way"
+                ))
+                .into());
         }
+        inputs.push(mt.in_.clone());
+        by_input.push(Some(mt));
     }
+
+    let mut chain = Vec::new();
+    let mut comm = from.clone();
+    while comm != Commodity::Location {
+        let Some(idx) = inputs.iter().position(|input| *input == comm) else {
+            return Err(unsolvable(format!(
+                "no map from {} on towards location",
+                comm.name()
+            )));
+        };
+        // each table is only there to take once, so a loop runs out of them
+        let Some(mt) = by_input[idx].take() else {
+            return Err(unsolvable(format!(
+                "the maps go round in a circle back to {}",
+                comm.name()
+            )));
+        };
+        comm = mt.out.clone();
+        chain.push(mt);
+    }
+    Ok(chain)
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Almanac, SolveError> {
     let mut tasks = Vec::new();
 
-    while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { Input::from_str(&line).unwrap() });
-        tasks.push(task);
+    // blocks come split on single blank lines, so each one starts a line
+    // past the end of the one before
+    let mut line_no = 0;
+
+    while let Some(block) = rx.recv().await {
+        let block_lines = block.split('\n').count();
+        if !block.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                let parsed = Input::parse(line_no, &block)?;
+                let header = block.lines().next().unwrap_or_default().to_string();
+                Ok::<_, ParseError>((line_no, header, parsed))
+            }));
+        }
+        line_no += block_lines + 1;
     }
 
-    let mut stage = Stage::from_str("seeds: 1 2").unwrap();
+    let mut seeds = None;
     let mut maptables = Vec::new();
 
-    for task in tasks {
-        if let Ok(input) = task.await {
-            match input {
-                Input::Stage(s) => stage = s,
-                Input::MapTable(mt) => maptables.push(mt),
+    for result in join_tasks(tasks).await? {
+        match result? {
+            (line_no, header, Input::Stage(stage)) => {
+                if seeds.is_some() {
+                    return Err(InputLine::new(DAY, line_no, &header)
+                        .error("a second list of seeds")
+                        .into());
+                }
+                seeds = Some(stage);
             }
+            (line_no, header, Input::MapTable(mt)) => maptables.push((line_no, header, mt)),
         }
     }
 
-    while stage.comm != Commodity::Location {
-        for mt in maptables.iter() {
-            if let Some(next_stage) = mt.next_stage(&stage) {
-                stage = next_stage;
-            }
-        }
+    let Some(seeds) = seeds else {
+        return Err(unsolvable("no seeds line to start from".to_string()));
+    };
+    let tables = chain_tables(&seeds.comm, maptables)?;
+
+    Ok(Almanac { seeds, tables })
+}
+
+pub fn part1(almanac: &Almanac) -> Result<usize, SolveError> {
+    let values = almanac.to_location(almanac.seeds.values.clone(), |mt, values| {
+        mt.map_values(values)
+    });
+    values
+        .into_iter()
+        .min()
+        .ok_or_else(|| unsolvable("no seeds to find a location for".to_string()))
+}
+
+pub fn part2(almanac: &Almanac) -> Result<usize, SolveError> {
+    let intervals = almanac.to_location(almanac.seeds.intervals.clone(), |mt, intervals| {
+        mt.map_intervals(intervals)
+    });
+    intervals
+        .min()
+        .ok_or_else(|| unsolvable("every range of seeds is empty".to_string()))
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let almanac = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&almanac)).transpose()?,
+        parts.part2().then(|| part2(&almanac)).transpose()?,
+    ))
+}
+
+pub struct Day05;
+
+impl Solver for Day05 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn split_input(&self, raw: &str) -> Vec<String> {
+        split_blocks(raw)
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Blocks
     }
 
-    let part1 = stage.values.iter().min().unwrap();
-    let part2 = stage.intervals.iter().map(|i| i.start).min().unwrap();
-    println!("Part 1: {part1} Part 2: {part2}");
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn table(interchange: &[(usize, usize, usize)]) -> MapTable {
+        MapTable {
+            in_: Commodity::Seed,
+            out: Commodity::Soil,
+            interchange: interchange
+                .iter()
+                .map(|(out_start, start, len)| (Interval::with_len(*start, *len), *out_start))
+                .collect(),
+        }
+    }
+
     #[test]
     fn interval_non_overlap() {
-        let a = Interval { start: 1, len: 1 };
-        let b = Interval { start: 2, len: 2 };
+        let seeds = IntervalSet::from(Interval::with_len(1, 1));
 
-        let (overlap, offcuts) = a.compare(&b);
-        assert!(overlap.is_none());
-        assert!(offcuts.is_empty());
+        let mapped = table(&[(10, 2, 2)]).map_intervals(&seeds);
+        assert_eq!(mapped, seeds);
     }
 
     #[test]
     fn interval_contained() {
-        let a = Interval { start: 1, len: 5 };
-        let b = Interval { start: 2, len: 2 };
-
-        let (overlap, offcuts) = a.compare(&b);
-        assert_eq!(overlap, Some(Interval { start: 2, len: 2 }));
-        assert_eq!(offcuts[0], Interval { start: 1, len: 1 });
-        assert_eq!(offcuts[1], Interval { start: 4, len: 2 });
-        assert_eq!(offcuts.len(), 2);
+        let seeds = IntervalSet::from(Interval::with_len(1, 5));
+
+        let mapped = table(&[(10, 2, 2)]).map_intervals(&seeds);
+        assert_eq!(
+            mapped.intervals(),
+            [
+                Interval::with_len(1, 1),
+                Interval::with_len(4, 2),
+                Interval::with_len(10, 2)
+            ]
+        );
+        assert_eq!(mapped.len(), 5);
+    }
+
+    async fn almanac(input: &str) -> Result<Almanac, SolveError> {
+        let blocks = split_blocks(input);
+        let (tx, rx) = tokio::sync::mpsc::channel(blocks.len().max(1));
+        for block in blocks {
+            tx.send(block).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await
+    }
+
+    #[tokio::test]
+    async fn broken_almanacs() {
+        for (input, expected) in [
+            (
+                "seed-to-location map:\n1 2 3",
+                "day 05: no seeds line to start from",
+            ),
+            (
+                "seeds: 1 2\n\nseeds: 3 4\n\nseed-to-location map:\n1 2 3",
+                "line 3: a second list of seeds",
+            ),
+            (
+                "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-seed map:\n1 2 3",
+                "day 05: the maps go round in a circle back to seed",
+            ),
+            (
+                "seeds: 1 2\n\nseed-to-soil map:\n1 2 3",
+                "day 05: no map from soil on towards location",
+            ),
+            (
+                "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nseed-to-location map:\n1 2 3",
+                "line 6: a second map from seed",
+            ),
+            (
+                "seeds: 1 2\n\nseed-to-location map:\n1 2 3\n4 5",
+                "line 5: expected three numbers",
+            ),
+        ] {
+            let error = almanac(input).await.err().unwrap().to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+
+        let empty_ranges = almanac("seeds: 7 0\n\nseed-to-location map:\n1 2 3")
+            .await
+            .unwrap();
+        assert_eq!(part1(&empty_ranges), Ok(0));
+        assert!(part2(&empty_ranges).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn almanac_round_trip() {
+        let example = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        for block in split_blocks(example) {
+            tx.send(block).await.unwrap();
+        }
+        drop(tx);
+
+        let json = serde_json::to_string(&parse(rx).await.unwrap()).unwrap();
+        let almanac: Almanac = serde_json::from_str(&json).unwrap();
+        assert_eq!((part1(&almanac), part2(&almanac)), (Ok(35), Ok(46)));
     }
 }
@@ -0,0 +1,27 @@
+use crate::Day06;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// `lines` races lasting up to `width` milliseconds, each record is beatable
+impl Generator for Day06 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let races = size.lines_or(4).max(1);
+        let max_time = size.width_or(100).max(3);
+        let mut times = Vec::new();
+        let mut distances = Vec::new();
+
+        for _ in 0..races {
+            let time = rng.gen_range(3..=max_time);
+            let best = (time / 2) * (time - time / 2);
+            times.push(time.to_string());
+            distances.push(rng.gen_range(0..best).to_string());
+        }
+
+        format!(
+            "Time:      {}\nDistance:  {}\n",
+            times.join("  "),
+            distances.join("  ")
+        )
+    }
+}
@@ -1,29 +1,140 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, Framing, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
 use tokio::sync::mpsc::Receiver;
 
-async fn calc_line(line: &str) -> usize {
-    let (time, distance) = line.split_once(' ').unwrap();
-    let time = time.parse::<usize>().unwrap();
-    let distance = distance.parse::<usize>().unwrap();
+const DAY: &str = "06";
+
+/// A race's time limit and the record distance to beat
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Race {
+    time: usize,
+    distance: usize,
+}
+
+impl Race {
+    fn ways_to_win(&self) -> usize {
+        (1..self.time)
+            .filter(|wait| (self.time - wait) * wait > self.distance)
+            .count()
+    }
+}
+
+async fn calc_line(line_no: usize, line: &str) -> Result<Race, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let (time, distance) = input.split_once(line, " ")?;
+    if time.is_empty() || distance.is_empty() {
+        return Err(input.error("every race needs both a time and a distance"));
+    }
+    let time = input.parse::<usize>(time)?;
+    let distance = input.parse::<usize>(distance)?;
 
-    (1..time)
-        .filter(|wait| (time - wait) * wait > distance)
-        .count()
+    Ok(Race { time, distance })
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Race>, SolveError> {
     let mut tasks = Vec::new();
+    let mut line_no = 0;
     while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(&line).await });
-        tasks.push(task);
+        tasks.push(spawn_line(line_no, async move {
+            calc_line(line_no, &line).await
+        }));
+        line_no += 1;
     }
 
-    let mut ways_to_win = Vec::new();
-    for task in tasks {
-        if let Ok(ways) = task.await {
-            ways_to_win.push(ways);
+    let mut races = Vec::new();
+    for race in join_tasks(tasks).await? {
+        races.push(race?);
+    }
+    Ok(races)
+}
+
+pub fn part1(races: &[Race]) -> usize {
+    races.iter().map(Race::ways_to_win).product::<usize>()
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let races = parse(rx).await?;
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&races)),
+        None::<usize>,
+    ))
+}
+
+pub struct Day06;
+
+impl Solver for Day06 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn split_input(&self, raw: &str) -> Vec<String> {
+        // each race is sent as a "time distance" pair
+        let rows = raw
+            .lines()
+            .map(|l| {
+                let values = l.split_once(':').map_or(l, |(_feature, values)| values);
+                values
+                    .split(' ')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+
+        // a race short of its time or distance, or a missing row, is sent
+        // with a gap for parsing to reject, rather than dropped
+        let empty = Vec::new();
+        let times = rows.first().unwrap_or(&empty);
+        let distances = rows.get(1).unwrap_or(&empty);
+        let value = |row: &[String], idx: usize| row.get(idx).cloned().unwrap_or_default();
+        (0..times.len().max(distances.len()).max(1))
+            .map(|idx| format!("{} {}", value(times, idx), value(distances, idx)))
+            .collect::<Vec<String>>()
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Whole
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn races(raw: &str) -> Result<Vec<Race>, SolveError> {
+        let units = Day06.split_input(raw);
+        let (tx, rx) = tokio::sync::mpsc::channel(units.len());
+        for unit in units {
+            tx.send(unit).await.unwrap();
         }
+        drop(tx);
+        parse(rx).await
     }
 
-    let answer = ways_to_win.into_iter().product::<usize>();
-    println!("Output: {answer}");
+    #[tokio::test]
+    async fn example() {
+        let races = races("Time:      7  15   30\nDistance:  9  40  200\n")
+            .await
+            .unwrap();
+        assert_eq!(part1(&races), 288);
+    }
+
+    #[tokio::test]
+    async fn missing_values() {
+        for raw in ["Time:      7  15   30\n", "Time: 7 15\nDistance: 9\n", ""] {
+            let error = races(raw).await.err().unwrap().to_string();
+            assert!(
+                error.contains("needs both a time and a distance"),
+                "{raw:?}: {error}"
+            );
+        }
+    }
 }
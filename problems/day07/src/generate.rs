@@ -0,0 +1,25 @@
+use crate::Day07;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const CARDS: &[u8] = b"23456789TJQKA";
+
+/// `lines` random hands of camel cards with bids up to `width`
+impl Generator for Day07 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let hands = size.lines_or(30_000);
+        let max_bid = size.width_or(1000).max(1);
+        let mut out = String::new();
+
+        for _ in 0..hands {
+            let hand: String = (0..5)
+                .map(|_| *CARDS.choose(rng).unwrap() as char)
+                .collect();
+            out.push_str(&format!("{hand} {}\n", rng.gen_range(1..=max_bid)));
+        }
+
+        out
+    }
+}
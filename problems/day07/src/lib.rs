@@ -1,5 +1,15 @@
+mod generate;
+
+use common::{
+    join_part, join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
+use tracing::{info_span, Instrument, Span};
+
+const DAY: &str = "07";
 
 const NUMBER_OF_CARDS: usize = 13;
 const HAND_SIZE: usize = 5;
@@ -21,9 +31,11 @@ enum Card {
     A,
 }
 
-impl From<char> for Card {
-    fn from(c: char) -> Self {
-        match c {
+impl TryFrom<char> for Card {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        let card = match c {
             '2' => Self::C2,
             '3' => Self::C3,
             '4' => Self::C4,
@@ -37,8 +49,9 @@ impl From<char> for Card {
             'Q' => Self::Q,
             'K' => Self::K,
             'A' => Self::A,
-            _ => panic!("invalid card"),
-        }
+            _ => return Err(c),
+        };
+        Ok(card)
     }
 }
 
@@ -60,11 +73,19 @@ struct Hand {
 }
 
 impl Hand {
-    fn new(s: &str, jokers: bool) -> Self {
-        if s.len() != HAND_SIZE {
-            panic!("invalid hand length");
+    fn parse(input: &InputLine, s: &str, jokers: bool) -> Result<Self, ParseError> {
+        if s.chars().count() != HAND_SIZE {
+            return Err(input.error_at(s, format!("expected {HAND_SIZE} cards")));
         }
+        if let Some((idx, chr)) = s.char_indices().find(|(_, c)| Card::try_from(*c).is_err()) {
+            return Err(input.error_at(&s[idx..], format!("invalid card {chr:?}")));
+        }
+
+        Ok(Self::new(s, jokers))
+    }
 
+    /// Build a hand from five valid cards, see `Hand::parse` for checked input
+    fn new(s: &str, jokers: bool) -> Self {
         if jokers {
             return Self::_new_jokers(s);
         }
@@ -109,7 +130,10 @@ impl Hand {
         let mut card_value_counts = [0u8; NUMBER_OF_CARDS];
         let mut card_value_meta_counts = [0u8; HAND_SIZE];
 
-        let values: Vec<u8> = s.chars().map(|c| Card::from(c) as u8).collect();
+        let values: Vec<u8> = s
+            .chars()
+            .map(|c| Card::try_from(c).expect("cards are checked when parsing") as u8)
+            .collect();
 
         for value in values.iter() {
             card_value_counts[*value as usize] += 1;
@@ -137,57 +161,96 @@ impl Hand {
     }
 }
 
-async fn calc_line(line: &str) -> (Hand, Hand, usize) {
-    let (hand_str, bet_str) = line.split_once(' ').unwrap();
+/// A checked hand of cards and what was bet on it
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bid {
+    hand: String,
+    bet: usize,
+}
+
+async fn calc_line(line_no: usize, line: &str) -> Result<Bid, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let (hand_str, bet_str) = input.split_once(line, " ")?;
+    Hand::parse(&input, hand_str, false)?;
 
-    (
-        Hand::new(hand_str, false),
-        Hand::new(hand_str, true),
-        bet_str.parse().unwrap(),
-    )
+    Ok(Bid {
+        hand: hand_str.to_string(),
+        bet: input.parse(bet_str)?,
+    })
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Bid>, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut line_no = 0;
+
     while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(&line).await });
-        tasks.push(task);
+        tasks.push(spawn_line(line_no, async move {
+            calc_line(line_no, &line).await
+        }));
+        line_no += 1;
     }
 
-    let mut hand_bet_pairs_part1 = Vec::new();
-    let mut hand_bet_pairs_part2 = Vec::new();
-
-    for task in tasks {
-        if let Ok((hand1, hand2, bet)) = task.await {
-            hand_bet_pairs_part1.push((hand1, bet));
-            hand_bet_pairs_part2.push((hand2, bet));
-        }
+    let mut bids = Vec::new();
+    for result in join_tasks(tasks).await? {
+        bids.push(result?);
     }
+    Ok(bids)
+}
 
-    let part1 = tokio::spawn(async move {
-        hand_bet_pairs_part1.sort_by(|(hand1, _), (hand2, _)| hand1.cmp(hand2));
+fn winnings(bids: &[Bid], jokers: bool) -> usize {
+    let mut hand_bet_pairs: Vec<(Hand, usize)> = bids
+        .iter()
+        .map(|bid| (Hand::new(&bid.hand, jokers), bid.bet))
+        .collect();
+    hand_bet_pairs.sort_by(|(hand1, _), (hand2, _)| hand1.cmp(hand2));
+
+    hand_bet_pairs
+        .iter()
+        .enumerate()
+        .map(|(idx, (_hand, bet))| (idx + 1) * bet)
+        .sum::<usize>()
+}
 
-        hand_bet_pairs_part1
-            .iter()
-            .enumerate()
-            .map(|(idx, (_hand, bet))| (idx + 1) * bet)
-            .sum::<usize>()
-    });
-
-    let part2 = tokio::spawn(async move {
-        hand_bet_pairs_part2.sort_by(|(hand1, _), (hand2, _)| hand1.cmp(hand2));
-        hand_bet_pairs_part2
-            .iter()
-            .enumerate()
-            .map(|(idx, (_hand, bet))| (idx + 1) * bet)
-            .sum::<usize>()
-    });
+pub fn part1(bids: &[Bid]) -> usize {
+    winnings(bids, false)
+}
+
+pub fn part2(bids: &[Bid]) -> usize {
+    winnings(bids, true)
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let bids = Arc::new(parse(rx).await?);
+
+    // the two parts rank the same hands independently, so run them side by side
+    let spawn_part = |part: fn(&[Bid]) -> usize, span: Span| {
+        let bids = bids.clone();
+        tokio::spawn(async move { part(&bids) }.instrument(span))
+    };
+    let part1 = parts
+        .part1()
+        .then(|| spawn_part(part1, info_span!("part1")));
+    let part2 = parts
+        .part2()
+        .then(|| spawn_part(part2, info_span!("part2")));
+
+    let part1 = join_part(1, part1).await?;
+    let part2 = join_part(2, part2).await?;
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day07;
 
-    let part1 = part1.await.unwrap();
-    let part2 = part2.await.unwrap();
+impl Solver for Day07 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
 
-    println!("Part 1: {part1}, Part 2: {part2}");
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
 
 #[cfg(test)]
@@ -219,4 +282,14 @@ mod tests {
         assert_eq!(alljs.handtype, HandType::FiveOAK);
         assert_eq!(alljs.values, [0, 0, 0, 0, 0]);
     }
+
+    #[test]
+    fn bad_hands() {
+        let input = InputLine::new(DAY, 3, "K2X3K 10");
+        let err = Hand::parse(&input, &input.text[..5], false).unwrap_err();
+        assert_eq!((err.line, err.column), (3, Some(2)));
+
+        let input = InputLine::new(DAY, 0, "K23K 10");
+        assert!(Hand::parse(&input, &input.text[..4], true).is_err());
+    }
 }
@@ -0,0 +1,90 @@
+use crate::Day08;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// chain lengths are these multiples of the instruction length, distinct
+// primes keep the ghosts' periods coprime apart from that shared factor
+const CYCLE_FACTORS: [usize; 10] = [29, 31, 37, 41, 43, 47, 53, 59, 61, 67];
+
+/// `lines` ghosts and `width` left/right instructions. Every ghost walks a
+/// loop whose length is a multiple of the instruction length and lands on its
+/// `..Z` node at the end of each lap, which is what the part 2 LCM relies on.
+/// The first ghost walks from AAA to ZZZ.
+impl Generator for Day08 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let ghosts = size.lines_or(6).clamp(1, CYCLE_FACTORS.len());
+        let instructions = size.width_or(41).max(1);
+
+        let directions: Vec<bool> = (0..instructions).map(|_| rng.gen_bool(0.5)).collect();
+
+        let mut factors = CYCLE_FACTORS.to_vec();
+        factors.shuffle(rng);
+        let chain_lengths: Vec<usize> =
+            factors[..ghosts].iter().map(|f| f * instructions).collect();
+
+        // names ending in A and Z are reserved for the ghosts' start and end
+        let mut middles: Vec<String> = (b'A'..=b'Z')
+            .flat_map(|a| (b'A'..=b'Z').map(move |b| (a, b)))
+            .flat_map(|(a, b)| {
+                (b'B'..=b'Y').map(move |c| String::from_utf8(vec![a, b, c]).unwrap())
+            })
+            .collect();
+        let needed: usize = chain_lengths.iter().map(|len| len - 1).sum();
+        assert!(
+            needed <= middles.len(),
+            "too many nodes for three letter names, use fewer ghosts or instructions"
+        );
+        middles.shuffle(rng);
+
+        let mut prefixes: Vec<String> = (b'A'..=b'Z')
+            .flat_map(|a| (b'A'..=b'Z').map(move |b| String::from_utf8(vec![a, b]).unwrap()))
+            .filter(|p| p != "AA" && p != "ZZ")
+            .collect();
+        prefixes.shuffle(rng);
+        prefixes.insert(0, "AA".to_string());
+
+        let mut all_names = middles[..needed].to_vec();
+        let mut nodes = Vec::new();
+
+        for (ghost, length) in chain_lengths.iter().enumerate() {
+            let prefix = if ghost == 0 { "ZZ" } else { &prefixes[ghost] };
+            let start = format!("{}A", prefixes[ghost]);
+            let end = format!("{prefix}Z");
+            let middle: Vec<String> = middles.drain(..length - 1).collect();
+
+            // start -> middle.. -> end, then end loops back onto the first middle node
+            let mut chain = vec![start.clone()];
+            chain.extend(middle.iter().cloned());
+            chain.push(end.clone());
+            all_names.push(start);
+            all_names.push(end);
+
+            for (step, window) in chain.windows(2).enumerate() {
+                nodes.push((window[0].clone(), step % instructions, window[1].clone()));
+            }
+            let loop_target = chain.get(1).cloned().unwrap_or(chain[0].clone());
+            nodes.push((chain[chain.len() - 1].clone(), 0, loop_target));
+        }
+
+        // the branch not taken leads anywhere, it is never followed
+        let mut out: String = directions
+            .iter()
+            .map(|&r| if r { 'R' } else { 'L' })
+            .collect();
+        out.push_str("\n\n");
+        nodes.shuffle(rng);
+        for (name, step, next) in nodes {
+            let decoy = all_names.choose(rng).unwrap();
+            let (left, right) = if directions[step] {
+                (decoy, &next)
+            } else {
+                (&next, decoy)
+            };
+            out.push_str(&format!("{name} = ({left}, {right})\n"));
+        }
+
+        out
+    }
+}
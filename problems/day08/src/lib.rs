@@ -1,59 +1,127 @@
-use std::collections::{HashMap, VecDeque};
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use graph::{Graph, Labels, NodeId};
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
+
+const DAY: &str = "08";
 
 enum Input {
-    Directions(VecDeque<bool>),
+    Directions(Vec<bool>),
     Node((String, String, String)),
 }
 
-async fn calc_line(line: &str) -> Input {
+async fn calc_line(line_no: usize, line: &str) -> Result<Input, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
     if let Some((start, destinations)) = line.split_once(" = (") {
-        let (left_dest, right_dest) = destinations.split_once(", ").unwrap();
-        return Input::Node((
+        let (left_dest, right_dest) = input.split_once(destinations, ", ")?;
+        return Ok(Input::Node((
             start.to_string(),
             left_dest.to_string(),
             right_dest.replace(')', ""),
-        ));
+        )));
+    }
+
+    if let Some(idx) = line.find(|c| (c != 'L') && (c != 'R')) {
+        return Err(input.error_at(&line[idx..], "expected L or R"));
     }
+    Ok(Input::Directions(line.chars().map(|c| c == 'R').collect()))
+}
 
-    Input::Directions(line.chars().map(|c| c == 'R').collect())
+/// The left/right instructions and the node network they are followed
+/// through, each node's edges going left then right
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Network {
+    directions: Vec<bool>,
+    labels: Labels<String>,
+    nodes: Graph,
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Network, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut line_no = 0;
+
     while let Some(line) = rx.recv().await {
         if !line.is_empty() {
-            let task = tokio::spawn(async move { calc_line(&line).await });
-            tasks.push(task);
+            tasks.push(spawn_line(line_no, async move {
+                calc_line(line_no, &line).await
+            }));
         }
+        line_no += 1;
     }
 
-    let mut directions = VecDeque::new();
-    let mut nodes = HashMap::new();
+    let mut directions = Vec::new();
+    let mut labels = Labels::new();
+    let mut nodes = Graph::new();
 
-    for task in tasks {
-        if let Ok(input) = task.await {
-            match input {
-                Input::Directions(dir) => {
-                    directions = dir;
-                }
-                Input::Node(node) => {
-                    nodes.insert(node.0, (node.1, node.2));
+    for result in join_tasks(tasks).await? {
+        match result? {
+            Input::Directions(dir) => {
+                directions = dir;
+            }
+            Input::Node((start, left, right)) => {
+                let ids = [start, left, right].map(|name| labels.intern(name));
+                while nodes.len() < labels.len() {
+                    nodes.add_node();
                 }
-            };
-        }
+                nodes.add_edge(ids[0], ids[1], ());
+                nodes.add_edge(ids[0], ids[2], ());
+            }
+        };
     }
 
-    let part1 = count_to_zzz("AAA".to_string(), &nodes, &directions);
+    Ok(Network {
+        directions,
+        labels,
+        nodes,
+    })
+}
+
+pub fn part1(network: &Network) -> usize {
+    let (Some(start), Some(end)) = (network.labels.id("AAA"), network.labels.id("ZZZ")) else {
+        return 0;
+    };
+    steps_until(network, start, |node| node == end)
+}
+
+pub fn part2(network: &Network) -> usize {
+    let ends_z = |node: NodeId| network.labels.key(node).chars().nth(2) == Some('Z');
+    network
+        .labels
+        .iter()
+        .filter(|(_, key)| key.chars().nth(2) == Some('A'))
+        .map(|(start, _)| steps_until(network, start, ends_z))
+        .fold(1, |acc, num| lcm(acc, num))
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let network = parse(rx).await?;
 
-    let part2 = nodes
-        .keys()
-        .filter(|k| k.chars().skip(2).next().unwrap() == 'A')
-        .map(|key| period_to_xxz(key.to_string(), &nodes, &directions))
-        .fold(1, |acc, num| lcm(acc, num));
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&network)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&network)));
 
-    println!("Part 1: {part1}, Part 2: {part2}");
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day08;
+
+impl Solver for Day08 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
 
 fn gcd(a: usize, b: usize) -> usize {
@@ -68,57 +136,19 @@ fn lcm(a: usize, b: usize) -> usize {
     a / gcd(a, b) * b
 }
 
-fn count_to_zzz(
-    start: String,
-    nodes: &HashMap<String, (String, String)>,
-    directions: &VecDeque<bool>,
-) -> usize {
-    let mut current_node = start.clone();
-    let mut count = 0;
-    let mut directions = directions.clone();
-
-    loop {
-        count += 1;
-        if let Some((left, right)) = nodes.get(&current_node) {
-            current_node = if directions[0] {
-                right.to_string()
-            } else {
-                left.to_string()
-            }
-        }
-        directions.rotate_left(1);
-        if current_node == "ZZZ" {
-            break;
-        }
-    }
-
-    count
-}
+/// Follow the directions round and round from `start` until `done`
+fn steps_until(network: &Network, start: NodeId, done: impl Fn(NodeId) -> bool) -> usize {
+    let mut current_node = start;
 
-fn period_to_xxz(
-    start: String,
-    nodes: &HashMap<String, (String, String)>,
-    directions: &VecDeque<bool>,
-) -> usize {
-    let mut current_node = start.clone();
-    let mut count = 0;
-    let mut directions = directions.clone();
-
-    loop {
-        count += 1;
-        if let Some((left, right)) = nodes.get(&current_node) {
-            current_node = if directions[0] {
-                right.to_string()
-            } else {
-                left.to_string()
-            }
+    for (count, right) in network.directions.iter().cycle().enumerate() {
+        // nodes that are only ever pointed at have nowhere to go
+        if let [(left, _), (right_node, _)] = network.nodes.edges(current_node) {
+            current_node = if *right { *right_node } else { *left };
         }
-        directions.rotate_left(1);
-
-        if let Some(last_char) = current_node.chars().skip(2).next() {
-            if last_char == 'Z' {
-                return count;
-            }
+        if done(current_node) {
+            return count + 1;
         }
     }
+
+    unreachable!("there are no directions to follow")
 }
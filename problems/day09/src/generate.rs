@@ -0,0 +1,35 @@
+use crate::Day09;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const MAX_DEGREE: usize = 6;
+
+/// `lines` sequences of `width` values, each sampled from a random integer
+/// polynomial so the differences always bottom out at zero
+impl Generator for Day09 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let sequences = size.lines_or(200_000);
+        let length = size.width_or(21).max(2);
+        let mut out = String::new();
+
+        for _ in 0..sequences {
+            let degree = rng.gen_range(0..=std::cmp::min(MAX_DEGREE, length - 2));
+            let coeffs: Vec<i64> = (0..=degree).map(|_| rng.gen_range(-9..=9)).collect();
+
+            let values: Vec<String> = (0..length as i64)
+                .map(|x| {
+                    coeffs
+                        .iter()
+                        .rev()
+                        .fold(0i64, |acc, c| acc * x + c)
+                        .to_string()
+                })
+                .collect();
+            out.push_str(&values.join(" "));
+            out.push('\n');
+        }
+
+        out
+    }
+}
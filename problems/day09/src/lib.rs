@@ -1,20 +1,23 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
 use tokio::sync::mpsc::Receiver;
 
+const DAY: &str = "09";
+
 //0 3 6 9 12 15
 //1 3 6 10 15 21
 //10 13 16 21 30 45
-async fn calc_line(line: &str) -> (isize, isize) {
-    let sequence: Vec<isize> = line
-        .split(' ')
-        .map(|s| s.parse::<isize>().unwrap())
-        .collect();
-
-    let reverse_sequence: Vec<isize> = sequence.clone().into_iter().rev().collect();
+async fn calc_line(line_no: usize, line: &str) -> Result<Vec<isize>, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    line.split(' ').map(|s| input.parse(s)).collect()
+}
 
-    (
-        sequence[sequence.len() - 1] + next_diff(&sequence),
-        reverse_sequence[reverse_sequence.len() - 1] + next_diff(&reverse_sequence),
-    )
+fn extrapolate(sequence: &[isize]) -> isize {
+    sequence[sequence.len() - 1] + next_diff(sequence)
 }
 
 fn next_diff(values: &[isize]) -> isize {
@@ -30,27 +33,58 @@ fn next_diff(values: &[isize]) -> isize {
     diffs[diffs.len() - 1] + next_diff(&diffs)
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Vec<isize>>, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut line_no = 0;
+
     while let Some(line) = rx.recv().await {
         if !line.is_empty() {
-            let task = tokio::spawn(async move { calc_line(&line).await });
-            tasks.push(task);
+            tasks.push(spawn_line(line_no, async move {
+                calc_line(line_no, &line).await
+            }));
         }
+        line_no += 1;
     }
 
-    let mut next_values = Vec::new();
-    let mut prev_values = Vec::new();
+    let mut sequences = Vec::new();
+    for result in join_tasks(tasks).await? {
+        sequences.push(result?);
+    }
+    Ok(sequences)
+}
 
-    for task in tasks {
-        if let Ok((next_val, prev_val)) = task.await {
-            next_values.push(next_val);
-            prev_values.push(prev_val);
-        }
+pub fn part1(sequences: &[Vec<isize>]) -> isize {
+    sequences.iter().map(|seq| extrapolate(seq)).sum::<isize>()
+}
+
+pub fn part2(sequences: &[Vec<isize>]) -> isize {
+    sequences
+        .iter()
+        .map(|seq| {
+            let reverse_sequence: Vec<isize> = seq.iter().rev().copied().collect();
+            extrapolate(&reverse_sequence)
+        })
+        .sum::<isize>()
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let sequences = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&sequences)),
+        parts.part2().then(|| part2(&sequences)),
+    ))
+}
+
+pub struct Day09;
+
+impl Solver for Day09 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    let part1 = next_values.into_iter().sum::<isize>();
-    let part2 = prev_values.into_iter().sum::<isize>();
-    println!("Part 1: {part1}, Part 2: {part2}");
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
@@ -0,0 +1,76 @@
+use crate::Day10;
+use common::generate::{random_region, region_outline};
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+const JUNK: &[u8] = b"|-LJ7F.";
+
+fn pipe(a: (isize, isize), b: (isize, isize)) -> char {
+    // a and b are the (d_row, d_col) offsets to the two connected neighbours
+    let mut dirs = [a, b];
+    dirs.sort();
+    match dirs {
+        [(-1, 0), (1, 0)] => '|',
+        [(0, -1), (0, 1)] => '-',
+        [(-1, 0), (0, 1)] => 'L',
+        [(-1, 0), (0, -1)] => 'J',
+        [(0, -1), (1, 0)] => '7',
+        [(0, 1), (1, 0)] => 'F',
+        _ => unreachable!("pipe loop doubles back on itself"),
+    }
+}
+
+/// A `lines` x `width` field holding one closed pipe loop, the outline of a
+/// random blob, surrounded by junk pipes
+impl Generator for Day10 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(140).max(3);
+        let cols = size.width_or(140).max(3);
+
+        let region = random_region(rng, rows - 1, cols - 1, 0.45);
+        let outline = region_outline(&region);
+
+        let mut grid: Vec<Vec<char>> = (0..rows)
+            .map(|_| {
+                (0..cols)
+                    .map(|_| *JUNK.choose(rng).unwrap() as char)
+                    .collect()
+            })
+            .collect();
+
+        let len = outline.len();
+        for (idx, &(row, col)) in outline.iter().enumerate() {
+            let prev = outline[(idx + len - 1) % len];
+            let next = outline[(idx + 1) % len];
+            let offset =
+                |p: (usize, usize)| (p.0 as isize - row as isize, p.1 as isize - col as isize);
+            grid[row][col] = pipe(offset(prev), offset(next));
+        }
+
+        let &(row, col) = outline.choose(rng).unwrap();
+        grid[row][col] = 'S';
+
+        // junk next to the start must not look like it connects to it
+        let on_loop = |p: (usize, usize)| outline.contains(&p);
+        if row > 0 && !on_loop((row - 1, col)) {
+            grid[row - 1][col] = '.';
+        }
+        if col > 0 && !on_loop((row, col - 1)) {
+            grid[row][col - 1] = '.';
+        }
+        if row + 1 < rows && !on_loop((row + 1, col)) {
+            grid[row + 1][col] = '.';
+        }
+        if col + 1 < cols && !on_loop((row, col + 1)) {
+            grid[row][col + 1] = '.';
+        }
+
+        let mut out = String::new();
+        for line in grid {
+            out.extend(line);
+            out.push('\n');
+        }
+        out
+    }
+}
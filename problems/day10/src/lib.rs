@@ -1,81 +1,48 @@
-#![feature(extract_if)]
+mod generate;
 
 use colored::Colorize;
-use std::collections::HashMap;
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, Knob, Knobs, ParseError,
+    Parts, SolveError, SolveFuture, Solver,
+};
+use grid::{Direction, Grid, GridBuilder, GridError, Point};
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
-use tokio::sync::Mutex;
-use tokio::time::{sleep, Duration};
-
-#[derive(PartialEq, Clone, Copy, Debug)]
-enum Direction {
-    N,
-    E,
-    S,
-    W,
-}
+use tokio::sync::{Mutex, Notify};
+use tracing::{debug, info_span, trace, Level};
 
-impl Direction {
-    fn opp(&self) -> Self {
-        match self {
-            Self::N => Self::S,
-            Self::E => Self::W,
-            Self::S => Self::N,
-            Self::W => Self::E,
-        }
-    }
-}
+const DAY: &str = "10";
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Step {
     start_heading: Direction,
     end_heading: Direction,
-    location: (usize, usize),
+    location: Point,
 }
 
 impl Step {
-    fn right_side(&self, loc: &(usize, usize)) -> bool {
-        let start = match self.start_heading {
-            Direction::N => *loc == (self.location.0 + 1, self.location.1),
-            Direction::E => *loc == (self.location.0, self.location.1 + 1),
-            Direction::S => self.location == (loc.0 + 1, loc.1),
-            Direction::W => self.location == (loc.0, loc.1 + 1),
-        };
-
-        let end = match self.end_heading {
-            Direction::N => *loc == (self.location.0 + 1, self.location.1),
-            Direction::E => *loc == (self.location.0, self.location.1 + 1),
-            Direction::S => self.location == (loc.0 + 1, loc.1),
-            Direction::W => self.location == (loc.0, loc.1 + 1),
-        };
-
-        start | end
+    /// The cells beside this step on the right, looking along either heading
+    fn right(&self) -> impl Iterator<Item = Point> + '_ {
+        [self.start_heading, self.end_heading]
+            .into_iter()
+            .filter_map(|dir| dir.turn_right().step(self.location))
     }
-    fn left_side(&self, loc: &(usize, usize)) -> bool {
-        let start = match self.start_heading {
-            Direction::S => *loc == (self.location.0 + 1, self.location.1),
-            Direction::W => *loc == (self.location.0, self.location.1 + 1),
-            Direction::N => self.location == (loc.0 + 1, loc.1),
-            Direction::E => self.location == (loc.0, loc.1 + 1),
-        };
-        let end = match self.end_heading {
-            Direction::S => *loc == (self.location.0 + 1, self.location.1),
-            Direction::W => *loc == (self.location.0, self.location.1 + 1),
-            Direction::N => self.location == (loc.0 + 1, loc.1),
-            Direction::E => self.location == (loc.0, loc.1 + 1),
-        };
 
-        start | end
+    fn left(&self) -> impl Iterator<Item = Point> + '_ {
+        [self.start_heading, self.end_heading]
+            .into_iter()
+            .filter_map(|dir| dir.turn_left().step(self.location))
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 struct Animal {
     heading: Direction,
-    location: (usize, usize),
+    location: Point,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct Pipe {
     in_: Direction,
     out: Direction,
@@ -112,8 +79,20 @@ impl Pipe {
         }
     }
 
+    /// The pipe leading out `a` and `b`
+    fn symbol(a: Direction, b: Direction) -> char {
+        "|-F7JL"
+            .chars()
+            .find(|&chr| Pipe::new(chr).is_some_and(|pipe| pipe.joins(a) && pipe.joins(b)))
+            .expect("a pipe joins two different sides")
+    }
+
+    fn joins(&self, side: Direction) -> bool {
+        self.in_ == side || self.out == side
+    }
+
     fn pass(&self, animal: &mut Animal) -> bool {
-        let from_dir = animal.heading.opp();
+        let from_dir = animal.heading.opposite();
 
         let to_dir = if from_dir == self.in_ {
             self.out
@@ -124,222 +103,589 @@ impl Pipe {
         };
 
         animal.heading = to_dir;
-        match to_dir {
-            Direction::N => {
-                if animal.location.1 > 0 {
-                    animal.location.1 -= 1;
-                    return true;
-                }
-            }
-            Direction::E => {
-                animal.location.0 += 1;
-                return true;
-            }
-            Direction::S => {
-                animal.location.1 += 1;
-                return true;
+        match to_dir.step(animal.location) {
+            Some(next) => {
+                animal.location = next;
+                true
             }
-            Direction::W => {
-                if animal.location.0 > 0 {
-                    animal.location.0 -= 1;
-                    return true;
-                }
-            }
-        };
-
-        false
+            None => false,
+        }
     }
 }
 
-type PipeMap = Arc<Mutex<HashMap<(usize, usize), Pipe>>>;
+/// What has been read of the map so far
+#[derive(Default)]
+struct Arrived {
+    /// Pipes for the rows that have arrived, `None` where there is no pipe
+    pipes: GridBuilder<Option<Pipe>>,
+    /// Lines dealt with so far, good or bad
+    settled: usize,
+    /// How many lines there are, once the input has all been read
+    lines: Option<usize>,
+}
 
-async fn follow_animal(start: (usize, usize), pipemap: PipeMap) -> Vec<Step> {
-    let steps = [(1, 0), (0, 1)];
-    let dirs = [Direction::E, Direction::S];
+/// The map as it streams in, with word to anyone waiting whenever it changes
+#[derive(Default)]
+struct PipeMap {
+    arrived: Mutex<Arrived>,
+    changed: Notify,
+}
 
-    let mut animal = Animal {
-        heading: Direction::W,
-        location: start,
-    };
+impl PipeMap {
+    /// Put in a row, or note that the line couldn't be read so nobody waits
+    /// on it
+    async fn settle(&self, row: usize, cells: Option<Vec<Option<Pipe>>>) -> Result<(), GridError> {
+        let mut arrived = self.arrived.lock().await;
+        arrived.settled += 1;
+        let inserted = cells.map_or(Ok(()), |cells| arrived.pipes.insert_row(row, cells));
+        drop(arrived);
+        self.changed.notify_waiters();
+        inserted
+    }
 
-    let mut path = Vec::new();
+    async fn finish(&self, lines: usize) {
+        self.arrived.lock().await.lines = Some(lines);
+        self.changed.notify_waiters();
+    }
 
-    let pmap = pipemap.lock().await;
-    for (step, dir) in steps.into_iter().zip(dirs.into_iter()) {
-        animal.heading = dir;
-        animal.location = (start.0 + step.0, start.1 + step.1);
-        // check that there is a pipe in that spot
-        if let Some(pipe) = pmap.get(&(start.0 + step.0, start.1 + step.1)) {
-            if pipe.pass(&mut animal) {
-                path.push(Step {
-                    start_heading: dir,
-                    end_heading: dir,
-                    location: start,
-                });
-                path.push(Step {
-                    start_heading: dir,
-                    end_heading: dir,
-                    location: (start.0 + step.0, start.1 + step.1),
-                });
-                break;
+    /// The pipe at `point`, once its row has arrived. `None` where there is
+    /// no pipe, or when the point is off the map.
+    async fn pipe_at(&self, point: Point) -> Option<Pipe> {
+        loop {
+            // registered before looking, so a row that arrives in between
+            // still wakes us
+            let changed = self.changed.notified();
+            let arrived = self.arrived.lock().await;
+            if let Some(pipe) = arrived.pipes.get(point) {
+                return *pipe;
             }
+            if arrived.lines == Some(arrived.settled) {
+                return None;
+            }
+            drop(arrived);
+            trace!(location = ?point, "waiting for more data");
+            changed.await;
         }
     }
-    drop(pmap);
+}
 
-    if animal.location == start {
-        panic!("no first move");
-    }
-    let pmap = pipemap.lock().await;
+/// Walk the pipes from `start`, setting off `heading`. `None` when they lead
+/// anywhere but back to the start.
+async fn walk(start: Point, heading: Direction, pipemap: &PipeMap) -> Option<Vec<Step>> {
+    let mut animal = Animal {
+        heading,
+        location: heading.step(start)?,
+    };
+
+    let mut path = Vec::new();
     while animal.location != start {
-        if let Some(pipe) = pmap.get(&animal.location) {
-            let last_heading = animal.heading;
-            let last_location = animal.location;
-            pipe.pass(&mut animal);
-            path.push(Step {
-                start_heading: last_heading,
-                end_heading: animal.heading,
-                location: last_location,
-            });
-        } else {
-            // data still being populated, wait a few ms
-            sleep(Duration::from_millis(10)).await;
-            dbg!("waiting for more data");
+        let pipe = pipemap.pipe_at(animal.location).await?;
+        let (start_heading, location) = (animal.heading, animal.location);
+        if !pipe.pass(&mut animal) {
+            return None;
         }
+        path.push(Step {
+            start_heading,
+            end_heading: animal.heading,
+            location,
+        });
     }
 
-    path
+    path.insert(
+        0,
+        Step {
+            start_heading: animal.heading,
+            end_heading: heading,
+            location: start,
+        },
+    );
+    Some(path)
 }
 
-async fn process_line(line: &str, line_no: usize, pipemap: PipeMap) -> Option<Vec<Step>> {
-    let add_pipes = line
-        .chars()
-        .map(Pipe::new)
-        .enumerate()
-        .filter(|(_col, opt_pipe)| opt_pipe.is_some())
-        .map(|(col, opt_pipe)| ((col, line_no), opt_pipe.unwrap()));
+async fn follow_animal(start: Point, pipemap: &PipeMap) -> Option<Vec<Step>> {
+    for heading in Direction::ALL {
+        // only set off towards pipes that lead back into the start
+        let Some(next) = heading.step(start) else {
+            continue;
+        };
+        if !pipemap
+            .pipe_at(next)
+            .await
+            .is_some_and(|pipe| pipe.joins(heading.opposite()))
+        {
+            continue;
+        }
 
-    // fill in the pipemap
-    let mut pmap = pipemap.lock().await;
-    pmap.extend(add_pipes);
-    drop(pmap);
+        if let Some(path) = walk(start, heading, pipemap).await {
+            let first = &path[0];
+            let under = Pipe::symbol(first.start_heading.opposite(), first.end_heading);
+            debug!(?start, %under, "found the loop");
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+async fn process_line(
+    line: &str,
+    line_no: usize,
+    pipemap: Arc<PipeMap>,
+) -> Result<Option<Vec<Step>>, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let checked = input.only_chars("|-LJ7F.S");
+
+    // fill in the pipemap, a bad line still has to be counted off
+    let cells = checked
+        .is_ok()
+        .then(|| line.chars().map(Pipe::new).collect());
+    let inserted = pipemap.settle(line_no, cells).await;
+    checked?;
+    inserted.map_err(|e| ParseError::grid(DAY, e, line))?;
 
     // if this line contains the start
     // use this call to calculate the solution
     if let Some(col) = line.find('S') {
-        return Some(follow_animal((col, line_no), pipemap).await);
+        return match follow_animal((line_no, col), &pipemap).await {
+            Some(path) => Ok(Some(path)),
+            None => Err(input.error_at(&line[col..], "no loop of pipes runs through the start")),
+        };
     }
 
-    None
+    Ok(None)
 }
 
-fn count_enclosed(max_rows: usize, max_cols: usize, path: &[Step], print: bool) -> (usize, usize) {
-    let mut nonpath_cells = Vec::new();
-    let path_positions: Vec<_> = path.iter().map(|a| a.location).collect();
-    for row in 0..=max_rows {
-        for col in 0..=max_cols {
-            if !path_positions.contains(&(col, row)) {
-                nonpath_cells.push((col, row));
-            }
+/// Ways of counting the cells inside the loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Method {
+    /// The loop's area by the shoelace formula, less the loop itself by
+    /// Pick's theorem
+    #[default]
+    Shoelace,
+    /// Along each row, the cells after an odd number of crossings of the loop
+    Scanline,
+    /// Flood the cells off the loop and keep those on the side it turns
+    /// towards. Draws both sides at debug level.
+    Flood,
+}
+
+impl std::str::FromStr for Method {
+    type Err = String;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "shoelace" => Ok(Method::Shoelace),
+            "scanline" => Ok(Method::Scanline),
+            "flood" => Ok(Method::Flood),
+            _ => Err("expected shoelace, scanline or flood".to_string()),
         }
     }
+}
 
-    let mut areas = Vec::new();
-    let mut current_area = vec![nonpath_cells.pop().unwrap()];
-    while !nonpath_cells.is_empty() {
-        let mut flood: Vec<_> = nonpath_cells
-            .extract_if(|c| {
-                current_area
-                    .iter()
-                    .any(|c2| (c.0.abs_diff(c2.0) <= 1) & (c.1.abs_diff(c2.1) <= 1))
-            })
-            .collect();
+/// Every method has to count the same cells, so `check` runs them all
+const KNOBS: &[Knob] = &[Knob {
+    name: "method",
+    help: "how part 2 counts the cells inside the loop: shoelace, scanline or flood",
+    check: &["scanline", "flood"],
+}];
+
+/// Twice the area inside the loop, positive when it runs clockwise and the
+/// inside is on its right
+fn double_area(path: &[Step]) -> i64 {
+    let len = path.len();
+    (0..len)
+        .map(|idx| {
+            let (row, col) = path[idx].location;
+            let (next_row, next_col) = path[(idx + 1) % len].location;
+            col as i64 * next_row as i64 - next_col as i64 * row as i64
+        })
+        .sum()
+}
+
+fn shoelace(path: &[Step]) -> usize {
+    // the area through the middle of the loop's cells is the cells inside,
+    // plus half of those on the loop, less one for the corners turning round
+    let area = double_area(path).unsigned_abs() as usize;
+    (area + 2).saturating_sub(path.len()) / 2
+}
+
+fn scanline(rows: usize, cols: usize, path: &[Step]) -> usize {
+    // pipes joined to the row above cross it, bends that go back the way they
+    // came cross twice or not at all
+    let len = path.len();
+    let mut loop_cells = Grid::new(rows, cols, None);
+    for (idx, step) in path.iter().enumerate() {
+        let joined_above = [(idx + len - 1) % len, (idx + 1) % len]
+            .into_iter()
+            .any(|other| path[other].location.0 < step.location.0);
+        loop_cells[step.location] = Some(joined_above);
+    }
+
+    (0..rows)
+        .map(|row| {
+            let mut inside = false;
+            (0..cols)
+                .filter(|&col| match loop_cells[(row, col)] {
+                    Some(crosses) => {
+                        inside ^= crosses;
+                        false
+                    }
+                    None => inside,
+                })
+                .count()
+        })
+        .sum()
+}
+
+fn flood(rows: usize, cols: usize, path: &[Step], clockwise: bool) -> usize {
+    let mut on_path = Grid::new(rows, cols, false);
+    for step in path.iter() {
+        on_path[step.location] = true;
+    }
 
-        if flood.is_empty() {
-            areas.push(current_area.clone());
-            current_area.clear();
-            if let Some(cell) = nonpath_cells.pop() {
-                current_area = vec![cell];
+    // number the areas off the path. Only straight steps, a diagonal one can
+    // slip between two corners of the loop that touch.
+    let mut areas = Grid::new(rows, cols, None);
+    let mut sizes = Vec::new();
+    for start in on_path.positions(|cell| !cell) {
+        if areas[start].is_some() {
+            continue;
+        }
+        let area = sizes.len();
+        areas[start] = Some(area);
+        let (mut queue, mut size) = (vec![start], 0);
+        while let Some(cell) = queue.pop() {
+            size += 1;
+            for (_, next) in on_path.neighbours(cell) {
+                if !on_path[next] && areas[next].is_none() {
+                    areas[next] = Some(area);
+                    queue.push(next);
+                }
             }
-        } else {
-            current_area.append(&mut flood);
         }
+        sizes.push(size);
     }
 
-    areas.push(current_area);
+    let mut right = vec![false; sizes.len()];
+    let mut left = vec![false; sizes.len()];
+    for step in path.iter() {
+        for (side, cells) in [
+            (&mut right, step.right().collect::<Vec<_>>()),
+            (&mut left, step.left().collect()),
+        ] {
+            for cell in cells {
+                if let Some(Some(area)) = areas.get(cell) {
+                    side[*area] = true;
+                }
+            }
+        }
+    }
 
-    let right: Vec<_> = areas
-        .clone()
-        .into_iter()
-        .filter(|a| a.iter().any(|c| path.iter().any(|a| a.right_side(c))))
-        .flatten()
-        .collect();
+    if tracing::enabled!(Level::DEBUG) {
+        let mut picture = String::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                let tile = '#'.to_string();
+                let tile = match areas[(row, col)] {
+                    None => tile.red(),
+                    Some(area) if right[area] && left[area] => tile.purple(),
+                    Some(area) if right[area] => tile.yellow(),
+                    Some(area) if left[area] => tile.green(),
+                    Some(_) => tile.normal(),
+                };
+                picture.push_str(&tile.to_string());
+            }
+            picture.push('\n');
+        }
+        debug!("path and the two sides of it:\n{picture}");
+    }
 
-    let left: Vec<_> = areas
+    let inside = if clockwise { right } else { left };
+    sizes
         .into_iter()
-        .filter(|a| a.iter().any(|c| path.iter().any(|a| a.left_side(c))))
-        .flatten()
-        .collect();
+        .zip(inside)
+        .filter(|(_, inside)| *inside)
+        .map(|(size, _)| size)
+        .sum()
+}
 
-    assert_eq!(
-        (max_rows + 1) * (max_cols + 1),
-        right.len() + left.len() + path.len()
-    );
+/// The loop the animal runs around, traced while the map streams in, and
+/// the size of the map it sits in
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Maze {
+    path: Vec<Step>,
+    rows: usize,
+    cols: usize,
+}
 
-    println!();
-    if print {
-        for row in 0..=max_rows {
-            for col in 0..=max_cols {
-                if path_positions.contains(&(col, row)) {
-                    print!("{}", '#'.to_string().red());
-                } else if right.contains(&(col, row)) & left.contains(&(col, row)) {
-                    print!("{}", '#'.to_string().purple());
-                } else if right.contains(&(col, row)) {
-                    print!("{}", '#'.to_string().yellow());
-                } else if left.contains(&(col, row)) {
-                    print!("{}", '#'.to_string().green());
-                } else {
-                    print!("#");
-                }
-            }
-            println!()
-        }
+impl Maze {
+    /// The pipe hidden under the start, `None` if there was no start
+    pub fn start_pipe(&self) -> Option<char> {
+        let start = self.path.first()?;
+        Some(Pipe::symbol(
+            start.start_heading.opposite(),
+            start.end_heading,
+        ))
     }
-    println!();
+}
 
-    (right.len(), left.len())
+/// The maze needs exactly one start, `starts` holds the lines with an 'S'
+fn check_starts(starts: &[(usize, String)]) -> Result<(), SolveError> {
+    let second = match starts {
+        [] => {
+            return Err(SolveError::Unsolvable {
+                day: DAY,
+                message: "the maze has no S to start from".to_string(),
+            })
+        }
+        [(line_no, line)] => (line_no, line, line.match_indices('S').nth(1)),
+        [_, (line_no, line), ..] => (line_no, line, line.match_indices('S').next()),
+    };
+
+    match second {
+        (_, _, None) => Ok(()),
+        (&line_no, line, Some((col, _))) => Err(InputLine::new(DAY, line_no, line)
+            .error_at(&line[col..], "a second S, the maze has one start")
+            .into()),
+    }
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
-    let pipemap = Arc::new(Mutex::new(HashMap::new()));
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Maze, SolveError> {
+    let pipemap = Arc::new(PipeMap::default());
 
     let mut tasks = Vec::new();
+    let mut starts = Vec::new();
 
     while let Some((line_no, line)) = rx.recv().await {
         if !line.is_empty() {
+            if line.contains('S') {
+                starts.push((line_no, line.clone()));
+            }
             let pipemap_clone = Arc::clone(&pipemap);
-            let task =
-                tokio::spawn(async move { process_line(&line, line_no, pipemap_clone).await });
-            tasks.push(task);
+            tasks.push(spawn_line(line_no, async move {
+                process_line(&line, line_no, pipemap_clone).await
+            }));
         }
     }
 
+    pipemap.finish(tasks.len()).await;
+
+    let results = join_tasks(tasks).await?;
+    check_starts(&starts)?;
+
     let mut animal_path = Vec::new();
-    for task in tasks {
-        if let Ok(opt_dist) = task.await {
-            if let Some(path) = opt_dist {
-                animal_path = path;
-            }
+    for result in results {
+        if let Some(path) = result? {
+            animal_path = path;
         }
     }
 
-    let pmap = pipemap.lock().await;
-    let max_cols = pmap.keys().map(|c| c.0).max().unwrap();
-    let max_rows = pmap.keys().map(|c| c.1).max().unwrap();
-    drop(pmap);
+    let pipes = std::mem::take(&mut pipemap.arrived.lock().await.pipes)
+        .build()
+        .map_err(|e| ParseError::grid(DAY, e, ""))?;
+
+    Ok(Maze {
+        path: animal_path,
+        rows: pipes.rows(),
+        cols: pipes.cols(),
+    })
+}
+
+pub fn part1(maze: &Maze) -> usize {
+    maze.path.len() / 2
+}
+
+/// Cells inside the loop, counted by `method`
+pub fn count_enclosed(maze: &Maze, method: Method) -> usize {
+    let clockwise = double_area(&maze.path) > 0;
+    debug!(clockwise, ?method, "counting enclosed cells");
+    match method {
+        Method::Shoelace => shoelace(&maze.path),
+        Method::Scanline => scanline(maze.rows, maze.cols, &maze.path),
+        Method::Flood => flood(maze.rows, maze.cols, &maze.path, clockwise),
+    }
+}
 
-    let part1 = animal_path.len() / 2;
+pub fn part2(maze: &Maze, method: Method) -> usize {
+    count_enclosed(maze, method)
+}
 
-    let part2 = count_enclosed(max_rows, max_cols, &animal_path, true);
-    println!("Part 1: {}, Part 2: {:?}", part1, part2);
+pub async fn solve(
+    rx: Receiver<(usize, String)>,
+    parts: Parts,
+    knobs: Knobs,
+) -> Result<Answer, SolveError> {
+    let method = knobs.get_or(DAY, "method", Method::default())?;
+    let maze = parse(rx).await?;
+
+    let part1 = parts.part1().then(|| part1(&maze));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&maze, method)));
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        self.solve_with(input, parts, &Knobs::default())
+    }
+
+    fn knobs(&self) -> &'static [Knob] {
+        KNOBS
+    }
+
+    fn solve_with(&self, input: InputStream, parts: Parts, knobs: &Knobs) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts, knobs.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{GenSize, Generator};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    async fn maze(input: &str) -> Maze {
+        // room for every line, nothing reads them until they're all sent
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count());
+        for line in input.lines().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.unwrap()
+    }
+
+    fn every_method(maze: &Maze) -> [usize; 3] {
+        [Method::Shoelace, Method::Scanline, Method::Flood]
+            .map(|method| count_enclosed(maze, method))
+    }
+
+    #[tokio::test]
+    async fn examples() {
+        // the animal sets off clockwise round one and anticlockwise round the
+        // other
+        let squeezed = maze(
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+        )
+        .await;
+        assert_eq!(every_method(&squeezed), [4; 3]);
+
+        let larger = maze(
+            ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+        )
+        .await;
+        assert!(double_area(&squeezed.path) > 0 && double_area(&larger.path) < 0);
+        assert_eq!(every_method(&larger), [8; 3]);
+
+        // the start is a 7, with the row the animal heads into still to come
+        let junk = maze(
+            "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+        )
+        .await;
+        assert_eq!(junk.start_pipe(), Some('7'));
+        assert_eq!(every_method(&junk), [10; 3]);
+        assert_eq!(
+            [&squeezed, &larger].map(|maze| maze.start_pipe()),
+            [Some('F'); 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn start_on_the_edge() {
+        for (input, under) in [
+            ("S-7\n|.|\nL-J", 'F'),
+            ("F-7\n|.|\nL-S", 'J'),
+            ("F-7\nS.|\nL-J", '|'),
+            ("..F7\n.FJ|\nFJ.|\nL--S", 'J'),
+        ] {
+            let maze = maze(input).await;
+            assert_eq!(maze.start_pipe(), Some(under), "{input}");
+            assert_eq!(part1(&maze) * 2, maze.path.len(), "{input}");
+            assert_eq!(every_method(&maze), [1; 3], "{input}");
+        }
+    }
+
+    #[tokio::test]
+    async fn start_off_the_loop() {
+        let (tx, rx) = tokio::sync::mpsc::channel(3);
+        for line in ["F-7", "|S|", "L-J"].into_iter().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
+        }
+        drop(tx);
+        let error = parse(rx).await.err().unwrap().to_string();
+        assert!(
+            error.contains("no loop of pipes runs through the start"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn one_start() {
+        for (lines, expected) in [
+            (
+                ["F-7", "|.|", "L-J"],
+                "day 10: the maze has no S to start from",
+            ),
+            (["S-7", "|.|", "L-S"], "line 3, column 3: a second S"),
+            (["SS7", "|.|", "L-J"], "line 1, column 2: a second S"),
+        ] {
+            let (tx, rx) = tokio::sync::mpsc::channel(3);
+            for line in lines.into_iter().enumerate() {
+                tx.send((line.0, line.1.to_string())).await.unwrap();
+            }
+            drop(tx);
+            let error = parse(rx).await.err().unwrap().to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+
+    #[tokio::test]
+    async fn generated_methods_agree() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let size = GenSize {
+            lines: Some(300),
+            width: Some(300),
+        };
+        let input = Day10.generate(&mut rng, &size);
+        let maze = maze(&input).await;
+        let [shoelace, scanline, flood] = every_method(&maze);
+        assert_eq!((scanline, flood), (shoelace, shoelace));
+    }
 }
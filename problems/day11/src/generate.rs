@@ -0,0 +1,45 @@
+use crate::Day11;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const GALAXY_DENSITY: f64 = 0.02;
+const EMPTY_FRACTION: f64 = 0.05;
+
+/// A `lines` x `width` image of galaxies with some rows and columns left
+/// empty so that the universe expands
+impl Generator for Day11 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(140).max(2);
+        let cols = size.width_or(140).max(2);
+
+        let empty_rows: Vec<bool> = (0..rows).map(|_| rng.gen_bool(EMPTY_FRACTION)).collect();
+        let empty_cols: Vec<bool> = (0..cols).map(|_| rng.gen_bool(EMPTY_FRACTION)).collect();
+
+        let mut grid: Vec<Vec<char>> = (0..rows)
+            .map(|row| {
+                (0..cols)
+                    .map(|col| {
+                        let open = !empty_rows[row] && !empty_cols[col];
+                        if open && rng.gen_bool(GALAXY_DENSITY) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // always at least two galaxies to measure between
+        grid[0][0] = '#';
+        grid[rows - 1][cols - 1] = '#';
+
+        let mut out = String::new();
+        for line in grid {
+            out.extend(line);
+            out.push('\n');
+        }
+        out
+    }
+}
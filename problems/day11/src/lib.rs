@@ -1,29 +1,36 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use grid::{Grid, GridBuilder};
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
 
-async fn calc_line(line_no: usize, line: String) -> Vec<(usize, usize)> {
-    line.chars()
-        .enumerate()
-        .filter(|(_col, c)| *c == '#')
-        .map(|(col, _c)| (col, line_no))
-        .collect()
-}
+const DAY: &str = "11";
 
-fn expanded_distances(galaxies: &[(usize, usize)], factor: usize) -> usize {
-    let mut galaxies: Vec<_> = galaxies.iter().cloned().collect();
-    let x_vals: Vec<_> = galaxies.iter().map(|(x, _)| x).collect();
-    let y_vals: Vec<_> = galaxies.iter().map(|(_, y)| y).collect();
+async fn calc_line(line_no: usize, line: &str) -> Result<Vec<bool>, ParseError> {
+    InputLine::new(DAY, line_no, line).only_chars(".#")?;
+
+    Ok(line.chars().map(|c| c == '#').collect())
+}
 
-    let max_cols = x_vals.iter().cloned().max().unwrap();
-    let max_rows = y_vals.iter().cloned().max().unwrap();
+fn expanded_distances(image: &Grid<bool>, factor: usize) -> usize {
+    let mut galaxies: Vec<_> = image.positions(|galaxy| *galaxy).collect();
 
-    let empty_cols: Vec<_> = (0..=*max_cols).filter(|x| !x_vals.contains(&x)).collect();
-    let empty_rows: Vec<_> = (0..=*max_rows).filter(|y| !y_vals.contains(&y)).collect();
+    let empty_rows: Vec<_> = (0..image.rows())
+        .filter(|row| !image.row(*row).contains(&true))
+        .collect();
+    let empty_cols: Vec<_> = (0..image.cols())
+        .filter(|col| (0..image.rows()).all(|row| !image[(row, *col)]))
+        .collect();
 
     let expansion = factor - 1;
     let mut drift = 0usize;
-    for col in empty_cols.iter() {
+    for row in empty_rows.iter() {
         for galaxy in galaxies.iter_mut() {
-            if galaxy.0 > (col + drift) {
+            if galaxy.0 > (row + drift) {
                 galaxy.0 += expansion;
             }
         }
@@ -31,9 +38,9 @@ fn expanded_distances(galaxies: &[(usize, usize)], factor: usize) -> usize {
     }
 
     let mut drift = 0usize;
-    for row in empty_rows.iter() {
+    for col in empty_cols.iter() {
         for galaxy in galaxies.iter_mut() {
-            if galaxy.1 > (row + drift) {
+            if galaxy.1 > (col + drift) {
                 galaxy.1 += expansion;
             }
         }
@@ -53,23 +60,67 @@ fn expanded_distances(galaxies: &[(usize, usize)], factor: usize) -> usize {
     distances
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The telescope image, `true` where there is a galaxy
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Image {
+    grid: Grid<bool>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Image, SolveError> {
     let mut tasks = Vec::new();
 
     while let Some((line_no, line)) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(line_no, line).await });
-        tasks.push(task);
+        if !line.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                (line_no, calc_line(line_no, &line).await, line)
+            }));
+        }
     }
 
-    let mut galaxies = Vec::new();
-    for task in tasks {
-        if let Ok(mut gals) = task.await {
-            galaxies.append(&mut gals);
-        }
+    let mut builder = GridBuilder::new();
+    for (line_no, row, line) in join_tasks(tasks).await? {
+        builder
+            .insert_row(line_no, row?)
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
     }
+    let grid = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
 
-    let part1 = expanded_distances(&galaxies, 2);
-    let part2 = expanded_distances(&galaxies, 1_000_000);
+    Ok(Image { grid })
+}
 
-    println!("Part 1: {part1}  Part 2: {part2} ");
+pub fn part1(image: &Image) -> usize {
+    expanded_distances(&image.grid, 2)
+}
+
+pub fn part2(image: &Image) -> usize {
+    expanded_distances(&image.grid, 1_000_000)
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let image = parse(rx).await?;
+
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&image)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&image)));
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
@@ -0,0 +1,55 @@
+use crate::Day12;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const UNKNOWN_FRACTION: f64 = 0.4;
+
+/// `lines` condition records of up to `width` springs, each made by hiding
+/// part of a real arrangement so at least one arrangement fits
+impl Generator for Day12 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let records = size.lines_or(1000);
+        let width = size.width_or(20).max(1);
+        let mut out = String::new();
+
+        for _ in 0..records {
+            let length = rng.gen_range(1..=width);
+            let mut springs = String::new();
+            let mut groups = Vec::new();
+
+            while springs.len() < length {
+                if rng.gen_bool(0.5) {
+                    springs.push('.');
+                    continue;
+                }
+                let run = rng.gen_range(1..=std::cmp::min(6, length - springs.len()));
+                springs.push_str(&"#".repeat(run));
+                groups.push(run);
+                if springs.len() < length {
+                    springs.push('.');
+                }
+            }
+
+            if groups.is_empty() {
+                springs.replace_range(0..1, "#");
+                groups.push(1);
+            }
+
+            let hidden: String = springs
+                .chars()
+                .map(|c| {
+                    if rng.gen_bool(UNKNOWN_FRACTION) {
+                        '?'
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+            let groups: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+            out.push_str(&format!("{hidden} {}\n", groups.join(",")));
+        }
+
+        out
+    }
+}
@@ -1,10 +1,25 @@
-use std::collections::HashMap;
-
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, Knob, Knobs, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use rand::Rng;
+use std::fmt;
 use tokio::sync::mpsc::Receiver;
 
-const PART2_SCALE: usize = 5;
+const DAY: &str = "12";
+
+/// How many copies of each record part 2 joins together
+const PART2_UNFOLD: usize = 5;
+const KNOBS: &[Knob] = &[Knob {
+    name: "unfold",
+    help: "how many copies of each record part 2 joins together",
+    check: &[],
+}];
 
 #[derive(Debug, Clone, PartialEq, Copy, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Spring {
     Damaged,
     Operational,
@@ -12,171 +27,405 @@ enum Spring {
 }
 
 impl Spring {
-    fn new(chr: char) -> Self {
+    fn new(chr: char) -> Option<Self> {
         match chr {
-            '?' => Self::Unknown,
-            '.' => Self::Operational,
-            '#' => Self::Damaged,
-            _ => panic!("invalid spring input"),
+            '?' => Some(Self::Unknown),
+            '.' => Some(Self::Operational),
+            '#' => Some(Self::Damaged),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            Self::Unknown => '?',
+            Self::Operational => '.',
+            Self::Damaged => '#',
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct RunCalc {
-    runs: Vec<u8>, // current runs calculated
-    open: bool,    // whether the last run is still open (could increase)
-    springs: Vec<Spring>,
+/// Where a count has got to: how far into the springs, how many groups are
+/// done and how long the group being laid down is so far
+type State = (usize, usize, usize);
+
+/// How many ways there are to finish a record from each state
+struct Ways<'a> {
+    record: &'a Record,
+    longest: usize,
+    table: Vec<u128>,
 }
 
-impl RunCalc {
-    fn new(springs: Vec<Spring>) -> Self {
-        Self {
-            runs: Vec::new(),
-            open: false,
-            // reverse the springs so we can pop off springs as we go
-            springs: springs.into_iter().rev().collect(),
+impl<'a> Ways<'a> {
+    fn new(record: &'a Record) -> Self {
+        let (len, groups) = (record.springs.len(), &record.groups);
+        let longest = groups.iter().copied().max().unwrap_or(0);
+        let mut ways = Self {
+            record,
+            longest,
+            table: vec![0; (len + 1) * (groups.len() + 1) * (longest + 1)],
+        };
+
+        // only runs no longer than their group can be reached
+        let states = |pos| {
+            (0..=groups.len()).flat_map(move |group| {
+                (0..=groups.get(group).copied().unwrap_or(0)).map(move |run| (pos, group, run))
+            })
+        };
+        for state @ (_, group, run) in states(len) {
+            let done = (group == groups.len() && run == 0)
+                || (group + 1 == groups.len() && run == groups[group]);
+            let idx = ways.index(state);
+            ways.table[idx] = done.into();
+        }
+        for pos in (0..len).rev() {
+            for state in states(pos) {
+                let total = [Spring::Damaged, Spring::Operational]
+                    .into_iter()
+                    .filter_map(|spring| ways.next(state, spring))
+                    // a count that doesn't fit sticks at the top, see `arrangements`
+                    .fold(0u128, |total, next| total.saturating_add(ways.get(next)));
+                let idx = ways.index(state);
+                ways.table[idx] = total;
+            }
         }
+
+        ways
     }
 
-    fn valid(&self, target: &[u8]) -> bool {
-        if self.springs.is_empty() {
-            return self.runs == target;
-        }
+    fn index(&self, (pos, group, run): State) -> usize {
+        (pos * (self.record.groups.len() + 1) + group) * (self.longest + 1) + run
+    }
 
-        if self.open {
-            let matches: Vec<_> = self
-                .runs
-                .iter()
-                .zip(target.iter())
-                .map(|(s, t)| s == t)
-                .collect();
+    fn get(&self, state: State) -> u128 {
+        self.table[self.index(state)]
+    }
 
-            let closed = matches.iter().rev().skip(1).all(|&b| b);
+    /// The state after putting `spring` down next, `None` if it doesn't fit
+    /// the record
+    fn next(&self, (pos, group, run): State, spring: Spring) -> Option<State> {
+        let here = self.record.springs[pos];
+        if here != Spring::Unknown && here != spring {
+            return None;
+        }
 
-            if self.runs.len() > target.len() {
-                return false;
+        let groups = &self.record.groups;
+        match spring {
+            Spring::Damaged => {
+                (group < groups.len() && run < groups[group]).then_some((pos + 1, group, run + 1))
             }
+            _ if run == 0 => Some((pos + 1, group, 0)),
+            _ => (run == groups[group]).then_some((pos + 1, group + 1, 0)),
+        }
+    }
+}
+
+/// One row of the condition report
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    springs: Vec<Spring>,
+    /// Lengths of the runs of damaged springs, in order
+    groups: Vec<usize>,
+}
 
-            let run_len = self.runs.len() - 1;
-            let open = target[run_len] >= self.runs[run_len];
+impl Record {
+    /// Repeat the springs `times` times joined by unknowns, and the groups to
+    /// match
+    pub fn unfold(&self, times: usize) -> Self {
+        let mut springs: Vec<_> = (0..times)
+            .flat_map(|_| {
+                let mut s = self.springs.clone();
+                s.push(Spring::Unknown);
+                s
+            })
+            .collect();
+        springs.pop();
 
-            return open & closed;
-        }
+        let groups: Vec<_> = (0..times).flat_map(|_| self.groups.clone()).collect();
 
-        self.runs.iter().zip(target.iter()).all(|(s, t)| s == t)
+        Self { springs, groups }
     }
 
-    fn assess(&mut self) {
-        let next_spring = self.springs.pop().expect("assessing empty spring list");
-        match (self.open, next_spring) {
-            (true, Spring::Damaged) => {
-                let run_length = self.runs.len() - 1;
-                self.runs[run_length] += 1;
-            }
-            (true, Spring::Operational) => {
-                self.open = false;
+    /// How many ways the unknown springs can be filled in to fit the groups,
+    /// `None` when there are too many for a `u128`
+    pub fn arrangements(&self) -> Option<u128> {
+        Some(Ways::new(self).get((0, 0, 0))).filter(|&ways| ways < u128::MAX)
+    }
+
+    /// Every way of filling in the unknown springs, damaged before
+    /// operational at each unknown. There can be a great many.
+    pub fn every_arrangement(&self) -> impl Iterator<Item = String> + '_ {
+        let ways = Ways::new(self);
+        let mut stack = Vec::new();
+        if ways.get((0, 0, 0)) > 0 {
+            stack.push(((0, 0, 0), String::new()));
+        }
+
+        std::iter::from_fn(move || {
+            while let Some((state, laid)) = stack.pop() {
+                if state.0 == self.springs.len() {
+                    return Some(laid);
+                }
+                // only go where there's some way to finish
+                for spring in [Spring::Operational, Spring::Damaged] {
+                    if let Some(next) = ways.next(state, spring).filter(|&next| ways.get(next) > 0)
+                    {
+                        let mut laid = laid.clone();
+                        laid.push(spring.symbol());
+                        stack.push((next, laid));
+                    }
+                }
             }
-            (false, Spring::Damaged) => {
-                self.open = true;
-                self.runs.push(1);
+            None
+        })
+    }
+
+    /// One arrangement picked at random, each as likely as any other. `None`
+    /// when there are none.
+    pub fn sample_arrangement(&self, rng: &mut impl Rng) -> Option<String> {
+        let ways = Ways::new(self);
+        let mut state = (0, 0, 0);
+        if ways.get(state) == 0 {
+            return None;
+        }
+
+        // take each way on in proportion to how many arrangements follow it
+        let mut laid = String::new();
+        while state.0 < self.springs.len() {
+            let mut pick = rng.gen_range(0..ways.get(state));
+            for spring in [Spring::Damaged, Spring::Operational] {
+                let Some(next) = ways.next(state, spring) else {
+                    continue;
+                };
+                if pick < ways.get(next) {
+                    laid.push(spring.symbol());
+                    state = next;
+                    break;
+                }
+                pick -= ways.get(next);
             }
-            (_, _) => (),
         }
+        Some(laid)
     }
+}
 
-    fn step(&self) -> Vec<Self> {
-        // terminal condition?
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let springs: String = self.springs.iter().map(|spring| spring.symbol()).collect();
+        let groups: Vec<_> = self.groups.iter().map(|group| group.to_string()).collect();
+        write!(f, "{springs} {}", groups.join(","))
+    }
+}
 
-        if self.springs.last().expect("one step too far") != &Spring::Unknown {
-            let mut out = self.clone();
-            out.assess();
-            return vec![out];
-        }
+async fn calc_line(line_no: usize, line: &str) -> Result<Record, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let (springs_str, groups_str) = input.split_once(line, " ")?;
+
+    let springs: Vec<Spring> = springs_str
+        .char_indices()
+        .map(|(idx, chr)| {
+            Spring::new(chr).ok_or_else(|| {
+                input.error_at(&springs_str[idx..], format!("invalid spring {chr:?}"))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let groups: Vec<usize> = groups_str
+        .split(',')
+        .map(|s| input.parse(s))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Record { springs, groups })
+}
 
-        let mut out1 = self.clone();
-        out1.springs.pop();
-        out1.springs.push(Spring::Damaged);
-        out1.assess();
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Record>, SolveError> {
+    let mut tasks = Vec::new();
 
-        let mut out2 = self.clone();
-        out2.springs.pop();
-        out2.springs.push(Spring::Operational);
-        out2.assess();
+    let mut line_no = 0;
 
-        vec![out1, out2]
+    while let Some(line) = rx.recv().await {
+        tasks.push(spawn_line(line_no, async move {
+            calc_line(line_no, &line).await
+        }));
+        line_no += 1;
     }
-}
 
-fn possible_arrangements(springs: Vec<Spring>, counts: Vec<u8>) -> usize {
-    let length = springs.len();
-    let mut run_calcs = HashMap::new();
-    run_calcs.insert(RunCalc::new(springs), 1);
+    let mut records = Vec::new();
+    for result in join_tasks(tasks).await? {
+        records.push(result?);
+    }
+    Ok(records)
+}
 
-    for _ in 0..length {
-        let updates: Vec<_> = run_calcs
-            .into_iter()
-            .flat_map(|(rc, count)| {
-                rc.step()
-                    .into_iter()
-                    .map(|rc1| (rc1, count))
-                    .collect::<Vec<(RunCalc, usize)>>()
-            })
-            .filter(|(rc, _count)| rc.valid(&counts))
-            .collect();
+/// Count every record's arrangements on its own task, once unfolded `times`
+/// times
+pub async fn total_arrangements(records: &[Record], times: usize) -> Result<u128, SolveError> {
+    let tasks = records
+        .iter()
+        .enumerate()
+        .map(|(line_no, record)| {
+            let record = record.unfold(times);
+            spawn_line(line_no, async move { (line_no, record.arrangements()) })
+        })
+        .collect();
 
-        run_calcs = HashMap::new();
-        for (rc, count) in updates {
-            *run_calcs.entry(rc).or_insert(0) += count;
-        }
+    let too_many = |what: String| SolveError::Unsolvable {
+        day: DAY,
+        message: format!("{what} more arrangements than fit in a u128"),
+    };
+    let mut total = 0u128;
+    for (line_no, ways) in join_tasks(tasks).await? {
+        let ways = ways.ok_or_else(|| too_many(format!("line {} has", line_no + 1)))?;
+        total = total
+            .checked_add(ways)
+            .ok_or_else(|| too_many("the records have".to_string()))?;
     }
+    Ok(total)
+}
 
-    run_calcs.values().sum()
+pub async fn part1(records: &[Record]) -> Result<u128, SolveError> {
+    total_arrangements(records, 1).await
 }
 
-async fn calc_line(line: &str) -> (usize, usize) {
-    let (springs_str, counts_str) = line.split_once(' ').unwrap();
+pub async fn part2(records: &[Record], unfold: usize) -> Result<u128, SolveError> {
+    total_arrangements(records, unfold).await
+}
 
-    let springs: Vec<Spring> = springs_str.chars().map(Spring::new).collect();
-    let counts: Vec<u8> = counts_str
-        .split(',')
-        .map(|s| s.parse::<u8>().unwrap())
-        .collect();
+pub async fn solve(rx: Receiver<String>, parts: Parts, knobs: Knobs) -> Result<Answer, SolveError> {
+    let unfold = knobs.get_or(DAY, "unfold", PART2_UNFOLD)?;
+    if unfold == 0 {
+        return Err(SolveError::Knob {
+            day: DAY,
+            message: "unfold needs at least one copy of each record".to_string(),
+        });
+    }
+    let records = parse(rx).await?;
+
+    let part1 = match parts.part1() {
+        true => Some(part1(&records).await?),
+        false => None,
+    };
+    let part2 = match parts.part2() {
+        true => Some(part2(&records, unfold).await?),
+        false => None,
+    };
+
+    Ok(Answer::from_parts(part1, part2))
+}
 
-    let mut springs2: Vec<_> = (0..PART2_SCALE)
-        .flat_map(|_| {
-            let mut s = springs.clone();
-            s.push(Spring::Unknown);
-            s
-        })
-        .collect();
+pub struct Day12;
+
+impl Solver for Day12 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
 
-    springs2 = springs2.into_iter().rev().skip(1).rev().collect();
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        self.solve_with(input, parts, &Knobs::default())
+    }
 
-    let counts2: Vec<_> = (0..PART2_SCALE).map(|_| counts.clone()).flatten().collect();
+    fn knobs(&self) -> &'static [Knob] {
+        KNOBS
+    }
 
-    (
-        possible_arrangements(springs, counts),
-        possible_arrangements(springs2, counts2),
-    )
+    fn solve_with(&self, input: InputStream, parts: Parts, knobs: &Knobs) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts, knobs.clone()))
+    }
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
-    let mut tasks = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{GenSize, Generator};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const EXAMPLE: [&str; 6] = [
+        "???.### 1,1,3",
+        ".??..??...?##. 1,1,3",
+        "?#?#?#?#?#?#?#? 1,3,1,6",
+        "????.#...#... 4,1,1",
+        "????.######..#####. 1,6,5",
+        "?###???????? 3,2,1",
+    ];
+
+    async fn record(line: &str) -> Record {
+        calc_line(0, line).await.unwrap()
+    }
 
-    while let Some(line) = rx.recv().await {
-        let task = tokio::spawn(async move { calc_line(&line).await });
-        tasks.push(task);
+    /// Whether `laid` fills in the unknowns of `record` so the groups fit
+    fn fits(record: &Record, laid: &str) -> bool {
+        let agrees = laid
+            .chars()
+            .zip(record.springs.iter())
+            .all(|(chr, spring)| *spring == Spring::Unknown || spring.symbol() == chr);
+        let groups: Vec<_> = laid
+            .split('.')
+            .filter(|run| !run.is_empty())
+            .map(|run| run.len())
+            .collect();
+        laid.len() == record.springs.len() && agrees && groups == record.groups
     }
 
-    let mut part1 = 0usize;
-    let mut part2 = 0usize;
-    for task in tasks {
-        if let Ok((options1, options2)) = task.await {
-            part1 += options1;
-            part2 += options2;
+    #[tokio::test]
+    async fn example() {
+        let mut records = Vec::new();
+        for line in EXAMPLE {
+            records.push(record(line).await);
         }
+
+        let folded: Vec<_> = records.iter().map(|r| r.arrangements().unwrap()).collect();
+        assert_eq!(folded, [1, 4, 1, 1, 4, 10]);
+        let unfolded: Vec<_> = records
+            .iter()
+            .map(|r| r.unfold(PART2_UNFOLD).arrangements().unwrap())
+            .collect();
+        assert_eq!(unfolded, [1, 16384, 1, 16, 2500, 506250]);
+        assert_eq!(part1(&records).await, Ok(21));
+        assert_eq!(part2(&records, PART2_UNFOLD).await, Ok(525152));
+        assert_eq!(part2(&records, 1).await, Ok(21));
+        assert_eq!(records[1].to_string(), EXAMPLE[1]);
     }
 
-    println!("Part 1: {part1} Part 2: {part2}");
+    #[tokio::test]
+    async fn past_u64() {
+        // a lone damaged spring in every pair, so 60 copies leave 60 of 120
+        // unknowns damaged with none side by side: 120 choose 60 ways
+        let pairs = record("?? 1").await.unfold(60);
+        assert_eq!(
+            pairs.arrangements(),
+            Some(96_614_908_840_363_322_603_893_139_521_372_656)
+        );
+
+        // far past a u128 is an error, not a panic or a wrapped count
+        let pairs = [record("?? 1").await];
+        assert_eq!(
+            part2(&pairs, 200).await,
+            Err(SolveError::Unsolvable {
+                day: DAY,
+                message: "line 1 has more arrangements than fit in a u128".to_string()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn listed_and_sampled() {
+        let last = record(EXAMPLE[5]).await;
+        let listed: Vec<_> = last.every_arrangement().collect();
+        assert_eq!(listed.len(), 10);
+        assert_eq!(listed[0], ".###.##.#...");
+        assert!(listed.iter().all(|laid| fits(&last, laid)));
+        assert_eq!(record("#.# 1,2").await.every_arrangement().count(), 0);
+
+        let mut rng = StdRng::seed_from_u64(12);
+        let input = Day12.generate(&mut rng, &GenSize::default());
+        for line in input.lines().take(200) {
+            let record = record(line).await;
+            let listed: Vec<_> = record.every_arrangement().collect();
+            assert_eq!(Some(listed.len() as u128), record.arrangements(), "{line}");
+            assert!(listed.iter().all(|laid| fits(&record, laid)), "{line}");
+            let sampled = record.sample_arrangement(&mut rng).unwrap();
+            assert!(listed.contains(&sampled), "{line}: {sampled}");
+        }
+    }
 }
@@ -0,0 +1,130 @@
+use crate::Day13;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+type Pattern = Vec<Vec<bool>>;
+
+fn transpose(pattern: &Pattern) -> Pattern {
+    (0..pattern[0].len())
+        .map(|col| pattern.iter().map(|row| row[col]).collect())
+        .collect()
+}
+
+/// Every line of reflection, as (vertical, split)
+fn reflections(pattern: &Pattern) -> Vec<(bool, usize)> {
+    let rows = pattern.len();
+    let cols = pattern[0].len();
+    let mut out = Vec::new();
+
+    for split in 1..cols {
+        if pattern.iter().all(|row| {
+            (0..split)
+                .rev()
+                .zip(split..cols)
+                .all(|(a, b)| row[a] == row[b])
+        }) {
+            out.push((true, split));
+        }
+    }
+    for split in 1..rows {
+        if (0..split)
+            .rev()
+            .zip(split..rows)
+            .all(|(a, b)| pattern[a] == pattern[b])
+        {
+            out.push((false, split));
+        }
+    }
+
+    out
+}
+
+/// Build a pattern with exactly one line of reflection and exactly one smudge
+/// that, when fixed, reveals a single different line
+fn smudged_pattern(rng: &mut StdRng, max_size: usize) -> Pattern {
+    loop {
+        let rows = rng.gen_range(5..=max_size);
+        let cols = rng.gen_range(5..=max_size);
+
+        // the original line is vertical at `split`, it must leave some columns
+        // unmirrored so the smudge can hide there without breaking it
+        let split = rng.gen_range(1..cols);
+        if split * 2 == cols {
+            continue;
+        }
+        let free_cols = if split * 2 < cols {
+            (split * 2)..cols
+        } else {
+            0..(split * 2 - cols)
+        };
+        let hidden_split = rng.gen_range(1..rows);
+
+        let mut pattern: Pattern = (0..rows)
+            .map(|_| (0..cols).map(|_| rng.gen_bool(0.5)).collect())
+            .collect();
+        for row in pattern.iter_mut() {
+            for (a, b) in (0..split).rev().zip(split..cols) {
+                row[b] = row[a];
+            }
+        }
+        for (a, b) in (0..hidden_split).rev().zip(hidden_split..rows) {
+            pattern[b] = pattern[a].clone();
+        }
+
+        // smudge a mirrored cell of the hidden horizontal line
+        let row_lo = (hidden_split * 2).saturating_sub(rows);
+        let row_hi = std::cmp::min(rows, hidden_split * 2);
+        let smudge_row = rng.gen_range(row_lo..row_hi);
+        let smudge_col = rng.gen_range(free_cols);
+        pattern[smudge_row][smudge_col] = !pattern[smudge_row][smudge_col];
+
+        let original = reflections(&pattern);
+        if original != [(true, split)] {
+            continue;
+        }
+
+        let mut revealed = Vec::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                pattern[row][col] = !pattern[row][col];
+                revealed.extend(
+                    reflections(&pattern)
+                        .into_iter()
+                        .filter(|line| *line != (true, split)),
+                );
+                pattern[row][col] = !pattern[row][col];
+            }
+        }
+        revealed.sort_unstable();
+        revealed.dedup();
+
+        if revealed == [(false, hidden_split)] {
+            return pattern;
+        }
+    }
+}
+
+/// `lines` mirror patterns, each side between 5 and `width` cells
+impl Generator for Day13 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let patterns = size.lines_or(100);
+        let max_size = size.width_or(15).max(5);
+
+        let blocks: Vec<String> = (0..patterns)
+            .map(|_| {
+                let mut pattern = smudged_pattern(rng, max_size);
+                if rng.gen_bool(0.5) {
+                    pattern = transpose(&pattern);
+                }
+                pattern
+                    .iter()
+                    .map(|row| row.iter().map(|&b| if b { '#' } else { '.' }).collect())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect();
+
+        blocks.join("\n\n") + "\n"
+    }
+}
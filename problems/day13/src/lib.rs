@@ -1,81 +1,77 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, split_blocks, Answer, Framing, InputLine, InputStream, ParseError,
+    Parts, SolveError, SolveFuture, Solver,
+};
+use grid::{Grid, Point};
 use std::iter::{Rev, Zip};
 use std::ops::Range;
 use std::str::FromStr;
 use tokio::sync::mpsc::Receiver;
+use tracing::warn;
+
+const DAY: &str = "13";
 
 const BLEED: usize = 1;
 
-#[derive(Debug)]
-struct Mirror {
-    values: Vec<bool>,
-    row_len: usize,
-    col_len: usize,
-    flip: Option<usize>,
+/// One pattern of ash and rocks, `true` for rock
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mirror {
+    grid: Grid<bool>,
+    flip: Option<Point>,
 }
 
 impl FromStr for Mirror {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines: Vec<&str> = s
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .collect();
-        let row_len = lines[0].len();
-        let col_len = lines.len();
-
-        let values = lines
-            .into_iter()
-            .flat_map(|l| l.chars().collect::<Vec<char>>())
-            .filter(|c| ['.', '#'].contains(c))
-            .map(|c| c == '#')
-            .collect();
-
-        Ok(Mirror {
-            values,
-            row_len,
-            col_len,
-            flip: None,
-        })
+        Self::parse(&InputLine::new(DAY, 0, s))
     }
 }
 
 impl Mirror {
-    fn _get(&self, x: usize, y: usize) -> Option<bool> {
-        if (x >= self.row_len) | (y >= self.col_len) {
-            return None;
-        }
+    fn parse(input: &InputLine) -> Result<Self, ParseError> {
+        let mut grid = Grid::default();
 
-        let index1d = x + y * self.row_len;
-
-        if let Some(flipped_bit) = self.flip {
-            if index1d == flipped_bit {
-                return Some(!self.values[index1d]);
+        for line in input
+            .text
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+        {
+            if let Some(idx) = line.find(|c| (c != '.') && (c != '#')) {
+                return Err(input.error_at(&line[idx..], "expected . or #"));
             }
+            grid.push_row(line.chars().map(|c| c == '#').collect())
+                .map_err(|_| input.error_at(line, "rows differ in length"))?;
+        }
+        if grid.rows() == 0 {
+            return Err(input.error("empty pattern"));
         }
 
-        Some(self.values[index1d])
+        Ok(Mirror { grid, flip: None })
     }
 
-    fn _mirror_zip(&self, split: usize, horizontal: bool) -> Zip<Rev<Range<usize>>, Range<usize>> {
+    fn get(&self, point: Point) -> Option<bool> {
+        let value = *self.grid.get(point)?;
+        Some(value ^ (self.flip == Some(point)))
+    }
+
+    fn mirror_zip(split: usize, limit: usize) -> Zip<Rev<Range<usize>>, Range<usize>> {
         // get the pairs of indices that should be equal in a reflection
-        let limit = if horizontal {
-            self.col_len
-        } else {
-            self.row_len
-        };
         (0..split).rev().zip(split..limit)
     }
 
     fn vertical_reflection(&self, ignore: usize) -> Option<usize> {
-        'a: for split in BLEED..=(self.row_len - BLEED) {
+        'a: for split in BLEED..=(self.grid.cols() - BLEED) {
             if split == ignore {
                 continue;
             }
-            for compare_pair in self._mirror_zip(split, false) {
-                for idx in 0..self.col_len {
-                    if self._get(compare_pair.0, idx) != self._get(compare_pair.1, idx) {
+            for compare_pair in Self::mirror_zip(split, self.grid.cols()) {
+                for row in 0..self.grid.rows() {
+                    if self.get((row, compare_pair.0)) != self.get((row, compare_pair.1)) {
                         continue 'a;
                     }
                 }
@@ -87,13 +83,13 @@ impl Mirror {
     }
 
     fn horizontal_reflection(&self, ignore: usize) -> Option<usize> {
-        'a: for split in BLEED..=(self.col_len - BLEED) {
+        'a: for split in BLEED..=(self.grid.rows() - BLEED) {
             if split == ignore {
                 continue;
             }
-            for compare_pair in self._mirror_zip(split, true) {
-                for idx in 0..self.row_len {
-                    if self._get(idx, compare_pair.0) != self._get(idx, compare_pair.1) {
+            for compare_pair in Self::mirror_zip(split, self.grid.rows()) {
+                for col in 0..self.grid.cols() {
+                    if self.get((compare_pair.0, col)) != self.get((compare_pair.1, col)) {
                         continue 'a;
                     }
                 }
@@ -116,47 +112,92 @@ impl Mirror {
 
         (vert_score, hori_score)
     }
+
+    fn score(&self) -> usize {
+        let (vert, hori) = self.summarize(0, 0);
+        vert + hori * 100
+    }
+
+    /// Score of the other reflection line that fixing the smudge reveals
+    fn smudged_score(&self, block_no: usize) -> usize {
+        let mut mirr = self.clone();
+        mirr.flip = None;
+        let ref_score = mirr.summarize(0, 0);
+
+        for (point, _) in self.grid.iter() {
+            mirr.flip = Some(point);
+            let score = mirr.summarize(ref_score.0, ref_score.1);
+
+            if (score.0 > 0) & (score.0 != ref_score.0) {
+                return score.0;
+            }
+            if (score.1 > 0) & (score.1 != ref_score.1) {
+                return score.1 * 100;
+            }
+        }
+        warn!(block = block_no, "no smudge changes the reflection");
+        ref_score.0 + ref_score.1 * 100
+    }
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Mirror>, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut block_no = 0;
+
     while let Some(mirror) = rx.recv().await {
-        let task = tokio::spawn(async move {
-            let mut mirr = Mirror::from_str(&mirror).unwrap();
-            let ref_score = mirr.summarize(0, 0);
+        tasks.push(spawn_line(block_no, async move {
+            Mirror::parse(&InputLine::new(DAY, block_no, &mirror))
+        }));
+        block_no += 1;
+    }
 
-            for bit in 0..(mirr.row_len * mirr.col_len) {
-                mirr.flip = Some(bit);
-                let score = mirr.summarize(ref_score.0, ref_score.1);
+    let mut mirrors = Vec::new();
+    for result in join_tasks(tasks).await? {
+        mirrors.push(result?);
+    }
+    Ok(mirrors)
+}
 
-                if (score.0 > 0) & (score.0 != ref_score.0) {
-                    return (ref_score.0 + ref_score.1 * 100, score.0);
-                }
-                if (score.1 > 0) & (score.1 != ref_score.1) {
-                    return (ref_score.0 + ref_score.1 * 100, score.1 * 100);
-                }
-            }
-            dbg!("shouldn't get here");
-            (
-                ref_score.0 + ref_score.1 * 100,
-                ref_score.0 + ref_score.1 * 100,
-            )
-        });
-        tasks.push(task);
-    }
-
-    let mut total_score1 = 0;
-    let mut total_score2 = 0;
-
-    for task in tasks {
-        if let Ok((score1, score2)) = task.await {
-            total_score1 += score1;
-            total_score2 += score2;
-        }
+pub fn part1(mirrors: &[Mirror]) -> usize {
+    mirrors.iter().map(Mirror::score).sum()
+}
+
+pub fn part2(mirrors: &[Mirror]) -> usize {
+    mirrors
+        .iter()
+        .enumerate()
+        .map(|(block_no, mirr)| mirr.smudged_score(block_no))
+        .sum()
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let mirrors = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&mirrors)),
+        parts.part2().then(|| part2(&mirrors)),
+    ))
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    println!("Part 1: {total_score1} Part 2: {total_score2}");
+    fn split_input(&self, raw: &str) -> Vec<String> {
+        split_blocks(raw)
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Blocks
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,26 @@
+use crate::Day14;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A `lines` x `width` platform of round and cube rocks
+impl Generator for Day14 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(100).max(2);
+        let cols = size.width_or(100).max(2);
+        let mut out = String::new();
+
+        for _ in 0..rows {
+            for _ in 0..cols {
+                out.push(match rng.gen_range(0..100) {
+                    0..=17 => 'O',
+                    18..=27 => '#',
+                    _ => '.',
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
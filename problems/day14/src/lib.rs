@@ -1,185 +1,216 @@
 #![feature(extract_if)]
-use std::collections::HashMap;
-use tokio::sync::mpsc::Receiver;
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct Rock {
-    row: usize,
-    col: usize,
-    moves: bool,
+mod generate;
+
+use common::cycle::{self, Cycle};
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use grid::{Direction, Grid, GridBuilder};
+use tokio::sync::mpsc::Receiver;
+use tracing::{debug, info_span};
+
+const DAY: &str = "14";
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Tile {
+    Empty,
+    /// Rolls when the platform tilts
+    Round,
+    /// Stays put
+    Cube,
 }
 
-async fn get_rocks(row: usize, line: &str) -> Vec<Rock> {
-    let mut rocks = Vec::new();
-    for (col, chr) in line.chars().enumerate() {
-        match chr {
-            'O' => {
-                rocks.push(Rock {
-                    row,
-                    col,
-                    moves: true,
-                });
-            }
-            '#' => {
-                rocks.push(Rock {
-                    row,
-                    col,
-                    moves: false,
-                });
-            }
-            _ => {
-                ();
-            }
-        };
-    }
-    rocks
+async fn get_rocks(row: usize, line: &str) -> Result<Vec<Tile>, ParseError> {
+    InputLine::new(DAY, row, line).only_chars("O#.")?;
+
+    Ok(line
+        .chars()
+        .map(|chr| match chr {
+            'O' => Tile::Round,
+            '#' => Tile::Cube,
+            _ => Tile::Empty,
+        })
+        .collect())
 }
 
-fn get_load(rocks: &[Rock], rows: usize) -> usize {
-    rocks
-        .iter()
-        .filter(|r| r.moves)
-        .map(|r| r.row.abs_diff(rows))
+fn get_load(grid: &Grid<Tile>) -> usize {
+    grid.positions(|tile| *tile == Tile::Round)
+        .map(|(row, _)| grid.rows() - row)
         .sum()
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The rocks on the platform
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Platform {
+    grid: Grid<Tile>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Platform, SolveError> {
     let mut tasks = Vec::new();
 
-    let mut line_count = 0usize;
-    let mut line_len = 0usize;
     while let Some((line_no, line)) = rx.recv().await {
-        line_count += 1;
-        line_len = std::cmp::max(line_len, line.len());
+        if !line.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                (line_no, get_rocks(line_no, &line).await, line)
+            }));
+        }
+    }
 
-        let task = tokio::spawn(async move { get_rocks(line_no, &line).await });
-        tasks.push(task);
+    let mut builder = GridBuilder::new();
+    for (line_no, row, line) in join_tasks(tasks).await? {
+        builder
+            .insert_row(line_no, row?)
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
     }
+    let grid = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
 
-    let mut rocks = Vec::new();
+    Ok(Platform { grid })
+}
 
-    for task in tasks {
-        if let Ok(mut new_rocks) = task.await {
-            rocks.append(&mut new_rocks);
-        }
+fn spin(grid: &mut Grid<Tile>) {
+    for dir in [Direction::N, Direction::W, Direction::S, Direction::E] {
+        tilt(grid, dir);
     }
+}
 
-    let mut rocks1 = rocks.clone();
-    tilt(&mut rocks1, line_count, line_len, Dir::N);
-    let part1 = get_load(&rocks1, line_count);
+pub fn part1(platform: &Platform) -> usize {
+    let mut grid = platform.grid.clone();
+    tilt(&mut grid, Direction::N);
+    get_load(&grid)
+}
 
-    tilt(&mut rocks1, line_count, line_len, Dir::W);
-    tilt(&mut rocks1, line_count, line_len, Dir::S);
-    tilt(&mut rocks1, line_count, line_len, Dir::E);
+/// The spin cycles the platform settles into, counting from its starting layout
+pub fn spin_cycle(platform: &Platform) -> Cycle {
+    let cycle = cycle::find(&platform.grid, |grid| {
+        let mut grid = grid.clone();
+        spin(&mut grid);
+        grid
+    });
+    debug!(start = cycle.start, period = cycle.period, "spin cycle");
+    cycle
+}
 
-    let mut cycles = 1usize;
-    let target_cycles = 1000000000usize;
-    let mut part2 = 0;
+/// The load on the north beams after `spins` spin cycles
+pub fn load_after(platform: &Platform, spins: usize) -> usize {
+    let mut grid = platform.grid.clone();
+    for _ in 0..spin_cycle(platform).reduce(spins) {
+        spin(&mut grid);
+    }
+    get_load(&grid)
+}
 
-    for _ in 0..200 {
-        tilt(&mut rocks1, line_count, line_len, Dir::N);
-        tilt(&mut rocks1, line_count, line_len, Dir::W);
-        tilt(&mut rocks1, line_count, line_len, Dir::S);
-        tilt(&mut rocks1, line_count, line_len, Dir::E);
-        cycles += 1;
+pub fn part2(platform: &Platform) -> usize {
+    load_after(platform, 1_000_000_000)
+}
 
-        if (cycles >= 141) & (((target_cycles - cycles) % 14) == 0) {
-            part2 = get_load(&rocks1, line_count);
-            break;
-        }
-    }
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let platform = parse(rx).await?;
 
-    println!("Part 1: {part1} Part 2: {part2}");
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&platform)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&platform)));
+
+    Ok(Answer::from_parts(part1, part2))
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Dir {
-    N,
-    S,
-    E,
-    W,
+pub struct Day14;
+
+impl Solver for Day14 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
 
-fn tilt(rocks: &mut Vec<Rock>, rows: usize, cols: usize, dir: Dir) {
-    let mut stack_top: HashMap<_, _> = match dir {
-        Dir::N => rocks
-            .iter()
-            .filter(|rock| rock.row == 0)
-            .map(|rock| (rock.col, 0))
-            .collect(),
-        Dir::S => rocks
-            .iter()
-            .filter(|rock| rock.row == (rows - 1))
-            .map(|rock| (rock.col, rows - 1))
-            .collect(),
-        Dir::E => rocks
-            .iter()
-            .filter(|rock| rock.col == (cols - 1))
-            .map(|rock| (rock.row, cols - 1))
-            .collect(),
-        Dir::W => rocks
-            .iter()
-            .filter(|rock| rock.col == 0)
-            .map(|rock| (rock.row, 0))
-            .collect(),
+/// Roll every round rock as far as it goes towards `dir`
+fn tilt(grid: &mut Grid<Tile>, dir: Direction) {
+    let (rows, cols) = (grid.rows(), grid.cols());
+    let (lanes, len) = match dir.is_vertical() {
+        true => (cols, rows),
+        false => (rows, cols),
     };
-
-    let steps: Vec<usize> = match dir {
-        Dir::N => (1..rows).collect(),
-        Dir::S => (0..(rows - 1)).rev().collect(),
-        Dir::E => (0..(cols - 1)).rev().collect(),
-        Dir::W => (1..cols).collect(),
+    // the point `dist` cells in from the edge the rocks roll towards
+    let along = |lane: usize, dist: usize| match dir {
+        Direction::N => (dist, lane),
+        Direction::S => (rows - 1 - dist, lane),
+        Direction::W => (lane, dist),
+        Direction::E => (lane, cols - 1 - dist),
     };
 
-    for line in steps.into_iter() {
-        for rock in rocks.iter_mut() {
-            let loc = match dir {
-                Dir::N | Dir::S => (rock.row, rock.col),
-                _ => (rock.col, rock.row),
-            };
-
-            if line != loc.0 {
-                continue;
+    for lane in 0..lanes {
+        let mut free = 0;
+        for dist in 0..len {
+            match grid[along(lane, dist)] {
+                Tile::Cube => free = dist + 1,
+                Tile::Round => {
+                    grid[along(lane, dist)] = Tile::Empty;
+                    grid[along(lane, free)] = Tile::Round;
+                    free += 1;
+                }
+                Tile::Empty => (),
             }
+        }
+    }
+}
 
-            if !rock.moves {
-                let stack = stack_top.entry(loc.1).or_insert(loc.0);
-                *stack = loc.0;
-                continue;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn example_cycle() {
+        let example = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        for line in example.lines().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
+        }
+        drop(tx);
+
+        let platform = parse(rx).await.unwrap();
+        assert_eq!(
+            spin_cycle(&platform),
+            Cycle {
+                start: 3,
+                period: 7
             }
+        );
+        assert_eq!(load_after(&platform, 1_000_000_000), 64);
+        assert_eq!(part1(&platform), 136);
+    }
 
-            let mut space_to_move = true;
-            if let Some(stop) = stack_top.get(&loc.1) {
-                let next_dir_of_gravity = match dir {
-                    Dir::E | Dir::S => loc.0 + 1,
-                    Dir::W | Dir::N => loc.0 - 1,
-                };
-                if *stop == next_dir_of_gravity {
-                    space_to_move = false;
-                }
-                match dir {
-                    Dir::E | Dir::S => stack_top.insert(loc.1, stop - 1),
-                    Dir::W | Dir::N => stack_top.insert(loc.1, stop + 1),
-                };
-            } else {
-                match dir {
-                    Dir::E => stack_top.insert(loc.1, cols - 1),
-                    Dir::S => stack_top.insert(loc.1, rows - 1),
-                    Dir::W | Dir::N => stack_top.insert(loc.1, 0),
-                };
-            };
-
-            match (space_to_move, dir) {
-                (true, Dir::N | Dir::S) => {
-                    rock.row = *stack_top.get(&rock.col).unwrap();
-                }
-                (true, Dir::E | Dir::W) => {
-                    rock.col = *stack_top.get(&rock.row).unwrap();
-                }
-                (_, _) => {
-                    ();
-                }
-            }
+    #[tokio::test]
+    async fn ragged_row() {
+        let (tx, rx) = tokio::sync::mpsc::channel(3);
+        for line in ["O.#", ".O#", "O."].into_iter().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
         }
+        drop(tx);
+
+        assert_eq!(
+            parse(rx).await.err().unwrap().to_string(),
+            "day 14, line 3: 2 wide, the other rows are 3 in \"O.\""
+        );
     }
 }
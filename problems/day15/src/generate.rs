@@ -0,0 +1,33 @@
+use crate::Day15;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// `lines` comma separated steps, drawn from `width` distinct lens labels
+impl Generator for Day15 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let steps = size.lines_or(4000).max(1);
+        let label_count = size.width_or(500).max(1);
+
+        let labels: Vec<String> = (0..label_count)
+            .map(|_| {
+                (0..rng.gen_range(2..=6))
+                    .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                    .collect()
+            })
+            .collect();
+
+        let steps: Vec<String> = (0..steps)
+            .map(|_| {
+                let label = &labels[rng.gen_range(0..labels.len())];
+                if rng.gen_bool(0.3) {
+                    format!("{label}-")
+                } else {
+                    format!("{label}={}", rng.gen_range(1..=9))
+                }
+            })
+            .collect();
+
+        steps.join(",") + "\n"
+    }
+}
@@ -1,8 +1,18 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, Framing, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
+
+const DAY: &str = "15";
 
 const EMPTYBUCKET: Vec<Lens> = Vec::new();
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Lens {
     label: String,
     focal_length: Option<usize>,
@@ -19,12 +29,22 @@ fn hash_section(section: &str) -> usize {
     current_value
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+/// One step of the initialisation sequence, as written and as the lens operation it means
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    text: String,
+    lens: Lens,
+}
+
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Step>, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut step_no = 0;
+
     while let Some(section) = rx.recv().await {
-        let task = tokio::spawn(async move {
-            let part1 = hash_section(&section);
+        tasks.push(spawn_line(step_no, async move {
+            let input = InputLine::new(DAY, step_no, &section);
 
             let lens = if section.trim().ends_with("-") {
                 Lens {
@@ -32,38 +52,43 @@ pub async fn solve(mut rx: Receiver<String>) {
                     focal_length: None,
                 }
             } else {
-                let (label, focal_length) = section.split_once('=').unwrap();
+                let (label, focal_length) = input.split_once(&section, "=")?;
                 Lens {
                     label: label.into(),
-                    focal_length: focal_length.trim().parse::<usize>().ok(),
+                    focal_length: Some(input.parse(focal_length)?),
                 }
             };
-            let hash = hash_section(&lens.label);
 
-            (part1, hash, lens)
-        });
-        tasks.push(task);
+            Ok::<_, ParseError>(Step {
+                text: section,
+                lens,
+            })
+        }));
+        step_no += 1;
     }
 
-    let mut total_score = 0;
-    let mut lenses = Vec::new();
-
-    for task in tasks {
-        if let Ok((score, hash, lens)) = task.await {
-            total_score += score;
-            lenses.push((hash, lens));
-        }
+    let mut steps = Vec::new();
+    for result in join_tasks(tasks).await? {
+        steps.push(result?);
     }
+    Ok(steps)
+}
 
+pub fn part1(steps: &[Step]) -> usize {
+    steps.iter().map(|step| hash_section(&step.text)).sum()
+}
+
+pub fn part2(steps: &[Step]) -> usize {
     let mut buckets = [EMPTYBUCKET; 256];
 
-    for (hash, lens) in lenses {
+    for Step { lens, .. } in steps {
+        let hash = hash_section(&lens.label);
         match lens.focal_length {
             Some(_) => {
                 if let Some(lens_) = buckets[hash].iter_mut().find(|l| l.label == lens.label) {
                     lens_.focal_length = lens.focal_length;
                 } else {
-                    buckets[hash].push(lens);
+                    buckets[hash].push(lens.clone());
                 }
             }
             None => {
@@ -72,7 +97,7 @@ pub async fn solve(mut rx: Receiver<String>) {
         }
     }
 
-    let part2 = buckets
+    buckets
         .into_iter()
         .enumerate()
         .map(|(idx, bucket)| {
@@ -83,7 +108,36 @@ pub async fn solve(mut rx: Receiver<String>) {
                 .sum::<usize>()
                 * (idx + 1)
         })
-        .sum::<usize>();
+        .sum::<usize>()
+}
 
-    println!("Part 1: {total_score} Part 2: {part2}");
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let steps = parse(rx).await?;
+
+    let part1 = parts.part1().then(|| part1(&steps));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&steps)));
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn split_input(&self, raw: &str) -> Vec<String> {
+        raw.split(',').map(|s| s.to_string()).collect()
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Separator(',')
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
@@ -0,0 +1,29 @@
+use crate::Day16;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const MIRRORS: [char; 4] = ['|', '-', '/', '\\'];
+const MIRROR_DENSITY: f64 = 0.1;
+
+/// A `lines` x `width` contraption of mirrors and splitters
+impl Generator for Day16 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(110).max(1);
+        let cols = size.width_or(110).max(1);
+        let mut out = String::new();
+
+        for _ in 0..rows {
+            for _ in 0..cols {
+                if rng.gen_bool(MIRROR_DENSITY) {
+                    out.push(MIRRORS[rng.gen_range(0..MIRRORS.len())]);
+                } else {
+                    out.push('.');
+                }
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
@@ -1,16 +1,19 @@
-use std::collections::{HashMap, HashSet};
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use grid::{Direction, Grid, GridBuilder, Point};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
+use tracing::{info_span, instrument, Instrument};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
-enum Dir {
-    N,
-    E,
-    S,
-    W,
-}
+const DAY: &str = "16";
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Mirror {
     Hori,
     Vert,
@@ -32,67 +35,51 @@ impl Mirror {
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
 struct Beam {
-    location: (usize, usize),
-    direction: Dir,
+    location: Point,
+    direction: Direction,
 }
 
 impl Beam {
     fn hit_mirror(&mut self, mirror: &Mirror) -> Option<Self> {
         match (*mirror, &self.direction) {
-            (Mirror::Hori, Dir::N | Dir::S) => {
-                self.direction = Dir::W;
+            (Mirror::Hori, Direction::N | Direction::S) => {
+                self.direction = Direction::W;
                 let mut extra_beam = *self;
-                extra_beam.direction = Dir::E;
+                extra_beam.direction = Direction::E;
                 return Some(extra_beam);
             }
-            (Mirror::Vert, Dir::E | Dir::W) => {
-                self.direction = Dir::N;
+            (Mirror::Vert, Direction::E | Direction::W) => {
+                self.direction = Direction::N;
                 let mut extra_beam = *self;
-                extra_beam.direction = Dir::S;
+                extra_beam.direction = Direction::S;
                 return Some(extra_beam);
             }
-            (Mirror::Forw, Dir::N) => self.direction = Dir::E,
-            (Mirror::Forw, Dir::S) => self.direction = Dir::W,
-            (Mirror::Forw, Dir::E) => self.direction = Dir::N,
-            (Mirror::Forw, Dir::W) => self.direction = Dir::S,
-            (Mirror::Back, Dir::N) => self.direction = Dir::W,
-            (Mirror::Back, Dir::S) => self.direction = Dir::E,
-            (Mirror::Back, Dir::E) => self.direction = Dir::S,
-            (Mirror::Back, Dir::W) => self.direction = Dir::N,
+            (Mirror::Forw, Direction::N) => self.direction = Direction::E,
+            (Mirror::Forw, Direction::S) => self.direction = Direction::W,
+            (Mirror::Forw, Direction::E) => self.direction = Direction::N,
+            (Mirror::Forw, Direction::W) => self.direction = Direction::S,
+            (Mirror::Back, Direction::N) => self.direction = Direction::W,
+            (Mirror::Back, Direction::S) => self.direction = Direction::E,
+            (Mirror::Back, Direction::E) => self.direction = Direction::S,
+            (Mirror::Back, Direction::W) => self.direction = Direction::N,
             (_, _) => (),
         }
 
         None
     }
 
-    fn out_of_bounds(&self, bounds: Arc<(usize, usize)>) -> bool {
-        ((self.direction == Dir::N) & (self.location.0 == 0))
-            | ((self.direction == Dir::S) & (self.location.0 == bounds.0))
-            | ((self.direction == Dir::E) & (self.location.1 == bounds.1))
-            | ((self.direction == Dir::W) & (self.location.1 == 0))
-    }
-
-    fn step(
-        &mut self,
-        mirrors: Arc<HashMap<(usize, usize), Mirror>>,
-        bounds: Arc<(usize, usize)>,
-    ) -> (bool, Option<Self>) {
+    fn step(&mut self, mirrors: &Grid<Option<Mirror>>) -> (bool, Option<Self>) {
         // check if next step is out of bounds
-        if self.out_of_bounds(bounds) {
+        let Some(next) = mirrors.step(self.location, self.direction) else {
             return (false, None);
-        }
+        };
 
         // move one step
-        match self.direction {
-            Dir::N => self.location.0 -= 1,
-            Dir::S => self.location.0 += 1,
-            Dir::E => self.location.1 += 1,
-            Dir::W => self.location.1 -= 1,
-        }
+        self.location = next;
 
         // if a mirror is at the new location, potentially change direction
         // and possibly split into a new beam
-        if let Some(&mirror) = mirrors.get(&self.location) {
+        if let Some(mirror) = mirrors[self.location] {
             if let Some(new_beam) = self.hit_mirror(&mirror) {
                 return (true, Some(new_beam));
             }
@@ -102,111 +89,130 @@ impl Beam {
     }
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The mirrors and splitters laid out on the floor
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contraption {
+    mirrors: Grid<Option<Mirror>>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Contraption, SolveError> {
     let mut tasks = Vec::new();
 
-    let mut row_bound = 0usize;
-    let mut col_bound = 0usize;
     while let Some((line_no, line)) = rx.recv().await {
-        row_bound = std::cmp::max(row_bound, line_no);
-        col_bound = std::cmp::max(col_bound, line.len() - 1);
-        let task = tokio::spawn(async move {
-            line.chars()
-                .enumerate()
-                .filter_map(|(col, chr)| {
-                    if let Some(mirror) = Mirror::new(chr) {
-                        return Some(((line_no, col), mirror));
-                    }
-                    None
-                })
-                .collect::<Vec<((usize, usize), Mirror)>>()
-        });
-        tasks.push(task);
+        if line.is_empty() {
+            continue;
+        }
+        tasks.push(spawn_line(line_no, async move {
+            let row = InputLine::new(DAY, line_no, &line)
+                .only_chars(".-|/\\")
+                .map(|_| line.chars().map(Mirror::new).collect::<Vec<_>>());
+            (line_no, row, line)
+        }));
     }
 
-    let mut mirrors = HashMap::new();
-
-    for task in tasks {
-        if let Ok(new_mirrors) = task.await {
-            for (loc, mirror) in new_mirrors.into_iter() {
-                mirrors.insert(loc, mirror);
-            }
-        }
+    let mut builder = GridBuilder::new();
+    for (line_no, row, line) in join_tasks(tasks).await? {
+        builder
+            .insert_row(line_no, row?)
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
     }
+    let mirrors = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
+
+    Ok(Contraption { mirrors })
+}
 
+pub async fn part1(contraption: &Contraption) -> usize {
     let beam = Beam {
         location: (0, 0),
-        direction: Dir::E,
+        direction: Direction::E,
     };
 
-    let part1 = trace_beam(
-        beam,
-        Arc::new(mirrors.clone()),
-        Arc::new((row_bound, col_bound)),
-    )
-    .await;
-    let part2 = best_trace_beam(Arc::new(mirrors), Arc::new((row_bound, col_bound))).await;
+    trace_beam(beam, Arc::new(contraption.mirrors.clone()))
+        .instrument(info_span!("part1"))
+        .await
+}
 
-    println!("Part 1: {part1} Part 2: {part2} ");
+pub async fn part2(contraption: &Contraption) -> Result<usize, SolveError> {
+    best_trace_beam(Arc::new(contraption.mirrors.clone())).await
 }
 
-async fn best_trace_beam(
-    mirrors: Arc<HashMap<(usize, usize), Mirror>>,
-    bounds: Arc<(usize, usize)>,
-) -> usize {
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let contraption = parse(rx).await?;
+
+    let part1 = match parts.part1() {
+        true => Some(part1(&contraption).await),
+        false => None,
+    };
+    let part2 = match parts.part2() {
+        true => Some(part2(&contraption).await?),
+        false => None,
+    };
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day16;
+
+impl Solver for Day16 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
+}
+
+#[instrument(name = "part2", skip_all)]
+async fn best_trace_beam(mirrors: Arc<Grid<Option<Mirror>>>) -> Result<usize, SolveError> {
+    let (last_row, last_col) = (mirrors.rows() - 1, mirrors.cols() - 1);
     let mut beams = Vec::new();
-    for row in 0..=bounds.0 {
+    for row in 0..=last_row {
         let beam1 = Beam {
             location: (row, 0),
-            direction: Dir::E,
+            direction: Direction::E,
         };
         let beam2 = Beam {
-            location: (row, bounds.1),
-            direction: Dir::W,
+            location: (row, last_col),
+            direction: Direction::W,
         };
         beams.push(beam1);
         beams.push(beam2);
     }
 
-    for col in 0..=bounds.1 {
+    for col in 0..=last_col {
         let beam1 = Beam {
             location: (0, col),
-            direction: Dir::S,
+            direction: Direction::S,
         };
         let beam2 = Beam {
-            location: (bounds.0, col),
-            direction: Dir::N,
+            location: (last_row, col),
+            direction: Direction::N,
         };
         beams.push(beam1);
         beams.push(beam2);
     }
-    let mut tasks = Vec::new();
-
-    for beam in beams.into_iter() {
-        let mirrors_clone = Arc::clone(&mirrors);
-        let bounds_clone = Arc::clone(&bounds);
-        let task = tokio::spawn(async move { trace_beam(beam, mirrors_clone, bounds_clone).await });
-        tasks.push(task);
-    }
-
-    let mut top_score = 0usize;
-    for task in tasks {
-        if let Ok(score) = task.await {
-            top_score = std::cmp::max(top_score, score);
-        }
-    }
-
-    top_score
+    // tag each beam with the row it enters on, so a panic points at the input
+    let tasks = beams
+        .into_iter()
+        .map(|beam| {
+            let mirrors = Arc::clone(&mirrors);
+            spawn_line(beam.location.0, trace_beam(beam, mirrors))
+        })
+        .collect();
+
+    let scores = join_tasks(tasks).await?;
+    Ok(scores.into_iter().max().unwrap_or(0))
 }
 
-async fn trace_beam(
-    start: Beam,
-    mirrors: Arc<HashMap<(usize, usize), Mirror>>,
-    bounds: Arc<(usize, usize)>,
-) -> usize {
+async fn trace_beam(start: Beam, mirrors: Arc<Grid<Option<Mirror>>>) -> usize {
     let mut start = start;
-    if let Some(mirror) = mirrors.get(&start.location) {
-        start.hit_mirror(mirror);
+    if let Some(mirror) = mirrors[start.location] {
+        start.hit_mirror(&mirror);
     }
 
     let mut queue = vec![start];
@@ -217,7 +223,7 @@ async fn trace_beam(
         let mut next_steps = Vec::new();
 
         for beam in queue.iter_mut() {
-            let (valid, extra_beam) = beam.step(mirrors.clone(), bounds.clone());
+            let (valid, extra_beam) = beam.step(&mirrors);
             if !valid | history.contains(beam) {
                 continue;
             }
@@ -0,0 +1,23 @@
+use crate::Day17;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A `lines` x `width` map of heat loss digits
+impl Generator for Day17 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        // the ultra crucible needs room for a run of four to reach the corner
+        let rows = size.lines_or(141).max(5);
+        let cols = size.width_or(141).max(5);
+        let mut out = String::new();
+
+        for _ in 0..rows {
+            for _ in 0..cols {
+                out.push(char::from(b'0' + rng.gen_range(1..=9)));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
@@ -1,211 +1,187 @@
-use std::{cmp::Ordering, collections::HashMap};
+mod generate;
+
+use common::{
+    join_part, Answer, InputLine, InputShape, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use grid::{Direction, Grid, GridBuilder, Point};
+use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
+use tracing::instrument;
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-enum Dir {
-    N,
-    E,
-    S,
-    W,
-}
+const DAY: &str = "17";
 
+/// Where the crucible is, which way it is heading and how long it has gone straight
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 struct Bearing {
-    y: usize,
-    x: usize,
-    dir: Dir,
+    location: Point,
+    direction: Direction,
     run: u8,
 }
 
+/// Heat lost entering each block
 #[derive(Debug, Clone)]
-struct Progress {
-    heatloss: u32,
-    location: (usize, usize),
-    direction: Dir,
-    run: u8,
-}
-
-impl Ord for Progress {
-    fn cmp(&self, other: &Self) -> Ordering {
-        (self.heatloss).cmp(&(other.heatloss))
-    }
-}
-
-impl PartialOrd for Progress {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl PartialEq for Progress {
-    fn eq(&self, other: &Self) -> bool {
-        (self.heatloss) == (other.heatloss)
-    }
-}
-
-impl Eq for Progress {}
-
-#[derive(Debug, Clone)]
-struct HeatMap {
-    heatloss: Vec<u32>,
-    row_bound: usize,
-    col_bound: usize,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeatMap {
+    heatloss: Grid<u32>,
 }
 
 impl HeatMap {
-    fn next_steps(&self, progress: &Progress, min_run: u8, max_run: u8) -> Vec<Progress> {
+    fn next_steps(&self, bearing: &Bearing, min_run: u8, max_run: u8) -> Vec<(Bearing, u32)> {
         // removes option that would lead to too many straight steps
-        self.move_options(&progress.location, progress.direction)
-            .into_iter()
-            .filter(|(_, _, dir)| {
-                let start = progress.location == (0, 0);
-                let turning_too_soon = (progress.run < min_run) & (*dir != progress.direction);
-                let straight_too_long = (progress.run >= max_run) & (*dir == progress.direction);
+        self.move_options(bearing.location, bearing.direction)
+            .filter(|(_, dir)| {
+                let start = bearing.location == (0, 0);
+                let turning_too_soon = (bearing.run < min_run) & (*dir != bearing.direction);
+                let straight_too_long = (bearing.run >= max_run) & (*dir == bearing.direction);
                 !(turning_too_soon | straight_too_long) | start
             })
-            .map(|(loc0, loc1, dir)| {
-                let run = if dir == progress.direction {
-                    progress.run + 1
+            .map(|(location, dir)| {
+                let run = if dir == bearing.direction {
+                    bearing.run + 1
                 } else {
                     1
                 };
 
-                Progress {
-                    location: (loc0, loc1),
-                    heatloss: progress.heatloss + self.get_loc((loc0, loc1)), // add new heatloss
+                let next = Bearing {
+                    location,
                     direction: dir,
                     run,
-                }
+                };
+                // entering a block loses its heat
+                (next, self.heatloss[location])
             })
             .collect()
     }
 
-    fn move_options(&self, location: &(usize, usize), dir: Dir) -> Vec<(usize, usize, Dir)> {
-        // find possible moves for a given location and direction
-        let mut moves = Vec::new();
-        if (location.0 > 0) & (dir != Dir::S) {
-            moves.push((location.0 - 1, location.1, Dir::N));
-        }
-        if (location.1 > 0) & (dir != Dir::E) {
-            moves.push((location.0, location.1 - 1, Dir::W));
-        }
-        if (location.0 < self.row_bound) & (dir != Dir::N) {
-            moves.push((location.0 + 1, location.1, Dir::S));
-        }
-        if (location.1 < self.col_bound) & (dir != Dir::W) {
-            moves.push((location.0, location.1 + 1, Dir::E));
-        }
-
-        moves
+    fn move_options(
+        &self,
+        location: Point,
+        dir: Direction,
+    ) -> impl Iterator<Item = (Point, Direction)> + '_ {
+        // find possible moves for a given location and direction, never straight back
+        self.heatloss
+            .neighbours(location)
+            .filter(move |(next_dir, _)| *next_dir != dir.opposite())
+            .map(|(next_dir, next)| (next, next_dir))
     }
 
-    fn get_loc(&self, location: (usize, usize)) -> u32 {
-        // get the heatloss at a specific location
-        // SAFETY: The caller must guarantee that location is within the bounds of
-        unsafe {
-            *self
-                .heatloss
-                .get_unchecked(location.0 * (self.col_bound + 1) + location.1)
-        }
+    #[instrument(skip(self, start))]
+    async fn find_best_path(
+        &self,
+        start: Bearing,
+        min_run: u8,
+        max_run: u8,
+    ) -> Result<u32, SolveError> {
+        let end = (self.heatloss.rows() - 1, self.heatloss.cols() - 1);
+
+        graph::dijkstra(
+            start,
+            |bearing| self.next_steps(bearing, min_run, max_run),
+            |bearing| (bearing.location == end) & (bearing.run >= min_run),
+        )
+        .map(|(_, heatloss)| heatloss)
+        .ok_or_else(|| SolveError::Unsolvable {
+            day: DAY,
+            message: format!(
+                "no route to the bottom right moving {min_run} to {max_run} blocks at a time"
+            ),
+        })
     }
+}
 
-    async fn find_best_path(&self, start: Progress, min_run: u8, max_run: u8) -> u32 {
-        let mut queue = vec![start];
-        let mut best: HashMap<Bearing, u32> = HashMap::new();
-        let mut arrived = Vec::new();
-
-        loop {
-            // keep only the best heatloss for a given state
-            // sorting beforehand allows us to update the best heatloss HashMap
-            // and use it for filtering in one pass
-            // sorting is relatively cheap as it stays in a nearly sorted state
-            queue.sort_unstable();
-            queue.retain(|prog| {
-                let prog_ = prog.clone();
-                let bearing = Bearing {
-                    x: prog_.location.1,
-                    y: prog_.location.0,
-                    dir: prog_.direction,
-                    run: prog_.run,
-                };
-                let entry = best.entry(bearing).or_insert(u32::MAX);
-                if *entry > prog.heatloss {
-                    *entry = prog.heatloss;
-                    true
-                } else {
-                    false
-                }
-            });
-
-            let mut next_steps = Vec::new();
-            for progress in queue.iter() {
-                if (progress.location == (self.row_bound, self.col_bound))
-                    & (progress.run >= min_run)
-                {
-                    arrived.push(progress.clone());
-                    continue;
-                }
-
-                for step in self.next_steps(progress, min_run, max_run) {
-                    next_steps.push(step);
-                }
-            }
-
-            if next_steps.is_empty() {
-                break;
-            }
-
-            queue = next_steps;
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<HeatMap, SolveError> {
+    let mut builder = GridBuilder::new();
+    while let Some((line_no, line)) = rx.recv().await {
+        if line.is_empty() {
+            continue;
         }
+        let input = InputLine::new(DAY, line_no, &line);
+        input.only_chars("0123456789")?;
+        builder
+            .insert_row(
+                line_no,
+                line.chars().filter_map(|c| c.to_digit(10)).collect(),
+            )
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
+    }
+    let heatloss = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
 
-        if let Some(output) = arrived.iter().map(|p| p.heatloss).min() {
-            return output;
-        }
+    Ok(HeatMap { heatloss })
+}
 
-        0
+fn start() -> Bearing {
+    Bearing {
+        location: (0, 0),
+        direction: Direction::S,
+        run: 0,
     }
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
-    let mut holder = Vec::new();
-    while let Some((line_no, line)) = rx.recv().await {
-        holder.push((
-            line_no,
-            line.chars()
-                .map(|c| c.to_string().parse::<u32>().unwrap())
-                .collect::<Vec<u32>>(),
-        ));
-    }
-    holder.sort_by_key(|f| f.0);
-    let mut heatloss = Vec::new();
-    let row_bound = holder.len() - 1;
-    let col_bound = holder[0].1.len() - 1;
+pub async fn part1(map: &HeatMap) -> Result<u32, SolveError> {
+    map.find_best_path(start(), 1, 3).await
+}
+
+pub async fn part2(map: &HeatMap) -> Result<u32, SolveError> {
+    map.find_best_path(start(), 4, 10).await
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let map = Arc::new(parse(rx).await?);
+
+    let task1 = parts.part1().then(|| {
+        let map1 = Arc::clone(&map);
+        tokio::spawn(async move { part1(&map1).await })
+    });
+    let task2 = parts.part2().then(|| {
+        let map2 = Arc::clone(&map);
+        tokio::spawn(async move { part2(&map2).await })
+    });
 
-    for (_, mut line) in holder.into_iter() {
-        heatloss.append(&mut line);
+    let part1 = join_part(1, task1).await?.transpose()?;
+    let part2 = join_part(2, task2).await?.transpose()?;
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day17;
+
+impl Solver for Day17 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    let map1 = HeatMap {
-        heatloss,
-        row_bound,
-        col_bound,
-    };
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
 
-    let map2 = map1.clone();
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
+}
 
-    let start1 = Progress {
-        location: (0, 0),
-        heatloss: 0,
-        direction: Dir::S,
-        run: 0,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let start2 = start1.clone();
+    async fn heat_map(input: &str) -> HeatMap {
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count().max(1));
+        for line in input.lines().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.unwrap()
+    }
 
-    let task1 = tokio::spawn(async move { map1.find_best_path(start1, 1, 3).await });
-    let task2 = tokio::spawn(async move { map2.find_best_path(start2, 4, 10).await });
-    let part1 = task1.await.unwrap();
-    let part2 = task2.await.unwrap();
+    #[tokio::test]
+    async fn no_route() {
+        let small = heat_map("12\n34").await;
+        assert_eq!(part1(&small).await, Ok(6));
+        let error = part2(&small).await.unwrap_err().to_string();
+        assert!(error.contains("no route to the bottom right"), "{error}");
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+        let single = heat_map("5").await;
+        assert!(part1(&single).await.is_err());
+        assert!(part2(&single).await.is_err());
+    }
 }
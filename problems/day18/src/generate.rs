@@ -0,0 +1,77 @@
+use crate::Day18;
+use common::generate::{random_region, region_outline};
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+const MAX_HEX_MAGNITUDE: usize = 0xFFFFF;
+
+/// Stretch the lattice lines apart by random gaps, keeping their order so the
+/// polygon stays simple
+fn stretch(rng: &mut StdRng, lines: usize, max_gap: usize) -> Vec<usize> {
+    let mut position = 0;
+    (0..=lines)
+        .map(|_| {
+            let here = position;
+            position += rng.gen_range(1..=max_gap);
+            here
+        })
+        .collect()
+}
+
+fn moves(corners: &[(usize, usize)], ys: &[usize], xs: &[usize]) -> Vec<(usize, usize)> {
+    // (direction as in the colour code, magnitude)
+    let len = corners.len();
+    (0..len)
+        .map(|idx| {
+            let (r1, c1) = corners[idx];
+            let (r2, c2) = corners[(idx + 1) % len];
+            if r1 == r2 {
+                let dir = if c2 > c1 { 0 } else { 2 };
+                (dir, xs[c1].abs_diff(xs[c2]))
+            } else {
+                let dir = if r2 > r1 { 1 } else { 3 };
+                (dir, ys[r1].abs_diff(ys[r2]))
+            }
+        })
+        .collect()
+}
+
+/// A dig plan tracing the outline of a random blob on a `lines` x `width`
+/// lattice. Both the plain and the colour-coded plans are simple closed
+/// loops that always turn, they share a shape but not a scale.
+impl Generator for Day18 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let rows = size.lines_or(30).max(2);
+        let cols = size.width_or(30).max(2);
+
+        let region = random_region(rng, rows, cols, 0.5);
+        let outline = region_outline(&region);
+        let len = outline.len();
+        let corners: Vec<(usize, usize)> = (0..len)
+            .filter(|&idx| {
+                let prev = outline[(idx + len - 1) % len];
+                let next = outline[(idx + 1) % len];
+                (prev.0 != next.0) && (prev.1 != next.1)
+            })
+            .map(|idx| outline[idx])
+            .collect();
+
+        let small = moves(&corners, &stretch(rng, rows, 10), &stretch(rng, cols, 10));
+        let max_gap = MAX_HEX_MAGNITUDE / std::cmp::max(rows, cols);
+        let large = moves(
+            &corners,
+            &stretch(rng, rows, max_gap),
+            &stretch(rng, cols, max_gap),
+        );
+
+        let mut out = String::new();
+        for ((dir, magnitude), (hex_dir, hex_magnitude)) in small.into_iter().zip(large) {
+            let letter = ['R', 'D', 'L', 'U'][dir];
+            out.push_str(&format!(
+                "{letter} {magnitude} (#{hex_magnitude:05x}{hex_dir})\n"
+            ));
+        }
+        out
+    }
+}
@@ -1,11 +1,16 @@
-#![feature(extract_if)]
+mod generate;
 
-use rug::Float;
+use common::{
+    Answer, InputLine, InputShape, InputStream, ParseError, Parts, SolveError, SolveFuture, Solver,
+};
+use exact::Rational;
 use tokio::sync::mpsc::Receiver;
+use tracing::{debug, instrument};
 
-const PRECISION: u32 = 70;
+const DAY: &str = "18";
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Direction {
     U,
     R,
@@ -22,26 +27,33 @@ impl Direction {
             Direction::L => Direction::R,
         }
     }
-}
 
-#[derive(Debug, PartialEq, Clone)]
-struct Step {
-    start_heading: Direction,
-    end_heading: Direction,
-    location: (isize, isize),
+    /// One step this way as (down, right)
+    fn step(&self) -> (isize, isize) {
+        match self {
+            Direction::U => (-1, 0),
+            Direction::R => (0, 1),
+            Direction::D => (1, 0),
+            Direction::L => (0, -1),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Move {
     direction: Direction,
     magnitude: isize,
 }
 
+/// How far a corner block's outer point sits from its centre
+type Offset = (Rational, Rational);
+
 fn discrete_to_continuous(
     current_dir: Direction,
     next_dir: Direction,
     clockwise: bool,
-) -> (Float, Float) {
+) -> Option<Offset> {
     // adjust discrete blocks to continuous outer points
 
     let (dir_a, dir_b) = if clockwise {
@@ -51,48 +63,53 @@ fn discrete_to_continuous(
     };
 
     let out = match (dir_a, dir_b) {
-        (Direction::U, Direction::L) => (0.5, -0.5),
-        (Direction::U, Direction::R) => (-0.5, -0.5),
-        (Direction::L, Direction::U) => (0.5, -0.5),
-        (Direction::L, Direction::D) => (0.5, 0.5),
-        (Direction::D, Direction::L) => (0.5, 0.5),
-        (Direction::D, Direction::R) => (-0.5, 0.5),
-        (Direction::R, Direction::D) => (-0.5, 0.5),
-        (Direction::R, Direction::U) => (-0.5, -0.5),
-        _ => panic!("180s are radical but not allowed"),
+        (Direction::U, Direction::L) => (1, -1),
+        (Direction::U, Direction::R) => (-1, -1),
+        (Direction::L, Direction::U) => (1, -1),
+        (Direction::L, Direction::D) => (1, 1),
+        (Direction::D, Direction::L) => (1, 1),
+        (Direction::D, Direction::R) => (-1, 1),
+        (Direction::R, Direction::D) => (-1, 1),
+        (Direction::R, Direction::U) => (-1, -1),
+        // 180s are radical but not allowed, and nor is carrying straight on
+        _ => return None,
     };
 
-    (
-        Float::with_val(PRECISION, out.0),
-        Float::with_val(PRECISION, out.1),
-    )
+    Some((Rational::new(out.0, 2), Rational::new(out.1, 2)))
+}
+
+fn unsolvable(message: String) -> SolveError {
+    SolveError::Unsolvable { day: DAY, message }
 }
 
-fn gauss_area(moves: &[Move]) -> Float {
+/// The outer corner offsets turning from move `idx` onto `next`, both ways round
+fn corner(idx: usize, current: &Move, next: &Move) -> Result<(Offset, Offset), SolveError> {
+    let cw_adj = discrete_to_continuous(current.direction, next.direction, true);
+    let ccw_adj = discrete_to_continuous(current.direction, next.direction, false);
+    cw_adj.zip(ccw_adj).ok_or_else(|| {
+        unsolvable(format!(
+            "move {} goes {:?} then {:?}, the trench has to turn a corner",
+            idx + 1,
+            current.direction,
+            next.direction
+        ))
+    })
+}
+
+#[instrument(skip_all, fields(moves = moves.len()))]
+fn gauss_area(moves: &[Move]) -> Result<Rational, SolveError> {
     // assumes clockwise traversal
-    let cw_adj = discrete_to_continuous(
-        moves.last().unwrap().direction,
-        moves.first().unwrap().direction,
-        true,
-    );
-    let ccw_adj = discrete_to_continuous(
-        moves.last().unwrap().direction,
-        moves.first().unwrap().direction,
-        false,
-    );
-    let mut points: Vec<(isize, isize, Float, Float, Float, Float)> =
+    let (Some(first), Some(last)) = (moves.first(), moves.last()) else {
+        return Err(unsolvable("the dig plan has no moves".to_string()));
+    };
+    let (cw_adj, ccw_adj) = corner(moves.len() - 1, last, first)?;
+    let mut points: Vec<(isize, isize, Rational, Rational, Rational, Rational)> =
         vec![(0, 0, cw_adj.0, cw_adj.1, ccw_adj.0, ccw_adj.1)];
 
-    for moves in moves.windows(2) {
-        let cw_adj = discrete_to_continuous(moves[0].direction, moves[1].direction, true);
-        let ccw_adj = discrete_to_continuous(moves[0].direction, moves[1].direction, false);
+    for (idx, moves) in moves.windows(2).enumerate() {
+        let (cw_adj, ccw_adj) = corner(idx, &moves[0], &moves[1])?;
 
-        let diff = match moves[0].direction {
-            Direction::U => (-1isize, 0isize),
-            Direction::R => (0, 1),
-            Direction::D => (1, 0),
-            Direction::L => (0, -1),
-        };
+        let diff = moves[0].direction.step();
 
         let next_point = if let Some(point) = points.last() {
             let y = point.0 + moves[0].magnitude * diff.0;
@@ -101,60 +118,75 @@ fn gauss_area(moves: &[Move]) -> Float {
             (
                 y,
                 x,
-                Float::with_val(PRECISION, y as f64 + cw_adj.0),
-                Float::with_val(PRECISION, x as f64 + cw_adj.1),
-                Float::with_val(PRECISION, y as f64 + ccw_adj.0),
-                Float::with_val(PRECISION, x as f64 + ccw_adj.1),
+                Rational::from(y) + cw_adj.0,
+                Rational::from(x) + cw_adj.1,
+                Rational::from(y) + ccw_adj.0,
+                Rational::from(x) + ccw_adj.1,
             )
         } else {
             unreachable!();
         };
         points.push(next_point);
     }
-    points.push(points[0].clone());
 
-    let sums: (Float, Float, Float, Float) = points
+    // after the corners, so a bad turn is named ahead of the gap it leaves
+    let end = moves.iter().fold((0i128, 0i128), |(down, right), step| {
+        let (by_down, by_right) = step.direction.step();
+        let magnitude = step.magnitude as i128;
+        (
+            down + by_down as i128 * magnitude,
+            right + by_right as i128 * magnitude,
+        )
+    });
+    if end != (0, 0) {
+        return Err(unsolvable(format!(
+            "the trench ends {} down and {} right of where it started, it has to close",
+            end.0, end.1
+        )));
+    }
+    points.push(points[0]);
+
+    let sums: (Rational, Rational, Rational, Rational) = points
         .windows(2)
         .map(|w| {
             (
-                w[0].clone().2 * w[1].clone().3,
-                w[1].clone().2 * w[0].clone().3,
-                w[0].clone().4 * w[1].clone().5,
-                w[1].clone().4 * w[0].clone().5,
+                w[0].2 * w[1].3,
+                w[1].2 * w[0].3,
+                w[0].4 * w[1].5,
+                w[1].4 * w[0].5,
             )
         })
         .fold(
             (
-                Float::with_val(PRECISION, 0.0),
-                Float::with_val(PRECISION, 0.0),
-                Float::with_val(PRECISION, 0.0),
-                Float::with_val(PRECISION, 0.0),
+                Rational::ZERO,
+                Rational::ZERO,
+                Rational::ZERO,
+                Rational::ZERO,
             ),
             |acc, (x, y, x2, y2)| (acc.0 + x, acc.1 + y, acc.2 + x2, acc.3 + y2),
         );
 
-    dbg!(&sums);
-    let cw_area = (sums.0 - sums.1).abs() / Float::with_val(PRECISION, 2);
-    let ccw_area = (sums.2 - sums.3).abs() / Float::with_val(PRECISION, 2);
+    debug!(?sums, "shoelace sums");
+    let cw_area = (sums.0 - sums.1).abs() / Rational::from(2);
+    let ccw_area = (sums.2 - sums.3).abs() / Rational::from(2);
 
-    if cw_area > ccw_area {
-        cw_area
-    } else {
-        ccw_area
-    }
+    Ok(std::cmp::max(cw_area, ccw_area))
 }
 
-async fn parse_line(line: &str) -> (Move, Move) {
-    let line = line.replace(['(', ')', '#'], "");
+async fn parse_line(line_no: usize, line: &str) -> Result<(Move, Move), ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
     let parts: Vec<_> = line.split(' ').collect();
-    let magnitude = parts[1].parse::<isize>().unwrap();
+    let [dir, magnitude, colour] = parts[..] else {
+        return Err(input.error("expected a direction, a distance and a colour"));
+    };
+    let magnitude = input.parse::<isize>(magnitude)?;
 
-    let direction = match parts[0] {
+    let direction = match dir {
         "R" => Direction::R,
         "D" => Direction::D,
         "L" => Direction::L,
         "U" => Direction::U,
-        _ => panic!("incorrect input format"),
+        _ => return Err(input.error_at(dir, "expected U, D, L or R")),
     };
 
     let move1 = Move {
@@ -162,30 +194,41 @@ async fn parse_line(line: &str) -> (Move, Move) {
         magnitude,
     };
 
-    let direction = match parts[2].chars().next_back().unwrap() {
-        '0' => Direction::R,
-        '1' => Direction::D,
-        '2' => Direction::L,
-        '3' => Direction::U,
-        _ => panic!("incorrect input format"),
+    let hex = colour.trim_start_matches("(#").trim_end_matches(')');
+    if (hex.len() != 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(input.error_at(colour, "expected a colour like (#70c710)"));
+    }
+
+    let direction = match &hex[5..] {
+        "0" => Direction::R,
+        "1" => Direction::D,
+        "2" => Direction::L,
+        "3" => Direction::U,
+        _ => return Err(input.error_at(&hex[5..], "expected a direction digit from 0 to 3")),
     };
 
-    let magnitude =
-        isize::from_str_radix(&parts[2].chars().take(5).collect::<String>(), 16).unwrap();
+    let magnitude = isize::from_str_radix(&hex[..5], 16).unwrap();
 
-    (
+    Ok((
         move1,
         Move {
             direction,
             magnitude,
         },
-    )
+    ))
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The dig plan read both ways, as written and as decoded from the colours
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DigPlan {
+    moves: Vec<Move>,
+    colour_moves: Vec<Move>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<DigPlan, SolveError> {
     let mut holder = Vec::new();
     while let Some((line_no, line)) = rx.recv().await {
-        holder.push((line_no, parse_line(&line).await));
+        holder.push((line_no, parse_line(line_no, &line).await?));
     }
 
     holder.sort_by_key(|f| f.0);
@@ -197,8 +240,116 @@ pub async fn solve(mut rx: Receiver<(usize, String)>) {
         moves2.push(moves.1);
     }
 
-    let part1 = gauss_area(&moves1);
-    let part2 = gauss_area(&moves2);
+    Ok(DigPlan {
+        moves: moves1,
+        colour_moves: moves2,
+    })
+}
+
+pub fn part1(plan: &DigPlan) -> Result<i128, SolveError> {
+    to_whole(&gauss_area(&plan.moves)?)
+}
+
+pub fn part2(plan: &DigPlan) -> Result<i128, SolveError> {
+    to_whole(&gauss_area(&plan.colour_moves)?)
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let plan = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&plan)).transpose()?,
+        parts.part2().then(|| part2(&plan)).transpose()?,
+    ))
+}
+
+pub struct Day18;
+
+impl Solver for Day18 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
+}
+
+fn to_whole(area: &Rational) -> Result<i128, SolveError> {
+    area.to_integer().ok_or_else(|| {
+        unsolvable(format!(
+            "the lagoon covers {area} cubes, the trench can't close up"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn plan(input: &str) -> DigPlan {
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count().max(1));
+        for (line_no, line) in input.lines().enumerate() {
+            tx.send((line_no, line.to_string())).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn example() {
+        let plan = plan(
+            "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)",
+        )
+        .await;
+        assert_eq!(part1(&plan), Ok(62));
+        assert_eq!(part2(&plan), Ok(952408144115));
+    }
+
+    #[tokio::test]
+    async fn unclosed_plan() {
+        let plan = plan("R 4 (#000040)\nD 4 (#000041)\nL 2 (#000022)\nU 2 (#000023)").await;
+        let unclosed = Err(unsolvable(
+            "the trench ends 2 down and 2 right of where it started, it has to close".to_string(),
+        ));
+        assert_eq!(part1(&plan), unclosed);
+        assert_eq!(part2(&plan), unclosed);
+    }
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+    #[tokio::test]
+    async fn malformed_plans() {
+        assert_eq!(
+            part1(&plan("").await),
+            Err(unsolvable("the dig plan has no moves".to_string()))
+        );
+        assert_eq!(
+            part1(&plan("R 2 (#000020)\nD 2 (#000020)\nU 2 (#000020)").await),
+            Err(unsolvable(
+                "move 2 goes D then U, the trench has to turn a corner".to_string()
+            ))
+        );
+        assert_eq!(
+            part2(&plan("R 2 (#000020)\nR 2 (#000021)\nR 2 (#000021)").await),
+            Err(unsolvable(
+                "move 2 goes D then D, the trench has to turn a corner".to_string()
+            ))
+        );
+    }
 }
@@ -0,0 +1,71 @@
+use crate::Day19;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+
+const CATEGORIES: [char; 4] = ['x', 'm', 'a', 's'];
+
+fn fresh_name(rng: &mut StdRng, used: &mut HashSet<String>) -> String {
+    loop {
+        let name: String = (0..rng.gen_range(2..=3))
+            .map(|_| rng.gen_range(b'a'..=b'z') as char)
+            .collect();
+        if used.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+/// Up to `width` workflows branching out from `in` as a tree, so every part
+/// ends up accepted or rejected, followed by `lines` parts
+impl Generator for Day19 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let parts = size.lines_or(200);
+        let max_workflows = size.width_or(550).max(1);
+
+        let mut used = HashSet::from(["in".to_string()]);
+        let mut queue = VecDeque::from(vec!["in".to_string()]);
+        let mut created = 1;
+        let mut workflows = Vec::new();
+
+        while let Some(name) = queue.pop_front() {
+            let mut target = |rng: &mut StdRng| {
+                if (created < max_workflows) && rng.gen_bool(0.6) {
+                    created += 1;
+                    let child = fresh_name(rng, &mut used);
+                    queue.push_back(child.clone());
+                    child
+                } else if rng.gen_bool(0.5) {
+                    "A".to_string()
+                } else {
+                    "R".to_string()
+                }
+            };
+
+            let mut rules: Vec<String> = (0..rng.gen_range(1..=3))
+                .map(|_| {
+                    let category = CATEGORIES[rng.gen_range(0..CATEGORIES.len())];
+                    let op = if rng.gen_bool(0.5) { '<' } else { '>' };
+                    let threshold = rng.gen_range(2..4000);
+                    format!("{category}{op}{threshold}:{}", target(rng))
+                })
+                .collect();
+            rules.push(target(rng));
+            workflows.push(format!("{name}{{{}}}", rules.join(",")));
+        }
+        workflows.shuffle(rng);
+
+        let mut out = workflows.join("\n");
+        out.push_str("\n\n");
+        for _ in 0..parts {
+            let ratings: Vec<String> = CATEGORIES
+                .iter()
+                .map(|c| format!("{c}={}", rng.gen_range(1..=4000)))
+                .collect();
+            out.push_str(&format!("{{{}}}\n", ratings.join(",")));
+        }
+        out
+    }
+}
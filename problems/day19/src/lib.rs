@@ -1,39 +1,31 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use interval::{Cuboid, Interval};
 use std::collections::{HashMap, VecDeque};
 use tokio::sync::mpsc::Receiver;
+use tracing::info_span;
+
+const DAY: &str = "19";
 
 const RATINGS: usize = 4;
 const MIN_RATING: usize = 1;
 const MAX_RATING: usize = 4000;
 
-#[derive(Debug, Clone, Copy)]
-struct Interval {
-    start: usize,
-    end: usize,
-}
-
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Gear {
     ratings: [usize; RATINGS],
 }
 
-#[derive(Debug, Clone)]
-struct GearInterval {
-    ratings: [Interval; RATINGS],
-}
-
-impl GearInterval {
-    fn size(&self) -> usize {
-        let mut output = 1;
-
-        for interval in self.ratings.iter() {
-            output *= interval.end + 1 - interval.start;
-        }
-
-        output
-    }
-}
+/// Every combination of ratings in a box, one axis per rating
+type GearInterval = Cuboid<usize, RATINGS>;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Operation {
     LT,
     GT,
@@ -41,6 +33,7 @@ enum Operation {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Rule {
     rating: usize,
     operation: Operation,
@@ -64,49 +57,26 @@ impl Rule {
             None
         }
     }
-    fn interval_apply(&self, gearinterval: &mut GearInterval) -> Option<(String, GearInterval)> {
-        // assumes the ANY operation is always the last rule
-        match self.operation {
-            Operation::LT => {
-                let interval = gearinterval.ratings[self.rating];
-                if self.argument >= interval.start {
-                    let mut new_gearinterval = gearinterval.clone();
-                    if self.argument <= interval.end {
-                        new_gearinterval.ratings[self.rating].end = self.argument - 1;
-                        gearinterval.ratings[self.rating].start = self.argument;
-                        Some((self.output.clone(), new_gearinterval))
-                    } else {
-                        gearinterval.ratings[self.rating].start =
-                            gearinterval.ratings[self.rating].end + 1;
-                        Some((self.output.clone(), new_gearinterval))
-                    }
-                } else {
-                    None
-                }
-            }
+    /// Split off the part of `gearinterval` this rule sends on, returning it
+    /// with its destination along with whatever is left for the next rule
+    fn interval_apply(
+        &self,
+        gearinterval: GearInterval,
+    ) -> (Option<(String, GearInterval)>, Option<GearInterval>) {
+        let (matched, rest) = match self.operation {
+            Operation::LT => gearinterval.split_at(self.rating, self.argument),
             Operation::GT => {
-                let interval = gearinterval.ratings[self.rating];
-                if self.argument <= interval.end {
-                    let mut new_gearinterval = gearinterval.clone();
-                    if self.argument >= interval.start {
-                        new_gearinterval.ratings[self.rating].start = self.argument + 1;
-                        gearinterval.ratings[self.rating].end = self.argument;
-                        Some((self.output.clone(), new_gearinterval))
-                    } else {
-                        gearinterval.ratings[self.rating].start =
-                            gearinterval.ratings[self.rating].end + 1;
-                        Some((self.output.clone(), new_gearinterval))
-                    }
-                } else {
-                    None
-                }
+                let (rest, matched) = gearinterval.split_at(self.rating, self.argument + 1);
+                (matched, rest)
             }
-            Operation::ANY => Some((self.output.clone(), gearinterval.clone())),
-        }
+            Operation::ANY => (Some(gearinterval), None),
+        };
+        (matched.map(|m| (self.output.clone(), m)), rest)
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Pattern {
     rules: Vec<Rule>,
 }
@@ -118,19 +88,19 @@ impl Pattern {
                 return next_step;
             }
         }
-        panic!("full pattern without resolution");
+        unreachable!("parse_line checks the last rule has no condition");
     }
 
     fn interval_apply(&self, gearinterval: &GearInterval) -> Vec<(String, GearInterval)> {
         let mut output = Vec::new();
-        let mut gearinterval = gearinterval.clone();
+        let mut rest = Some(*gearinterval);
         for rule in self.rules.iter() {
-            if gearinterval.size() == 0 {
+            let Some(gearinterval) = rest else {
                 break;
-            }
-            if let Some(next) = rule.interval_apply(&mut gearinterval) {
-                output.push(next);
-            }
+            };
+            let (next, left) = rule.interval_apply(gearinterval);
+            output.extend(next);
+            rest = left;
         }
         output
     }
@@ -142,20 +112,25 @@ enum GearPattern {
     Pattern(String, Pattern),
 }
 
-async fn parse_line(line: &str) -> GearPattern {
+async fn parse_line(line_no: usize, line: &str) -> Result<GearPattern, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
     if line.starts_with('{') {
-        let ratings: Vec<_> = line
+        let ratings: Vec<usize> = line
             .trim()
-            .split(['=', ',', '{', '}'])
-            .filter_map(|s| s.parse::<usize>().ok())
-            .collect();
-
-        GearPattern::Gear(Gear {
-            ratings: ratings.try_into().unwrap(),
-        })
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .split(',')
+            .map(|field| input.parse(input.split_once(field, "=")?.1))
+            .collect::<Result<_, _>>()?;
+
+        Ok(GearPattern::Gear(Gear {
+            ratings: ratings
+                .try_into()
+                .map_err(|_| input.error(format!("expected {RATINGS} ratings")))?,
+        }))
     } else {
         let mut split = line.trim().split(['{', ',', '}']);
-        let name = split.next().unwrap();
+        let name = split.next().unwrap_or_default();
         let mut rules = Vec::new();
 
         for rule_str in split {
@@ -169,16 +144,18 @@ async fn parse_line(line: &str) -> GearPattern {
                     Operation::LT
                 };
 
-                let (rating_name, argument_str) = condition.split_once(['<', '>']).unwrap();
+                let (rating_name, argument_str) = condition
+                    .split_once(['<', '>'])
+                    .ok_or_else(|| input.error_at(condition, "expected < or >"))?;
                 let rating = match rating_name {
                     "x" => 0,
                     "m" => 1,
                     "a" => 2,
                     "s" => 3,
-                    _ => panic!("incorrect input"),
+                    _ => return Err(input.error_at(rating_name, "expected x, m, a or s")),
                 };
 
-                let argument = argument_str.parse::<usize>().unwrap();
+                let argument = input.parse::<usize>(argument_str)?;
 
                 rules.push(Rule {
                     rating,
@@ -196,73 +173,121 @@ async fn parse_line(line: &str) -> GearPattern {
             }
         }
 
-        GearPattern::Pattern(name.into(), Pattern { rules })
+        // a part that matches none of the rules would have nowhere to go
+        if rules.last().map(|rule| &rule.operation) != Some(&Operation::ANY) {
+            return Err(input.error("the last rule has to send on every part, with no condition"));
+        }
+
+        Ok(GearPattern::Pattern(name.into(), Pattern { rules }))
     }
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+/// The named workflows and the parts waiting to go through them
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct System {
+    gears: Vec<Gear>,
+    patterns: HashMap<String, Pattern>,
+}
+
+pub async fn parse(mut rx: Receiver<String>) -> Result<System, SolveError> {
     let mut tasks = Vec::new();
 
+    let mut line_no = 0;
+
     while let Some(line) = rx.recv().await {
-        if line.is_empty() {
-            continue;
+        if !line.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                parse_line(line_no, &line)
+                    .await
+                    .map(|parsed| (line_no, line, parsed))
+            }));
         }
-
-        let task = tokio::spawn(async move { parse_line(&line).await });
-        tasks.push(task);
+        line_no += 1;
     }
 
     let mut gears: Vec<Gear> = Vec::new();
     let mut patterns: HashMap<String, Pattern> = HashMap::new();
+    let mut pattern_lines = Vec::new();
 
-    for task in tasks {
-        if let Ok(gearpattern) = task.await {
-            match gearpattern {
-                GearPattern::Gear(gear) => {
-                    gears.push(gear);
-                }
-                GearPattern::Pattern(name, pattern) => {
-                    patterns.insert(name, pattern);
+    for result in join_tasks(tasks).await? {
+        match result? {
+            (_, _, GearPattern::Gear(gear)) => {
+                gears.push(gear);
+            }
+            (line_no, line, GearPattern::Pattern(name, pattern)) => {
+                if patterns.insert(name, pattern).is_some() {
+                    return Err(InputLine::new(DAY, line_no, &line)
+                        .error("a second workflow with this name")
+                        .into());
                 }
+                pattern_lines.push((line_no, line));
             }
         }
     }
 
-    let mut part1 = 0;
+    if !patterns.contains_key("in") {
+        return Err(SolveError::Unsolvable {
+            day: DAY,
+            message: "no workflow named in to start from".to_string(),
+        });
+    }
+    for (line_no, line) in pattern_lines {
+        check_outputs(&patterns, &InputLine::new(DAY, line_no, &line))?;
+    }
 
-    for gear in gears.into_iter() {
+    Ok(System { gears, patterns })
+}
+
+/// Every rule on a workflow's line has to send parts to A, R or another workflow
+fn check_outputs(patterns: &HashMap<String, Pattern>, input: &InputLine) -> Result<(), ParseError> {
+    let (name, rules) = input.split_once(input.text, "{")?;
+    for rule in rules.split([',', '}']) {
+        let output = rule.rsplit(':').next().unwrap_or(rule).trim();
+        if !output.is_empty() && !["A", "R"].contains(&output) && !patterns.contains_key(output) {
+            return Err(input.error_at(
+                output,
+                format!(
+                    "workflow {} sends parts to {output}, which isn't a workflow",
+                    name.trim()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn part1(system: &System) -> usize {
+    let mut part1 = 0;
+    for gear in system.gears.iter() {
         let mut pattern_name = "in".to_string();
         while !["A", "R"].contains(&pattern_name.as_str()) {
-            pattern_name = patterns.get(&pattern_name).unwrap().apply(&gear);
+            // parse checked every rule leads to A, R or a workflow
+            pattern_name = system.patterns[&pattern_name].apply(gear);
             if pattern_name.as_str() == "A" {
                 part1 += gear.ratings.iter().sum::<usize>();
             }
         }
     }
+    part1
+}
 
+pub fn part2(system: &System) -> usize {
     let mut part2 = 0;
 
     let mut queue = VecDeque::from(vec![(
         "in".to_string(),
-        GearInterval {
-            ratings: [Interval {
-                start: MIN_RATING,
-                end: MAX_RATING,
-            }; RATINGS],
-        },
+        GearInterval::new([Interval::inclusive(MIN_RATING, MAX_RATING); RATINGS]),
     )]);
 
     loop {
         let mut next_queue = VecDeque::new();
 
         for (pattern_name, gearinterval) in queue {
-            for (next_pattern_name, child_gearinterval) in patterns
-                .get(&pattern_name)
-                .unwrap()
-                .interval_apply(&gearinterval)
+            for (next_pattern_name, child_gearinterval) in
+                system.patterns[&pattern_name].interval_apply(&gearinterval)
             {
                 if next_pattern_name == "A" {
-                    part2 += child_gearinterval.size();
+                    part2 += child_gearinterval.volume();
                 } else if next_pattern_name != "R" {
                     next_queue.push_back((next_pattern_name, child_gearinterval));
                 }
@@ -274,6 +299,70 @@ pub async fn solve(mut rx: Receiver<String>) {
         }
         queue = next_queue;
     }
+    part2
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let system = parse(rx).await?;
+
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&system)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&system)));
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day19;
+
+impl Solver for Day19 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse_error(input: &str) -> String {
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count().max(1));
+        for line in input.lines() {
+            tx.send(line.to_string()).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.err().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn workflows_lead_somewhere() {
+        for (input, expected) in [
+            (
+                "in{x<5:foo,A}\n\n{x=1,m=2,a=3,s=4}",
+                "line 1, column 8: workflow in sends parts to foo, which isn't a workflow",
+            ),
+            (
+                "in{x<5:A,bar}\n\n{x=1,m=2,a=3,s=4}",
+                "line 1, column 10: workflow in sends parts to bar",
+            ),
+            (
+                "in{x<5:A}\n\n{x=1,m=2,a=3,s=4}",
+                "line 1: the last rule has to send on every part",
+            ),
+            (
+                "in{A}\nin{R}\n\n{x=1,m=2,a=3,s=4}",
+                "line 2: a second workflow with this name",
+            ),
+            ("px{A}\n\n{x=1,m=2,a=3,s=4}", "no workflow named in"),
+        ] {
+            let error = parse_error(input).await;
+            assert!(error.contains(expected), "{error}");
+        }
+    }
 }
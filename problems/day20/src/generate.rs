@@ -0,0 +1,77 @@
+use crate::Day20;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+fn is_prime(n: usize) -> bool {
+    (n >= 2)
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+/// A circuit in the shape of the real puzzle: `lines` binary counters of
+/// `width` flip-flops hanging off the broadcaster. Each counter's conjunction
+/// fires after a distinct prime number of presses, and through an inverter
+/// feeds the conjunction in front of `rx`.
+impl Generator for Day20 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let counters = size.lines_or(4).max(1);
+        let bits = size.width_or(12).clamp(3, 20);
+
+        let mut names: Vec<String> = (b'a'..=b'z')
+            .flat_map(|a| (b'a'..=b'z').map(move |b| String::from_utf8(vec![a, b]).unwrap()))
+            .filter(|name| name != "rx")
+            .collect();
+        assert!(
+            counters * (bits + 2) < names.len(),
+            "too many modules for two letter names"
+        );
+        names.shuffle(rng);
+        let mut names = names.into_iter();
+
+        // the top bit must be set so the counter uses all of its flip-flops
+        let mut periods: Vec<usize> = ((1 << (bits - 1))..(1 << bits))
+            .filter(|&p| is_prime(p))
+            .collect();
+        periods.shuffle(rng);
+        assert!(periods.len() >= counters, "not enough distinct periods");
+
+        let last = names.next().unwrap();
+        let mut lines = vec![format!("&{last} -> rx")];
+        let mut firsts = Vec::new();
+
+        for &period in periods.iter().take(counters) {
+            let flipflops: Vec<String> = names.by_ref().take(bits).collect();
+            let hub = names.next().unwrap();
+            let inverter = names.next().unwrap();
+
+            let mut hub_to = Vec::new();
+            for (bit, flipflop) in flipflops.iter().enumerate() {
+                let mut to = Vec::new();
+                if let Some(next) = flipflops.get(bit + 1) {
+                    to.push(next.clone());
+                }
+                if period & (1 << bit) != 0 {
+                    to.push(hub.clone());
+                }
+                // the hub resets the counter through the first flip-flop and
+                // every flip-flop for a zero bit
+                if (bit == 0) || (period & (1 << bit) == 0) {
+                    hub_to.push(flipflop.clone());
+                }
+                to.shuffle(rng);
+                lines.push(format!("%{flipflop} -> {}", to.join(", ")));
+            }
+            hub_to.push(inverter.clone());
+            hub_to.shuffle(rng);
+            lines.push(format!("&{hub} -> {}", hub_to.join(", ")));
+            lines.push(format!("&{inverter} -> {last}"));
+            firsts.push(flipflops[0].clone());
+        }
+
+        lines.push(format!("broadcaster -> {}", firsts.join(", ")));
+        lines.shuffle(rng);
+        lines.join("\n") + "\n"
+    }
+}
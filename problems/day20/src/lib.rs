@@ -1,33 +1,206 @@
-use std::collections::{HashMap, VecDeque};
+mod generate;
+
+use common::{
+    Answer, InputLine, InputStream, Knob, Knobs, ParseError, Parts, SolveError, SolveFuture, Solver,
+};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use tokio::sync::mpsc::Receiver;
+use tracing::{debug, info_span};
 
-pub async fn solve(mut rx: Receiver<String>) {
+const DAY: &str = "20";
+
+pub async fn parse(mut rx: Receiver<String>) -> Result<Circuit, SolveError> {
     let mut inputs = Vec::new();
     while let Some(line) = rx.recv().await {
         inputs.push(line);
     }
 
-    let mut circuit = Circuit::new(inputs);
-    let mut tracker = HashMap::new();
+    Ok(Circuit::new(inputs)?)
+}
+
+/// How many times part 1 presses the button
+const PRESSES: usize = 1000;
+const KNOBS: &[Knob] = &[
+    Knob {
+        name: "trace",
+        help: "file to write every pulse of part 1's presses to, a line each",
+        check: &[],
+    },
+    Knob {
+        name: "state",
+        help: "file to write what each module remembers to, once part 1's presses are done",
+        check: &[],
+    },
+    Knob {
+        name: "dot",
+        help: "file to write the wiring to as a Graphviz digraph",
+        check: &[],
+    },
+];
+
+pub fn part1(circuit: &Circuit) -> usize {
+    let circuit = circuit.after_presses(PRESSES);
+    circuit.pulses.0 * circuit.pulses.1
+}
+
+/// Write to the file the knob `name` is set to, if it is
+fn write_knob(
+    knobs: &Knobs,
+    name: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> Result<(), SolveError> {
+    let Some(path) = knobs.get(name) else {
+        return Ok(());
+    };
+    File::create(path)
+        .map(BufWriter::new)
+        .and_then(|mut out| {
+            write(&mut out)?;
+            out.flush()
+        })
+        .map_err(|e| SolveError::Knob {
+            day: DAY,
+            message: format!("can't write the {name} to {path}: {e}"),
+        })
+}
+
+/// How many presses to watch the inputs in front of `rx` for before giving up
+const MAX_PRESSES: usize = 100_000;
+
+/// The conjunction that sends to `rx` and the modules that feed it, read
+/// from the wiring
+fn rx_hub(circuit: &Circuit) -> Result<(&Conjunction, Vec<String>), String> {
+    let feeders: Vec<&Module> = circuit
+        .modules
+        .iter()
+        .filter(|m| m.sends_to(&"rx".to_string()))
+        .collect();
+    if circuit.broadcast.iter().any(|dest| dest == "rx") {
+        return Err("the broadcaster sends straight to rx".into());
+    }
 
-    for _ in 0..1000 {
-        if let Some(source) = circuit.press_button() {
-            tracker.insert(source, circuit.steps);
+    match feeders[..] {
+        [Module::Conjunction(hub)] => {
+            let inputs = hub.received.iter().map(|(name, _)| name.clone()).collect();
+            Ok((hub, inputs))
+        }
+        [] => Err("nothing sends to rx".into()),
+        [Module::FlipFlop(flipflop)] => Err(format!(
+            "rx is fed by flip-flop {}, expected a conjunction",
+            flipflop.name
+        )),
+        _ => {
+            let names: Vec<String> = feeders.iter().map(|m| m.get_name()).collect();
+            Err(format!(
+                "rx is fed by {}, expected a single conjunction",
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Every input of the conjunction in front of `rx` with how often it sends
+/// that conjunction a high pulse. `rx` gets a low pulse once all of them do
+/// on the same press, which is their lowest common multiple as long as each
+/// one repeats from the very first press.
+pub fn rx_periods(circuit: &Circuit) -> Result<Vec<(String, usize)>, SolveError> {
+    let unsolvable = |message: String| SolveError::Unsolvable { day: DAY, message };
+
+    let (hub, inputs) = rx_hub(circuit).map_err(unsolvable)?;
+    let hub = hub.name.clone();
+    debug!(hub, ?inputs, "rx is fed by");
+
+    // the first two presses on which each input sent a high pulse
+    let mut highs: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut circuit = circuit.clone();
+    circuit.steps = 0;
+    while (circuit.steps < MAX_PRESSES)
+        && !inputs
+            .iter()
+            .all(|input| highs.get(input).is_some_and(|presses| presses.len() >= 2))
+    {
+        for pulse in circuit.press_button() {
+            if pulse.high && (pulse.to == hub) {
+                let presses = highs.entry(pulse.from).or_default();
+                if presses.last() != Some(&circuit.steps) {
+                    presses.push(circuit.steps);
+                }
+            }
         }
     }
-    let part1 = circuit.pulses.0 * circuit.pulses.1;
-    for _ in 0..4000 {
-        if let Some(source) = circuit.press_button() {
-            tracker.insert(source, circuit.steps);
-            if tracker.len() >= 4 {
-                break;
+
+    let mut periods = Vec::new();
+    let mut irregular = Vec::new();
+    for input in inputs {
+        match highs.get(&input).map(Vec::as_slice) {
+            Some([first, second, ..]) if *second == 2 * first => periods.push((input, *first)),
+            Some([first, second, ..]) => {
+                irregular.push(format!("{input} (high on presses {first} and {second})"))
             }
+            _ => irregular.push(format!("{input} (not high twice in {MAX_PRESSES} presses)")),
         }
     }
 
-    let part2 = tracker.into_values().fold(1, lcm);
+    if !irregular.is_empty() {
+        return Err(unsolvable(format!(
+            "inputs of {hub} don't repeat from the first press: {}",
+            irregular.join(", ")
+        )));
+    }
+    Ok(periods)
+}
+
+pub fn part2(circuit: &Circuit) -> Result<usize, SolveError> {
+    let periods = rx_periods(circuit)?;
+    debug!(?periods, "rx input periods");
+    Ok(periods.into_iter().map(|(_, period)| period).fold(1, lcm))
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts, knobs: Knobs) -> Result<Answer, SolveError> {
+    let circuit = parse(rx).await?;
+
+    write_knob(&knobs, "trace", |out| {
+        circuit.clone().write_trace(PRESSES, out)
+    })?;
+    write_knob(&knobs, "state", |out| {
+        circuit.after_presses(PRESSES).write_state(out)
+    })?;
+    write_knob(&knobs, "dot", |out| {
+        out.write_all(circuit.to_dot().as_bytes())
+    })?;
+
+    let part1 = parts
+        .part1()
+        .then(|| info_span!("part1").in_scope(|| part1(&circuit)));
+    let part2 = parts
+        .part2()
+        .then(|| info_span!("part2").in_scope(|| part2(&circuit)))
+        .transpose()?;
+
+    Ok(Answer::from_parts(part1, part2))
+}
+
+pub struct Day20;
+
+impl Solver for Day20 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        self.solve_with(input, parts, &Knobs::default())
+    }
+
+    fn knobs(&self) -> &'static [Knob] {
+        KNOBS
+    }
 
-    println!("Part 1: {part1} Part 2: {part2}");
+    fn solve_with(&self, input: InputStream, parts: Parts, knobs: &Knobs) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts, knobs.clone()))
+    }
 }
 
 fn gcd(a: usize, b: usize) -> usize {
@@ -42,8 +215,10 @@ fn lcm(a: usize, b: usize) -> usize {
     a / gcd(a, b) * b
 }
 
+/// The modules and how they are wired, with the pulses counted so far
 #[derive(Debug, Clone)]
-struct Circuit {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Circuit {
     broadcast: Vec<String>,
     modules: Vec<Module>,
     pulses: (usize, usize),
@@ -51,13 +226,14 @@ struct Circuit {
 }
 
 impl Circuit {
-    fn new(input: Vec<String>) -> Self {
+    fn new(input: Vec<String>) -> Result<Self, ParseError> {
         let pulses = (0, 0);
         let mut broadcast = Vec::new();
         let mut modules = Vec::new();
 
-        for line in input.into_iter().filter(|l| !l.is_empty()) {
-            let (pre, post) = line.split_once(" -> ").unwrap();
+        for (line_no, line) in input.iter().enumerate().filter(|(_, l)| !l.is_empty()) {
+            let input = InputLine::new(DAY, line_no, line);
+            let (pre, post) = input.split_once(line, " -> ")?;
 
             if line.starts_with('%') {
                 let name = pre.trim().chars().skip(1).collect();
@@ -78,7 +254,7 @@ impl Circuit {
             } else if line.starts_with("broadcaster") {
                 broadcast = post.split(',').map(|s| s.trim().to_string()).collect();
             } else {
-                panic!("invalid input");
+                return Err(input.error_at(pre, "expected %, & or broadcaster"));
             }
         }
 
@@ -98,15 +274,17 @@ impl Circuit {
             }
         }
 
-        Self {
+        Ok(Self {
             broadcast,
             modules,
             pulses,
             steps: 0,
-        }
+        })
     }
 
-    fn press_button(&mut self) -> Option<String> {
+    /// Send a low pulse to the broadcaster and let the circuit settle,
+    /// returning every pulse in the order they went, the button's first
+    pub fn press_button(&mut self) -> Vec<Pulse> {
         self.steps += 1;
 
         let mut queue: VecDeque<Pulse> = self
@@ -120,22 +298,15 @@ impl Circuit {
             .collect();
 
         self.pulses.0 += 1 + self.broadcast.len();
-        let mut output = None;
+        let mut sent = vec![Pulse {
+            from: "button".into(),
+            to: "broadcaster".into(),
+            high: false,
+        }];
 
         while let Some(pulse) = queue.pop_front() {
             if let Some(module) = self.modules.iter_mut().find(|m| m.is_name(&pulse.to)) {
-                let capture = module.is_name("vr");
                 for new_pulse in module.receive_pulse(&pulse) {
-                    if capture {
-                        if let Module::Conjunction(post) = &mut *module {
-                            for (source, high) in post.received.iter() {
-                                if *high {
-                                    output = Some(source.clone());
-                                    break;
-                                }
-                            }
-                        }
-                    }
                     if new_pulse.high {
                         self.pulses.1 += 1;
                     } else {
@@ -144,20 +315,131 @@ impl Circuit {
                     queue.push_back(new_pulse);
                 }
             }
+            sent.push(pulse);
+        }
+
+        sent
+    }
+
+    /// How many times the button has been pressed
+    pub fn presses(&self) -> usize {
+        self.steps
+    }
+
+    /// This circuit as it is after `presses` more presses of the button
+    pub fn after_presses(&self, presses: usize) -> Self {
+        let mut circuit = self.clone();
+        for _ in 0..presses {
+            circuit.press_button();
+        }
+        circuit
+    }
+
+    /// Whether the named flip-flop is on, `None` if there's no such flip-flop
+    pub fn flip_flop(&self, name: &str) -> Option<bool> {
+        self.modules.iter().find_map(|m| match m {
+            Module::FlipFlop(flipflop) if flipflop.name == name => Some(flipflop.status),
+            _ => None,
+        })
+    }
+
+    /// What the named conjunction last heard from each of its inputs, `true`
+    /// for high, `None` if there's no such conjunction
+    pub fn conjunction(&self, name: &str) -> Option<&[(String, bool)]> {
+        self.modules.iter().find_map(|m| match m {
+            Module::Conjunction(conj) if conj.name == name => Some(conj.received.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Press the button `presses` times, writing every pulse to `out` as a
+    /// line like `3: a -high-> inv`, numbered by press
+    pub fn write_trace(&mut self, presses: usize, mut out: impl Write) -> io::Result<()> {
+        for _ in 0..presses {
+            for pulse in self.press_button() {
+                writeln!(out, "{}: {pulse}", self.steps)?;
+            }
         }
+        Ok(())
+    }
 
-        output
+    /// Every module's state, a line each like `%a on` or `&con a=low, b=high`
+    pub fn write_state(&self, mut out: impl Write) -> io::Result<()> {
+        for name in self.modules.iter().map(Module::get_name) {
+            if let Some(on) = self.flip_flop(&name) {
+                writeln!(out, "%{name} {}", if on { "on" } else { "off" })?;
+            } else if let Some(received) = self.conjunction(&name) {
+                let inputs: Vec<String> = received
+                    .iter()
+                    .map(|(input, high)| format!("{input}={}", if *high { "high" } else { "low" }))
+                    .collect();
+                writeln!(out, "&{name} {}", inputs.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The wiring as a Graphviz digraph, each kind of module drawn its own way
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n");
+        dot.push_str("    broadcaster [shape=doubleoctagon, style=filled, fillcolor=palegreen];\n");
+        for module in self.modules.iter() {
+            let (prefix, shape, colour) = match module {
+                Module::FlipFlop(_) => ('%', "box", "lightblue"),
+                Module::Conjunction(_) => ('&', "ellipse", "lightpink"),
+            };
+            let name = module.get_name();
+            dot.push_str(&format!(
+                "    \"{name}\" [label=\"{prefix}{name}\", shape={shape}, style=filled, fillcolor={colour}];\n"
+            ));
+        }
+
+        let edges: Vec<(String, &String)> = self
+            .broadcast
+            .iter()
+            .map(|to| ("broadcaster".to_string(), to))
+            .chain(
+                self.modules
+                    .iter()
+                    .flat_map(|m| m.targets().iter().map(move |to| (m.get_name(), to))),
+            )
+            .collect();
+
+        // anything that isn't a module, like rx, is just an output
+        let outputs: BTreeSet<&String> = edges
+            .iter()
+            .map(|(_, to)| *to)
+            .filter(|to| !self.modules.iter().any(|m| m.is_name(to)))
+            .collect();
+        for output in outputs {
+            dot.push_str(&format!("    \"{output}\" [shape=plaintext];\n"));
+        }
+        for (from, to) in edges {
+            dot.push_str(&format!("    \"{from}\" -> \"{to}\";\n"));
+        }
+
+        dot.push_str("}\n");
+        dot
     }
 }
 
-#[derive(Debug, Clone)]
-struct Pulse {
-    from: String,
-    to: String,
-    high: bool,
+/// One pulse sent from module to module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pulse {
+    pub from: String,
+    pub to: String,
+    pub high: bool,
+}
+
+impl fmt::Display for Pulse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = if self.high { "high" } else { "low" };
+        write!(f, "{} -{level}-> {}", self.from, self.to)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Module {
     FlipFlop(FlipFlop),
     Conjunction(Conjunction),
@@ -185,6 +467,13 @@ impl Module {
         }
     }
 
+    fn targets(&self) -> &[String] {
+        match self {
+            Module::FlipFlop(flipflop) => &flipflop.to,
+            Module::Conjunction(conjunction) => &conjunction.to,
+        }
+    }
+
     fn sends_to(&self, module_name: &String) -> bool {
         match self {
             Module::FlipFlop(flipflop) => flipflop.to.contains(module_name),
@@ -198,6 +487,7 @@ trait SendReceive {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct FlipFlop {
     name: String,
     status: bool,
@@ -223,6 +513,7 @@ impl SendReceive for FlipFlop {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Conjunction {
     name: String,
     received: Vec<(String, bool)>,
@@ -256,16 +547,24 @@ impl SendReceive for Conjunction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::{GenSize, Generator};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(|l| l.to_string()).collect()
+    }
 
     #[test]
     fn example() {
-        let mut circuit = Circuit::new(
+        let mut circuit = Circuit::new(lines(
             "broadcaster -> a, b, c
 %a -> b
 %b -> c
 %c -> inv
 &inv -> a",
-        );
+        ))
+        .unwrap();
         for _ in 0..1000 {
             circuit.press_button();
         }
@@ -274,14 +573,15 @@ mod tests {
 
     #[test]
     fn example2() {
-        let mut circuit = Circuit::new(
+        let mut circuit = Circuit::new(lines(
             "broadcaster -> a
 %a -> inv, con
 &inv -> b
 %b -> con
 &con -> output
 ",
-        );
+        ))
+        .unwrap();
 
         for _ in 0..1000 {
             circuit.press_button();
@@ -289,14 +589,195 @@ mod tests {
         assert_eq!(circuit.pulses, (4250, 2750));
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn part1() {
-        let input = std::fs::read_to_string("input.txt").unwrap();
-        let mut circuit = Circuit::new(&input);
-
-        for _ in 0..15000 {
+    fn saved_state_resumes() {
+        let mut circuit = Circuit::new(lines(
+            "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output",
+        ))
+        .unwrap();
+        for _ in 0..3 {
             circuit.press_button();
         }
-        assert_eq!(circuit.pulses, (4250, 2750));
+
+        let json = serde_json::to_string(&circuit).unwrap();
+        let mut resumed: Circuit = serde_json::from_str(&json).unwrap();
+        for _ in 3..1000 {
+            resumed.press_button();
+        }
+        assert_eq!(resumed.pulses, (4250, 2750));
+    }
+
+    #[test]
+    fn generated_counter_period() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let size = GenSize {
+            lines: Some(1),
+            width: Some(6),
+        };
+        let mut circuit = Circuit::new(lines(&Day20.generate(&mut rng, &size))).unwrap();
+
+        // the counter's flip-flops all switch back off once it has counted to its period
+        let period = (1..64usize)
+            .find(|_| {
+                circuit.press_button();
+                circuit.modules.iter().all(|m| match m {
+                    Module::FlipFlop(flipflop) => !flipflop.status,
+                    Module::Conjunction(_) => true,
+                })
+            })
+            .unwrap();
+        assert!((32..64).contains(&period));
+        assert!((2..period).all(|d| !period.is_multiple_of(d)));
+    }
+
+    #[test]
+    fn generated_rx_periods() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let size = GenSize {
+            lines: Some(3),
+            width: Some(6),
+        };
+        let circuit = Circuit::new(lines(&Day20.generate(&mut rng, &size))).unwrap();
+
+        let periods = rx_periods(&circuit).unwrap();
+        assert_eq!(periods.len(), 3);
+        for (_, period) in periods.iter() {
+            assert!((32..64).contains(period));
+        }
+        let product: usize = periods.iter().map(|(_, period)| period).product();
+        assert_eq!(part2(&circuit), Ok(product));
+    }
+
+    #[test]
+    fn rx_must_hang_off_a_conjunction() {
+        let no_rx = Circuit::new(lines("broadcaster -> a\n%a -> b\n&b -> a")).unwrap();
+        let err = part2(&no_rx).unwrap_err().to_string();
+        assert_eq!(err, "day 20: nothing sends to rx");
+
+        let flipflop = Circuit::new(lines("broadcaster -> a\n%a -> rx")).unwrap();
+        let err = part2(&flipflop).unwrap_err().to_string();
+        assert!(err.contains("flip-flop a"), "{err}");
+
+        // a goes high on every other press but only from the first, and c
+        // never hears anything so never sends at all
+        let irregular =
+            Circuit::new(lines("broadcaster -> a\n%a -> hub\n&c -> hub\n&hub -> rx")).unwrap();
+        let err = part2(&irregular).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 20: inputs of hub don't repeat from the first press: \
+             a (high on presses 1 and 3), c (not high twice in 100000 presses)"
+        );
+    }
+
+    #[test]
+    fn trace_and_state() {
+        let mut circuit = Circuit::new(lines(
+            "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output",
+        ))
+        .unwrap();
+
+        let mut trace = Vec::new();
+        circuit.write_trace(2, &mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        let first: Vec<&str> = trace.lines().take_while(|l| l.starts_with("1:")).collect();
+        assert_eq!(
+            first,
+            [
+                "1: button -low-> broadcaster",
+                "1: broadcaster -low-> a",
+                "1: a -high-> inv",
+                "1: a -high-> con",
+                "1: inv -low-> b",
+                "1: con -high-> output",
+                "1: b -high-> con",
+                "1: con -low-> output",
+            ]
+        );
+        assert_eq!(circuit.presses(), 2);
+        assert!(trace.ends_with("2: con -high-> output\n"));
+
+        // after the second press a is off again and b stays on
+        assert_eq!(circuit.flip_flop("a"), Some(false));
+        assert_eq!(circuit.flip_flop("b"), Some(true));
+        assert_eq!(circuit.flip_flop("con"), None);
+        assert_eq!(
+            circuit.conjunction("con"),
+            Some(&[("a".to_string(), false), ("b".to_string(), true)][..])
+        );
+        let later = circuit.after_presses(2);
+        assert_eq!((later.presses(), later.flip_flop("b")), (4, Some(false)));
+    }
+
+    #[tokio::test]
+    async fn knobs_write_files() {
+        let dir = std::env::temp_dir().join(format!("aoc-day20-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut knobs = Knobs::default();
+        for name in ["trace", "state", "dot"] {
+            knobs.set(name, dir.join(name).to_str().unwrap());
+        }
+
+        let example = "broadcaster -> a\n%a -> inv, con\n&inv -> b\n%b -> con\n&con -> output";
+        let solve_example = |knobs: Knobs| async move {
+            let (tx, rx) = tokio::sync::mpsc::channel(5);
+            for line in lines(example) {
+                tx.send(line).await.unwrap();
+            }
+            drop(tx);
+            Day20
+                .solve_with(InputStream::Lines(rx), Parts::Part1, &knobs)
+                .await
+        };
+
+        let answer = solve_example(knobs).await.unwrap();
+        assert_eq!(answer.part1.unwrap().to_string(), "11687500");
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        let trace = read("trace");
+        assert!(trace.starts_with("1: button -low-> broadcaster\n"));
+        assert!(trace.ends_with("1000: con -high-> output\n"));
+        assert_eq!(
+            read("state"),
+            "%a off\n&inv a=low\n%b off\n&con a=low, b=low\n"
+        );
+        let circuit = Circuit::new(lines(example)).unwrap();
+        assert_eq!(read("dot"), circuit.to_dot());
+
+        // a directory can't be written over
+        let mut knobs = Knobs::default();
+        knobs.set("dot", dir.to_str().unwrap());
+        let err = solve_example(knobs).await.unwrap_err().to_string();
+        assert!(err.starts_with("day 20: can't write the dot to"), "{err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dot_export() {
+        let circuit = Circuit::new(lines("broadcaster -> a\n%a -> hub\n&hub -> rx, a")).unwrap();
+        assert_eq!(
+            circuit.to_dot(),
+            r#"digraph circuit {
+    broadcaster [shape=doubleoctagon, style=filled, fillcolor=palegreen];
+    "a" [label="%a", shape=box, style=filled, fillcolor=lightblue];
+    "hub" [label="&hub", shape=ellipse, style=filled, fillcolor=lightpink];
+    "rx" [shape=plaintext];
+    "broadcaster" -> "a";
+    "a" -> "hub";
+    "hub" -> "rx";
+    "hub" -> "a";
+}
+"#
+        );
     }
 }
@@ -0,0 +1,38 @@
+use crate::Day21;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A `width` x `width` garden (forced odd) with the start in the centre and
+/// the middle row, middle column and border left clear, like the real puzzle,
+/// so part 2 can take its quadratic shortcut. `lines` is the percentage of
+/// rocks elsewhere.
+impl Generator for Day21 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let side = size.width_or(131).max(5) | 1;
+        let density = size.lines_or(12).min(60);
+        let mid = side / 2;
+        let mut out = String::new();
+
+        for row in 0..side {
+            for col in 0..side {
+                let clear = (row == mid)
+                    || (col == mid)
+                    || (row == 0)
+                    || (col == 0)
+                    || (row == side - 1)
+                    || (col == side - 1);
+                out.push(if (row, col) == (mid, mid) {
+                    'S'
+                } else if !clear && rng.gen_range(0..100) < density {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
@@ -1,116 +1,39 @@
-use std::collections::{HashMap, HashSet};
+mod generate;
 
-use tokio::sync::mpsc::Receiver;
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, Knob, Knobs, ParseError,
+    Parts, SolveError, SolveFuture, Solver,
+};
+use exact::interpolate;
+use grid::{Direction, Grid, GridBuilder, Point};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-async fn parse_line(line_no: isize, line: &str) -> (Vec<(isize, isize)>, Option<(isize, isize)>) {
-    let start: Vec<_> = line
-        .chars()
-        .enumerate()
-        .filter_map(|(col, chr)| {
-            if chr == 'S' {
-                Some((line_no, col.try_into().unwrap()))
-            } else {
-                None
-            }
-        })
-        .collect();
+use tokio::sync::mpsc::Receiver;
+use tracing::{debug, instrument};
 
-    let rocks = line
-        .chars()
-        .enumerate()
-        .filter_map(|(col, chr)| {
-            if ['#'].contains(&chr) {
-                Some((line_no, col.try_into().unwrap()))
-            } else {
-                None
-            }
-        })
-        .collect();
+const DAY: &str = "21";
 
-    (rocks, start.first().copied())
-}
+type RowAndStart = (Vec<bool>, Option<usize>);
 
-fn untile(
-    loc: &(isize, isize),
-    lower_bound: &(isize, isize),
-    upper_bound: &(isize, isize),
-) -> (isize, isize) {
-    let mut y = loc.0;
-    let mut x = loc.1;
-    let y_size = upper_bound.0 - lower_bound.0 + 1;
-    let x_size = upper_bound.1 - lower_bound.1 + 1;
+async fn parse_line(line_no: usize, line: &str) -> Result<RowAndStart, ParseError> {
+    InputLine::new(DAY, line_no, line).only_chars(".#S")?;
 
-    while y < lower_bound.0 {
-        y += y_size;
-    }
-    while x < lower_bound.1 {
-        x += x_size;
-    }
-    while y > upper_bound.0 {
-        y -= y_size;
-    }
-    while x > upper_bound.1 {
-        x -= x_size;
-    }
-
-    (y, x)
+    let rocks = line.chars().map(|chr| chr == '#').collect();
+    Ok((rocks, line.find('S')))
 }
 
-fn neighbours(
-    loc: &(isize, isize),
-    rocks: &HashSet<(isize, isize)>,
-    lower_bound: &(isize, isize),
-    upper_bound: &(isize, isize),
-    tile: bool,
-) -> Vec<(isize, isize)> {
-    let mut output = Vec::new();
-
-    if tile {
-        output.push((loc.0 - 1, loc.1));
-        output.push((loc.0, loc.1 - 1));
-        output.push((loc.0 + 1, loc.1));
-        output.push((loc.0, loc.1 + 1));
-    } else {
-        if loc.0 > lower_bound.0 {
-            output.push((loc.0 - 1, loc.1));
-        }
-        if loc.1 > lower_bound.1 {
-            output.push((loc.0, loc.1 - 1));
-        }
-        if loc.0 < upper_bound.0 {
-            output.push((loc.0 + 1, loc.1));
-        }
-        if loc.1 < upper_bound.1 {
-            output.push((loc.0, loc.1 + 1));
-        }
-    }
+fn neighbours(loc: &(isize, isize), rocks: &Grid<bool>) -> Vec<(isize, isize)> {
+    Direction::ALL
+        .into_iter()
+        .map(|dir| dir.step_signed(*loc))
+        .filter(|next| rocks.get_signed(*next) == Some(&false))
+        .collect()
+}
 
-    if tile {
-        output
-            .into_iter()
-            .filter(|loc| {
-                let uloc = untile(loc, lower_bound, upper_bound);
-                !rocks.contains(&uloc)
-            })
-            .collect()
-    } else {
-        output
-            .into_iter()
-            .filter(|loc| !rocks.contains(loc))
-            .collect()
-    }
-}
-
-fn visit_gardens(
-    rocks: &HashSet<(isize, isize)>,
-    lower_bound: (isize, isize),
-    upper_bound: (isize, isize),
-    tile: bool,
-    start: &(isize, isize),
-    steps: isize,
-) -> Vec<usize> {
+#[instrument(skip(rocks, start))]
+fn visit_gardens(rocks: &Grid<bool>, start: Point, steps: usize) -> Vec<usize> {
     let mut positions = HashSet::new();
-    positions.insert(*start);
+    positions.insert((start.0 as isize, start.1 as isize));
     let mut gardens = vec![1];
 
     let mut cache: HashMap<(isize, isize), Vec<(isize, isize)>> = HashMap::new();
@@ -122,7 +45,7 @@ fn visit_gardens(
             let nbors = if let Some(nbors) = cache.get(step) {
                 nbors.clone()
             } else {
-                let nbors = neighbours(step, rocks, &lower_bound, &upper_bound, tile);
+                let nbors = neighbours(step, rocks);
                 cache.insert(*step, nbors.clone());
                 nbors
             };
@@ -143,106 +66,386 @@ fn visit_gardens(
     gardens
 }
 
-fn fit_quadratic_equation(x_values: Vec<f64>, y_values: Vec<f64>) -> (f64, f64, f64) {
-    // this function was written by ChatGPT and took a couple of tries to get it right
-    let n = x_values.len() as f64;
-    let sum_x = x_values.iter().sum::<f64>();
-    let sum_y = y_values.iter().sum::<f64>();
-    let sum_xx = x_values.iter().map(|&x| x * x).sum::<f64>();
-    let sum_xy = x_values
-        .iter()
-        .zip(&y_values)
-        .map(|(&x, &y)| x * y)
-        .sum::<f64>();
-    let sum_xxx = x_values.iter().map(|&x| x * x * x).sum::<f64>();
-    let sum_xxxx = x_values.iter().map(|&x| x * x * x * x).sum::<f64>();
-    let sum_xxy = x_values
-        .iter()
-        .zip(&y_values)
-        .map(|(&x, &y)| x * x * y)
-        .sum::<f64>();
-
-    // Matrix elements of the normal equation
-    let a11 = n;
-    let a12 = sum_x;
-    let a13 = sum_xx;
-    let a21 = sum_x;
-    let a22 = sum_xx;
-    let a23 = sum_xxx;
-    let a31 = sum_xx;
-    let a32 = sum_xxx;
-    let a33 = sum_xxxx;
-
-    let b1 = sum_y;
-    let b2 = sum_xy;
-    let b3 = sum_xxy;
-
-    // Solving the normal equations using Cramer's Rule
-    let det_a = a11 * (a22 * a33 - a23 * a32) - a12 * (a21 * a33 - a23 * a31)
-        + a13 * (a21 * a32 - a22 * a31);
-    let det_a1 =
-        b1 * (a22 * a33 - a23 * a32) - b2 * (a21 * a33 - a23 * a31) + b3 * (a21 * a32 - a22 * a31);
-    let det_a2 =
-        a11 * (b2 * a33 - b3 * a32) - a12 * (b1 * a33 - b3 * a31) + a13 * (b1 * a32 - b2 * a31);
-    let det_a3 =
-        a11 * (a22 * b3 - a23 * b2) - a12 * (a21 * b3 - a23 * b1) + a13 * (a21 * b2 - a22 * b1);
-
-    let a = det_a1 / det_a;
-    let b = det_a2 / det_a;
-    let c = det_a3 / det_a;
-
-    (a, b, c)
-}
-
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// One tile of the garden, `true` for rock, and where the elf starts
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Garden {
+    rocks: Grid<bool>,
+    start: Point,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Garden, SolveError> {
     let mut tasks = Vec::new();
-    let mut rows = 0;
-    let mut cols = 0;
     while let Some((line_no, line)) = rx.recv().await {
         if line.is_empty() {
             continue;
         }
-        let line_no: isize = line_no.try_into().unwrap();
-        rows = std::cmp::max(rows, line_no);
-        cols = std::cmp::max(cols, line.trim().len().try_into().unwrap());
-        let task = tokio::spawn(async move { parse_line(line_no, &line).await });
-        tasks.push(task);
+        tasks.push(spawn_line(line_no, async move {
+            (line_no, parse_line(line_no, line.trim()).await, line)
+        }));
     }
 
-    let mut rocks = HashSet::new();
+    let mut builder = GridBuilder::new();
     let mut start = (0, 0);
-    for task in tasks {
-        if let Ok((rocks_, start_)) = task.await {
-            for rock in rocks_.into_iter() {
-                rocks.insert(rock);
+    for (line_no, result, line) in join_tasks(tasks).await? {
+        let (row, start_) = result?;
+        builder
+            .insert_row(line_no, row)
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
+        if let Some(col) = start_ {
+            start = (line_no, col);
+        }
+    }
+    let rocks = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
+
+    debug!(rows = rocks.rows(), cols = rocks.cols(), "garden size");
+    Ok(Garden { rocks, start })
+}
+
+pub fn part1(garden: &Garden, steps: usize) -> usize {
+    let gardens = visit_gardens(&garden.rocks, garden.start, steps);
+    *gardens.last().unwrap()
+}
+
+/// How many steps the elf takes in part 1
+const STEPS: usize = 64;
+/// How many steps the elf takes in part 2
+const TARGET_STEPS: usize = 26501365;
+const KNOBS: &[Knob] = &[
+    Knob {
+        name: "steps",
+        help: "how many steps the elf takes in part 1",
+        check: &[],
+    },
+    Knob {
+        name: "target_steps",
+        help: "how many steps the elf takes in part 2",
+        check: &[],
+    },
+];
+/// The most tiles out from the start to search before deciding the distances
+/// through the repeating garden never settle into a pattern
+const MAX_RADIUS: usize = 12;
+
+/// Steps from the start to every plot of a square block of garden tiles,
+/// `radius` tiles either side of the start's own
+struct TileDistances {
+    rows: usize,
+    cols: usize,
+    radius: usize,
+    steps: Grid<Option<u32>>,
+}
+
+impl TileDistances {
+    fn new(garden: &Garden, radius: usize) -> Self {
+        let (rows, cols) = (garden.rocks.rows(), garden.rocks.cols());
+        let across = 2 * radius + 1;
+        let mut steps = Grid::new(across * rows, across * cols, None);
+
+        let start = (
+            radius * rows + garden.start.0,
+            radius * cols + garden.start.1,
+        );
+        steps[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(point) = queue.pop_front() {
+            let next = steps[point].unwrap() + 1;
+            let nbors: Vec<Point> = steps.neighbours(point).map(|(_, nbor)| nbor).collect();
+            for nbor in nbors {
+                let rock = garden.rocks[(nbor.0 % rows, nbor.1 % cols)];
+                if !rock && steps[nbor].is_none() {
+                    steps[nbor] = Some(next);
+                    queue.push_back(nbor);
+                }
             }
-            if let Some(starting_point) = start_ {
-                start = starting_point;
+        }
+
+        Self {
+            rows,
+            cols,
+            radius,
+            steps,
+        }
+    }
+
+    /// Steps to `plot` in the tile `tile` away from the start's, `None` if
+    /// it can't be reached
+    fn get(&self, tile: (isize, isize), plot: Point) -> Option<u32> {
+        let row = (tile.0 + self.radius as isize) as usize * self.rows + plot.0;
+        let col = (tile.1 + self.radius as isize) as usize * self.cols + plot.1;
+        self.steps[(row, col)]
+    }
+
+    /// Plots in the whole block the elf could be on after exactly `steps`
+    fn count(&self, steps: usize) -> usize {
+        self.steps
+            .positions(|dist| dist.is_some_and(|dist| ends_on(dist as usize, steps)))
+            .count()
+    }
+}
+
+/// A plot first reached after `dist` steps can be stood on after `steps`
+/// when there are enough steps and the rest can be spent stepping back and forth
+fn ends_on(dist: usize, steps: usize) -> bool {
+    (dist <= steps) && ((steps - dist).is_multiple_of(2))
+}
+
+/// How many of `dist`, `dist + period`, `dist + 2 * period`... the elf can
+/// end on after `steps`
+fn count_line(dist: usize, period: usize, steps: usize) -> i128 {
+    if dist > steps {
+        return 0;
+    }
+    let last = ((steps - dist) / period) as i128;
+    match (period.is_multiple_of(2), (steps - dist).is_multiple_of(2)) {
+        (true, true) => last + 1,
+        (true, false) => 0,
+        // odd periods flip parity every tile
+        (false, true) => last / 2 + 1,
+        (false, false) => (last + 1) / 2,
+    }
+}
+
+/// As [`count_line`] but for the wedge of tiles off a corner, where `m`
+/// tiles further out there are `m + 1` tiles
+fn count_wedge(dist: usize, period: usize, steps: usize) -> i128 {
+    if dist > steps {
+        return 0;
+    }
+    let last = ((steps - dist) / period) as i128;
+    match (period.is_multiple_of(2), (steps - dist).is_multiple_of(2)) {
+        (true, true) => (last + 1) * (last + 2) / 2,
+        (true, false) => 0,
+        (false, even) => {
+            // every other m from `first`, each worth m + 1 tiles
+            let first = if even { 0 } else { 1 };
+            if last < first {
+                return 0;
             }
+            let terms = (last - first) / 2 + 1;
+            terms * (first + 1) + terms * (terms - 1)
         }
     }
+}
 
-    dbg!(&rows);
-    dbg!(&cols);
-    let part1 = visit_gardens(&rocks, (0, 0), (rows, cols - 1), false, &start, 64);
-    let part2 = visit_gardens(&rocks, (0, 0), (rows, cols - 1), true, &start, 65 + 131 * 2);
+/// Count by fitting a quadratic to samples a whole tile apart, which works
+/// when the elf spreads through the tiles evenly. `None` when the garden
+/// isn't square, `steps` is too small to need it, or the samples show the
+/// growth isn't quadratic.
+fn quadratic(garden: &Garden, steps: usize) -> Option<i128> {
+    let side = garden.rocks.rows();
+    if garden.rocks.cols() != side {
+        return None;
+    }
+    let offset = steps % side;
+    let samples: Vec<usize> = (0..6).map(|m| offset + m * side).collect();
+    if steps <= samples[5] {
+        return None;
+    }
 
-    let y: Vec<_> = part2
+    let distances = TileDistances::new(garden, samples[5] / side + 2);
+    let counts: Vec<i128> = samples
         .iter()
-        .skip(65)
-        .step_by(131)
-        .map(|val| *val as f64)
+        .map(|&steps| distances.count(steps) as i128)
         .collect();
+    let second: Vec<i128> = counts.windows(3).map(|w| w[2] - 2 * w[1] + w[0]).collect();
+    debug!(?counts, ?second, "samples a tile apart");
+    // the first sample may come before the growth settles down
+    if second[1..].windows(2).any(|w| w[0] != w[1]) {
+        return None;
+    }
+
+    let points: Vec<(i128, i128)> = (3..6).map(|m| (m as i128, counts[m])).collect();
+    interpolate(&points, ((steps - offset) / side) as i128).to_integer()
+}
+
+/// Count exactly, tile by tile. Far enough out the steps to a plot go up by
+/// one tile's length for every tile further away, so only a block of tiles
+/// around the start needs searching and every tile past its edge follows
+/// from the tile on the edge.
+fn tiled(garden: &Garden, steps: usize) -> Result<i128, SolveError> {
+    for radius in 3..=MAX_RADIUS {
+        let distances = TileDistances::new(garden, radius);
+        let edge = radius as isize - 1;
+        if !settled(&distances, edge) {
+            continue;
+        }
+        debug!(radius, "tile distances settle");
+
+        let (rows, cols) = (distances.rows, distances.cols);
+        let mut total = 0i128;
+        for plot in garden.rocks.positions(|rock| !rock) {
+            for ti in -edge..=edge {
+                for tj in -edge..=edge {
+                    let Some(dist) = distances.get((ti, tj), plot) else {
+                        continue;
+                    };
+                    let dist = dist as usize;
+                    total += match (ti.abs() == edge, tj.abs() == edge) {
+                        (false, false) => ends_on(dist, steps) as i128,
+                        (true, false) => count_line(dist, rows, steps),
+                        (false, true) => count_line(dist, cols, steps),
+                        (true, true) if rows == cols => count_wedge(dist, rows, steps),
+                        (true, true) => (0..)
+                            .map(|down| dist + down * rows)
+                            .take_while(|dist| *dist <= steps)
+                            .map(|dist| count_line(dist, cols, steps))
+                            .sum(),
+                    };
+                }
+            }
+        }
+        return Ok(total);
+    }
+
+    Err(SolveError::Unsolvable {
+        day: DAY,
+        message: format!("steps through the garden don't settle within {MAX_RADIUS} tiles"),
+    })
+}
+
+/// Whether every tile on the `edge` of the block is exactly one tile's
+/// length further than the tile before it, along both axes
+fn settled(distances: &TileDistances, edge: isize) -> bool {
+    let (rows, cols) = (distances.rows as u32, distances.cols as u32);
+    let further = |outer: Option<u32>, inner: Option<u32>, by: u32| outer == inner.map(|d| d + by);
+
+    (0..distances.rows).all(|row| {
+        (0..distances.cols).all(|col| {
+            let plot = (row, col);
+            (-edge..=edge).all(|across| {
+                [-edge, edge].into_iter().all(|side| {
+                    let inward = side - side.signum();
+                    further(
+                        distances.get((side, across), plot),
+                        distances.get((inward, across), plot),
+                        rows,
+                    ) && further(
+                        distances.get((across, side), plot),
+                        distances.get((across, inward), plot),
+                        cols,
+                    )
+                })
+            })
+        })
+    })
+}
+
+/// How many plots the elf could be on after exactly `steps` in the garden
+/// repeated forever in every direction
+pub fn reachable(garden: &Garden, steps: usize) -> Result<i128, SolveError> {
+    if let Some(count) = quadratic(garden, steps) {
+        return Ok(count);
+    }
+    debug!(steps, "no quadratic shortcut, counting tile by tile");
+    tiled(garden, steps)
+}
+
+pub fn part2(garden: &Garden, target_steps: usize) -> Result<i128, SolveError> {
+    reachable(garden, target_steps)
+}
 
-    let x: Vec<_> = (0..y.len()).map(|val| val as f64).collect();
-    let coeffs = fit_quadratic_equation(x, y);
-    let target = 26501365.0;
-    let x = (target - 65.0) / 131.0;
-    let part2 = coeffs.2 * x * x + coeffs.1 * x + coeffs.0;
-
-    println!(
-        "Part 1: {} Part 2: {}",
-        part1.last().unwrap(),
-        part2.round()
-    );
+pub async fn solve(
+    rx: Receiver<(usize, String)>,
+    parts: Parts,
+    knobs: Knobs,
+) -> Result<Answer, SolveError> {
+    let steps = knobs.get_or(DAY, "steps", STEPS)?;
+    let target_steps = knobs.get_or(DAY, "target_steps", TARGET_STEPS)?;
+    let garden = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&garden, steps)),
+        parts
+            .part2()
+            .then(|| part2(&garden, target_steps))
+            .transpose()?,
+    ))
+}
+
+pub struct Day21;
+
+impl Solver for Day21 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        self.solve_with(input, parts, &Knobs::default())
+    }
+
+    fn knobs(&self) -> &'static [Knob] {
+        KNOBS
+    }
+
+    fn solve_with(&self, input: InputStream, parts: Parts, knobs: &Knobs) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts, knobs.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn garden(input: &str) -> Garden {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        for line in input.lines().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn example_infinite() {
+        let garden = garden(
+            "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........",
+        )
+        .await;
+
+        for (steps, plots) in [
+            (6, 16),
+            (10, 50),
+            (50, 1594),
+            (100, 6536),
+            (500, 167004),
+            (1000, 668697),
+            (5000, 16733044),
+        ] {
+            assert_eq!(reachable(&garden, steps), Ok(plots), "{steps} steps");
+            assert_eq!(tiled(&garden, steps), Ok(plots), "{steps} steps");
+        }
+    }
+
+    #[tokio::test]
+    async fn off_centre_and_oblong() {
+        // a start off to one side of a garden that isn't square
+        let garden = garden(
+            "......
+.#..#.
+S..#..
+.#....
+......",
+        )
+        .await;
+
+        let distances = TileDistances::new(&garden, 8);
+        for steps in [0, 1, 7, 12, 23, 30] {
+            let plots = distances.count(steps) as i128;
+            assert_eq!(reachable(&garden, steps), Ok(plots), "{steps} steps");
+        }
+    }
 }
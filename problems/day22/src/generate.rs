@@ -0,0 +1,50 @@
+use crate::Day22;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// `lines` bricks of up to `width` cubes each, scattered over a 10x10
+/// footprint without overlapping, waiting to fall
+impl Generator for Day22 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let bricks = size.lines_or(1000);
+        let max_len = size.width_or(4).clamp(1, 10);
+        let mut taken = HashSet::new();
+        let mut out = String::new();
+        let mut placed = 0;
+        let mut height = 1;
+
+        while placed < bricks {
+            let len = rng.gen_range(1..=max_len);
+            let axis = rng.gen_range(0..3);
+            let mut start = [rng.gen_range(0..10), rng.gen_range(0..10), height];
+            if axis < 2 {
+                start[axis] = rng.gen_range(0..=(10 - len));
+            }
+            let cubes: Vec<[usize; 3]> = (0..len)
+                .map(|step| {
+                    let mut cube = start;
+                    cube[axis] += step;
+                    cube
+                })
+                .collect();
+
+            // keep the stack loosely packed by moving up once a level gets busy
+            height += rng.gen_range(0..=1);
+            if cubes.iter().any(|cube| taken.contains(cube)) {
+                continue;
+            }
+            taken.extend(cubes.iter().copied());
+
+            let end = cubes[len - 1];
+            out.push_str(&format!(
+                "{},{},{}~{},{},{}\n",
+                start[0], start[1], start[2], end[0], end[1], end[2]
+            ));
+            placed += 1;
+        }
+
+        out
+    }
+}
@@ -1,65 +1,60 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use interval::Interval;
 use std::collections::HashSet;
 use tokio::sync::mpsc::Receiver;
+use tracing::instrument;
 
-#[derive(Debug, Clone, Copy)]
-struct Interval {
-    start: usize,
-    end: usize,
-}
-
-impl Interval {
-    fn overlap(&self, other: &Self) -> bool {
-        (self.end >= other.start) & (other.end >= self.start)
-    }
-}
+const DAY: &str = "22";
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Brick {
     number: usize,
     supported_by: Vec<usize>,
-    x: Interval,
-    y: Interval,
-    z: Interval,
+    x: Interval<usize>,
+    y: Interval<usize>,
+    z: Interval<usize>,
 }
 
-async fn parse_line(line_no: usize, line: &str) -> Brick {
-    let splits: Vec<_> = line
+async fn parse_line(line_no: usize, line: &str) -> Result<Brick, ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let splits: Vec<usize> = line
         .trim()
         .split(['~', ','])
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect();
+        .map(|s| input.parse(s))
+        .collect::<Result<_, _>>()?;
+    if splits.len() != 6 {
+        return Err(input.error("expected two x,y,z corners joined by ~"));
+    }
+
+    let corners = |a: usize, b: usize| Interval::inclusive(a.min(b), a.max(b));
+    let (x, y, z) = (
+        corners(splits[0], splits[3]),
+        corners(splits[1], splits[4]),
+        corners(splits[2], splits[5]),
+    );
 
-    let x = Interval {
-        start: std::cmp::min(splits[0], splits[3]),
-        end: std::cmp::max(splits[0], splits[3]),
-    };
-    let y = Interval {
-        start: std::cmp::min(splits[1], splits[4]),
-        end: std::cmp::max(splits[1], splits[4]),
-    };
-    let z = Interval {
-        start: std::cmp::min(splits[2], splits[5]),
-        end: std::cmp::max(splits[2], splits[5]),
-    };
-
-    Brick {
+    Ok(Brick {
         number: line_no,
         supported_by: Vec::new(),
         x,
         y,
         z,
-    }
+    })
 }
 
-fn removable_bricks(bricks: &[Brick]) -> (usize, usize) {
+/// Let every brick fall as far as it can, noting what each one comes to rest on
+#[instrument(skip_all, fields(bricks = bricks.len()))]
+fn settle(bricks: &[Brick]) -> Vec<Brick> {
     let mut bricks = bricks.to_vec();
-    bricks.sort_by_key(|b| (b.z.start, b.x.start, b.y.start));
-
-    // drop the first brick
-    bricks[0].z.start -= bricks[0].z.start;
-    bricks[0].z.end -= bricks[0].z.start;
+    bricks.sort_by_key(|b| (b.z.start(), b.x.start(), b.y.start()));
 
-    for num in 1..bricks.len() {
+    for num in 0..bricks.len() {
         let x = bricks[num].x;
         let y = bricks[num].y;
         let z = bricks[num].z;
@@ -67,31 +62,19 @@ fn removable_bricks(bricks: &[Brick]) -> (usize, usize) {
         let iter = bricks
             .iter()
             .take(num)
-            .filter(|b| b.x.overlap(&x) & b.y.overlap(&y));
+            .filter(|b| b.x.overlaps(&x) & b.y.overlaps(&y));
 
-        let highest = if let Some(val) = iter.clone().map(|b| b.z.end).max() {
-            val
-        } else {
-            0
-        };
+        let highest = iter.clone().map(|b| b.z.last()).max().unwrap_or(0);
 
         bricks[num].supported_by = iter
-            .filter(|b| b.z.end == highest)
+            .filter(|b| b.z.last() == highest)
             .map(|b| b.number)
             .collect();
 
-        let drop = z.start - highest - 1;
-        bricks[num].z.start -= drop;
-        bricks[num].z.end -= drop;
+        bricks[num].z = z.translate(z.start(), highest + 1);
     }
 
-    let sole_supporters: HashSet<_> = bricks
-        .iter()
-        .filter(|b| b.supported_by.len() == 1)
-        .flat_map(|b| b.supported_by.clone())
-        .collect();
-
-    (bricks.len() - sole_supporters.len(), collapses(&bricks))
+    bricks
 }
 
 fn collapses(bricks: &[Brick]) -> usize {
@@ -125,23 +108,69 @@ fn collapses(bricks: &[Brick]) -> usize {
     total_collapses
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
+/// The bricks once they have all settled
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stack {
+    bricks: Vec<Brick>,
+}
+
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Stack, SolveError> {
     let mut tasks = Vec::new();
     while let Some((line_no, line)) = rx.recv().await {
         if line.is_empty() {
             continue;
         }
-        let task = tokio::spawn(async move { parse_line(line_no, &line).await });
-        tasks.push(task);
+        tasks.push(spawn_line(line_no, async move {
+            parse_line(line_no, &line).await
+        }));
     }
 
     let mut bricks = Vec::new();
-    for task in tasks {
-        if let Ok(brick) = task.await {
-            bricks.push(brick);
-        }
+    for result in join_tasks(tasks).await? {
+        bricks.push(result?);
+    }
+
+    Ok(Stack {
+        bricks: settle(&bricks),
+    })
+}
+
+pub fn part1(stack: &Stack) -> usize {
+    let sole_supporters: HashSet<_> = stack
+        .bricks
+        .iter()
+        .filter(|b| b.supported_by.len() == 1)
+        .flat_map(|b| b.supported_by.clone())
+        .collect();
+
+    stack.bricks.len() - sole_supporters.len()
+}
+
+pub fn part2(stack: &Stack) -> usize {
+    collapses(&stack.bricks)
+}
+
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let stack = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&stack)),
+        parts.part2().then(|| part2(&stack)),
+    ))
+}
+
+pub struct Day22;
+
+impl Solver for Day22 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
     }
 
-    let (part1, part2) = removable_bricks(&bricks);
-    println!("Part 1: {} Part 2: {}", part1, part2,);
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
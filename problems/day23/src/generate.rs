@@ -0,0 +1,61 @@
+use crate::Day23;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// A `lines` x `width` lattice of junctions joined by corridors heading right
+/// and down, with slopes at both ends of every corridor like the real maze
+impl Generator for Day23 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let junction_rows = size.lines_or(6).max(1);
+        let junction_cols = size.width_or(6).max(1);
+
+        let first_row = rng.gen_range(2..=8);
+        let mut spread = |count: usize, first: usize| -> Vec<usize> {
+            let mut at = first;
+            (0..count)
+                .map(|idx| {
+                    if idx > 0 {
+                        at += rng.gen_range(3..=12);
+                    }
+                    at
+                })
+                .collect()
+        };
+        let rows = spread(junction_rows, first_row);
+        let cols = spread(junction_cols, 1);
+
+        let height = rows[junction_rows - 1] + rng.gen_range(2..=8);
+        let width = cols[junction_cols - 1] + 2;
+        let mut grid = vec![vec!['#'; width]; height];
+
+        // the entrance and exit corridors
+        for row in grid.iter_mut().take(rows[0] + 1) {
+            row[1] = '.';
+        }
+        for row in grid.iter_mut().skip(rows[junction_rows - 1]) {
+            row[width - 2] = '.';
+        }
+
+        for &row in rows.iter() {
+            for pair in cols.windows(2) {
+                grid[row][pair[0]..=pair[1]].fill('.');
+                grid[row][pair[0] + 1] = '>';
+                grid[row][pair[1] - 1] = '>';
+            }
+        }
+        for &col in cols.iter() {
+            for pair in rows.windows(2) {
+                for row in grid.iter_mut().take(pair[1] + 1).skip(pair[0]) {
+                    row[col] = '.';
+                }
+                grid[pair[0] + 1][col] = 'v';
+                grid[pair[1] - 1][col] = 'v';
+            }
+        }
+
+        grid.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
@@ -1,282 +1,238 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputShape, InputStream, ParseError, Parts,
+    SolveError, SolveFuture, Solver,
+};
+use graph::{Graph, Labels, NodeId};
+use grid::{Direction, Grid, GridBuilder, Point};
 use tokio::sync::mpsc::Receiver;
+use tracing::instrument;
+
+const DAY: &str = "23";
 
 #[derive(Debug, Eq, PartialEq)]
-enum PathSlope {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathSlope {
     Path,
-    SlopeN,
-    SlopeE,
-    SlopeW,
-    SlopeS,
+    /// Can only be walked down, in this direction
+    Slope(Direction),
 }
 
-async fn parse_line(line_no: usize, line: &str) -> Vec<((usize, usize), PathSlope)> {
-    line.trim()
+/// Walkable tiles, `None` for forest
+type Map = Grid<Option<PathSlope>>;
+
+async fn parse_line(line_no: usize, line: &str) -> Result<Vec<Option<PathSlope>>, ParseError> {
+    InputLine::new(DAY, line_no, line.trim()).only_chars("#.><v^")?;
+
+    Ok(line
+        .trim()
         .chars()
-        .enumerate()
-        .filter_map(|(col, chr)| match chr {
-            '.' => Some(((line_no, col), PathSlope::Path)),
-            '>' => Some(((line_no, col), PathSlope::SlopeE)),
-            'v' => Some(((line_no, col), PathSlope::SlopeS)),
-            '^' => Some(((line_no, col), PathSlope::SlopeN)),
-            '<' => Some(((line_no, col), PathSlope::SlopeW)),
+        .map(|chr| match chr {
+            '.' => Some(PathSlope::Path),
+            '>' => Some(PathSlope::Slope(Direction::E)),
+            'v' => Some(PathSlope::Slope(Direction::S)),
+            '^' => Some(PathSlope::Slope(Direction::N)),
+            '<' => Some(PathSlope::Slope(Direction::W)),
             _ => None,
         })
-        .collect()
-}
-
-#[derive(Debug, Clone)]
-struct Progress {
-    loc: (usize, usize),
-    last_loc: (usize, usize),
-    steps: usize,
-}
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct ProgressCanClimb {
-    loc: (usize, usize),
-    last_loc: (usize, usize),
-    slope_history: HashSet<(usize, usize)>,
-    steps: usize,
+        .collect())
 }
 
-fn next_steps(
-    loc: &(usize, usize),
-    map: &HashMap<(usize, usize), PathSlope>,
-) -> Vec<(usize, usize)> {
+fn next_steps(loc: &Point, map: &Map) -> Vec<Point> {
     // short circuit if on a slope
-
-    if let Some(pathslope) = map.get(loc) {
-        match pathslope {
-            PathSlope::Path => (),
-            PathSlope::SlopeN => return vec![(loc.0 - 1, loc.1)],
-            PathSlope::SlopeE => return vec![(loc.0, loc.1 + 1)],
-            PathSlope::SlopeW => return vec![(loc.0, loc.1 - 1)],
-            PathSlope::SlopeS => return vec![(loc.0 + 1, loc.1)],
-        }
+    if let Some(Some(PathSlope::Slope(dir))) = map.get(*loc) {
+        return map.step(*loc, *dir).into_iter().collect();
     }
 
-    let mut moves = Vec::new();
-    if loc.0 > 0 {
-        if let Some(pathslope) = map.get(&(loc.0 - 1, loc.1)) {
-            if *pathslope != PathSlope::SlopeS {
-                moves.push((loc.0 - 1, loc.1));
-            }
-        }
-    }
+    // never walk up a slope
+    map.neighbours(*loc)
+        .filter(|(dir, next)| match &map[*next] {
+            Some(PathSlope::Path) => true,
+            Some(PathSlope::Slope(slope)) => *slope != dir.opposite(),
+            None => false,
+        })
+        .map(|(_, next)| next)
+        .collect()
+}
 
-    if loc.1 > 0 {
-        if let Some(pathslope) = map.get(&(loc.0, loc.1 - 1)) {
-            if *pathslope != PathSlope::SlopeE {
-                moves.push((loc.0, loc.1 - 1));
+/// Every walkable tile as a node, with an edge for each step `moves` allows,
+/// contracted down to the junctions and the two ends. Edges into the start
+/// and out of the end are left out.
+fn trail_graph(
+    map: &Map,
+    start: Point,
+    end: Point,
+    moves: impl Fn(&Point) -> Vec<Point>,
+) -> (Graph<usize>, NodeId, NodeId) {
+    let mut labels = Labels::new();
+    for point in map.positions(Option::is_some) {
+        labels.intern(point);
+    }
+
+    let mut graph = Graph::with_nodes(labels.len());
+    for (node, point) in labels.iter() {
+        for next in moves(point) {
+            if let Some(next_node) = labels.id(&next) {
+                graph.add_edge(node, next_node, 1);
             }
         }
     }
 
-    if let Some(pathslope) = map.get(&(loc.0 + 1, loc.1)) {
-        if *pathslope != PathSlope::SlopeN {
-            moves.push((loc.0 + 1, loc.1));
-        }
-    }
+    let (mut contracted, kept) = graph.contract(|node| {
+        let point = *labels.key(node);
+        let paths = map
+            .neighbours(point)
+            .filter(|(_, next)| map[*next].is_some())
+            .count();
+        (point == start) | (point == end) | (paths >= 3)
+    });
+    let find = |point: Point| {
+        contracted
+            .nodes()
+            .find(|node| *labels.key(kept[node.index()]) == point)
+            .expect("the ends are walkable, so they're kept")
+    };
+
+    let (start, end) = (find(start), find(end));
+
+    // the corridors at either end have no slopes, but a route never goes
+    // back to the start or on past the end
+    for node in contracted.nodes().collect::<Vec<_>>() {
+        contracted.remove_edge(node, start);
+        contracted.remove_edge(end, node);
+    }
+    (contracted, start, end)
+}
 
-    if let Some(pathslope) = map.get(&(loc.0, loc.1 + 1)) {
-        if *pathslope != PathSlope::SlopeW {
-            moves.push((loc.0, loc.1 + 1));
-        }
-    }
+/// The first gap in `row`, an error naming `end` if it's all forest
+fn gap(map: &Map, row: usize, end: &str) -> Result<Point, ParseError> {
+    (0..map.cols())
+        .map(|col| (row, col))
+        .find(|point| map[*point].is_some())
+        .ok_or_else(|| InputLine::new(DAY, row, "").error(format!("no way {end} in this row")))
+}
 
-    moves
+fn unsolvable(message: &str) -> SolveError {
+    SolveError::Unsolvable {
+        day: DAY,
+        message: message.to_string(),
+    }
 }
 
-fn nbors(loc: &(usize, usize)) -> Vec<(usize, usize)> {
-    let mut output = vec![(loc.0 + 1, loc.1), (loc.0, loc.1 + 1)];
+#[instrument(name = "part1", skip(map))]
+fn longest_route(map: &Map, start: Point, end: Point) -> Result<usize, SolveError> {
+    let (graph, start, end) = trail_graph(map, start, end, |loc| next_steps(loc, map));
+    graph.dag_longest_path(start, end).ok_or_else(|| {
+        unsolvable("no route down the slopes to the exit, or one that can go round in circles")
+    })
+}
 
-    if loc.0 > 0 {
-        output.push((loc.0 - 1, loc.1));
-    }
-    if loc.1 > 0 {
-        output.push((loc.0, loc.1 - 1));
-    }
+#[instrument(name = "part2", skip(map))]
+fn longest_route_can_climb(map: &Map, start: Point, end: Point) -> Result<usize, SolveError> {
+    let (graph, start, end) = trail_graph(map, start, end, |loc| {
+        map.neighbours(*loc)
+            .filter(|(_, next)| map[*next].is_some())
+            .map(|(_, next)| next)
+            .collect()
+    });
+    graph
+        .longest_simple_path(start, end)
+        .ok_or_else(|| unsolvable("no route from the start to the exit"))
+}
 
-    output
+/// The trail map, with the gaps in its top and bottom rows that a route
+/// runs between
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trails {
+    map: Map,
+    start: Point,
+    end: Point,
 }
 
-fn longest_route_can_climb(
-    map: &HashMap<(usize, usize), PathSlope>,
-    start: (usize, usize),
-    end_row: usize,
-) -> usize {
-    // ported from https://gist.github.com/ke-hermann/279f352829cd590d61104c27cac59bdc
-    let paths: HashSet<(usize, usize)> = map.keys().cloned().collect();
-    let mut neighbours = HashMap::new();
-
-    for point in paths.iter() {
-        let point_nbors: Vec<(usize, usize)> = nbors(point)
-            .into_iter()
-            .filter(|p| paths.contains(p))
-            .collect();
-
-        neighbours.insert(*point, point_nbors);
+pub async fn parse(mut rx: Receiver<(usize, String)>) -> Result<Trails, SolveError> {
+    let mut tasks = Vec::new();
+    while let Some((line_no, line)) = rx.recv().await {
+        if line.is_empty() {
+            continue;
+        }
+        tasks.push(spawn_line(line_no, async move {
+            (line_no, parse_line(line_no, &line).await, line)
+        }));
     }
 
-    let mut intersections: HashSet<(usize, usize)> = neighbours
-        .iter()
-        .filter(|(_k, v)| v.len() >= 3)
-        .map(|(k, _v)| *k)
-        .collect();
-
-    // add start and end points to intersections
-    intersections.insert(start);
-    intersections.insert(*paths.iter().filter(|p| p.0 == end_row).next().unwrap());
-
-    let mut graph = HashMap::new();
-
-    for inter in intersections.iter() {
-        for neighbour in neighbours.get(&inter).unwrap() {
-            let mut history = HashSet::new();
-            history.insert(*inter);
-            let (point, distance) =
-                intersect_dist(*neighbour, 1, history, &intersections, &neighbours);
-            let entry = graph.entry(*inter).or_insert(Vec::new());
-            entry.push((point, distance));
-        }
+    let mut builder = GridBuilder::new();
+    for (line_no, row, line) in join_tasks(tasks).await? {
+        builder
+            .insert_row(line_no, row?)
+            .map_err(|e| ParseError::grid(DAY, e, &line))?;
     }
+    let map = builder.build().map_err(|e| ParseError::grid(DAY, e, ""))?;
 
-    let mut history = HashSet::new();
-    history.insert(start);
-    breadth_first_search(start, 0, history, end_row, &graph)
+    let start = gap(&map, 0, "in")?;
+    let end = gap(&map, map.rows() - 1, "out")?;
+    Ok(Trails { map, start, end })
 }
 
-fn breadth_first_search(
-    node: (usize, usize),
-    distance: usize,
-    history: HashSet<(usize, usize)>,
-    end_row: usize,
-    graph: &HashMap<(usize, usize), Vec<((usize, usize), usize)>>,
-) -> usize {
-    if node.0 == end_row {
-        return distance;
-    }
-
-    let max = graph
-        .get(&node)
-        .unwrap()
-        .iter()
-        .filter(|(point, _distance)| !history.contains(point))
-        .map(|(point, extra_distance)| {
-            let mut new_history = history.clone();
-            new_history.insert(*point);
-            breadth_first_search(
-                *point,
-                distance + extra_distance,
-                new_history,
-                end_row,
-                graph,
-            )
-        })
-        .max();
+pub fn part1(trails: &Trails) -> Result<usize, SolveError> {
+    longest_route(&trails.map, trails.start, trails.end)
+}
 
-    if let Some(max_) = max {
-        max_
-    } else {
-        0
-    }
+pub fn part2(trails: &Trails) -> Result<usize, SolveError> {
+    longest_route_can_climb(&trails.map, trails.start, trails.end)
 }
 
-fn intersect_dist(
-    cur: (usize, usize),
-    dist: usize,
-    history: HashSet<(usize, usize)>,
-    intersections: &HashSet<(usize, usize)>,
-    neighbours: &HashMap<(usize, usize), Vec<(usize, usize)>>,
-) -> ((usize, usize), usize) {
-    if intersections.contains(&cur) {
-        return (cur, dist);
-    }
+pub async fn solve(rx: Receiver<(usize, String)>, parts: Parts) -> Result<Answer, SolveError> {
+    let trails = parse(rx).await?;
 
-    if let Some(neighbour) = neighbours
-        .get(&cur)
-        .unwrap()
-        .into_iter()
-        .filter(|p| !history.contains(p))
-        .next()
-    {
-        let mut new_history = history.clone();
-        new_history.insert(cur);
-        return intersect_dist(*neighbour, dist + 1, new_history, intersections, neighbours);
-    }
-    dbg!(&cur);
-    dbg!(neighbours.get(&cur));
-    dbg!(&history);
-    unreachable!();
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&trails)).transpose()?,
+        parts.part2().then(|| part2(&trails)).transpose()?,
+    ))
 }
 
-fn longest_route(
-    map: &HashMap<(usize, usize), PathSlope>,
-    start: (usize, usize),
-    end_row: usize,
-) -> usize {
-    let mut queue = VecDeque::from(vec![Progress {
-        loc: start,
-        last_loc: start,
-        steps: 0,
-    }]);
-    let mut finished = Vec::new();
-
-    loop {
-        let mut next_queue = VecDeque::new();
-
-        for step in queue.iter() {
-            if step.loc.0 == end_row {
-                finished.push(step.steps);
-                continue;
-            }
-
-            for next_step in next_steps(&step.loc, map) {
-                if next_step == step.last_loc {
-                    continue;
-                }
+pub struct Day23;
 
-                next_queue.push_back(Progress {
-                    loc: next_step,
-                    last_loc: step.loc,
-                    steps: step.steps + 1,
-                })
-            }
-        }
+impl Solver for Day23 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
 
-        if next_queue.is_empty() {
-            break;
-        } else {
-            queue = next_queue;
-        }
+    fn input_shape(&self) -> InputShape {
+        InputShape::Enumerated
     }
 
-    finished.into_iter().max().unwrap()
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_enumerated(), parts))
+    }
 }
 
-pub async fn solve(mut rx: Receiver<(usize, String)>) {
-    let mut tasks = Vec::new();
-    while let Some((line_no, line)) = rx.recv().await {
-        if line.is_empty() {
-            continue;
-        }
-        let task = tokio::spawn(async move { parse_line(line_no, &line).await });
-        tasks.push(task);
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut map = HashMap::new();
-    for task in tasks {
-        if let Ok(pathslopes) = task.await {
-            for (loc, typ) in pathslopes.into_iter() {
-                map.insert(loc, typ);
-            }
+    async fn trails(input: &str) -> Result<Trails, SolveError> {
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count().max(1));
+        for line in input.lines().enumerate() {
+            tx.send((line.0, line.1.to_string())).await.unwrap();
         }
+        drop(tx);
+        parse(rx).await
     }
 
-    let end_row = map.keys().map(|k| k.0).max().unwrap();
-    let part1 = longest_route(&map, (0, 1), end_row);
-    let part2 = longest_route_can_climb(&map, (0, 1), end_row);
+    #[tokio::test]
+    async fn malformed_maps() {
+        let error = trails("###\n#.#\n#.#").await.err().unwrap().to_string();
+        assert!(error.contains("no way in"), "{error}");
+        let error = trails("#.#\n#.#\n###").await.err().unwrap().to_string();
+        assert!(error.contains("no way out"), "{error}");
+
+        // both ends are there, but the forest cuts them off
+        let cut_off = trails("#.#\n###\n#.#").await.unwrap();
+        assert!(part1(&cut_off).is_err());
+        assert!(part2(&cut_off).is_err());
 
-    println!("Part 1: {} Part 2: {}", part1, part2,);
+        let open = trails("#.#\n#.#\n#.#").await.unwrap();
+        assert_eq!((part1(&open), part2(&open)), (Ok(2), Ok(2)));
+    }
 }
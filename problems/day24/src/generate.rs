@@ -0,0 +1,45 @@
+use crate::Day24;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// `lines` hailstones that a single thrown rock hits, each at a distinct
+/// time, so part 2 has an exact answer. `width` bounds the velocities.
+impl Generator for Day24 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let stones = size.lines_or(300).max(3);
+        let speed = size.width_or(300).max(2) as i64;
+
+        let rock: [i64; 3] =
+            std::array::from_fn(|_| rng.gen_range(150_000_000_000_000..350_000_000_000_000));
+        let rock_velocity: [i64; 3] = std::array::from_fn(|_| rng.gen_range(-speed..=speed));
+
+        let mut times = HashSet::new();
+        let mut out = String::new();
+        while times.len() < stones {
+            let time: i64 = rng.gen_range(10_000_000_000..500_000_000_000);
+            if !times.insert(time) {
+                continue;
+            }
+
+            // a velocity matching the rock on any axis would never close the gap
+            let velocity: [i64; 3] = std::array::from_fn(|axis| loop {
+                let v = rng.gen_range(-speed..=speed);
+                if v != rock_velocity[axis] {
+                    break v;
+                }
+            });
+            let position: [i64; 3] = std::array::from_fn(|axis| {
+                rock[axis] + (rock_velocity[axis] - velocity[axis]) * time
+            });
+
+            out.push_str(&format!(
+                "{}, {}, {} @ {}, {}, {}\n",
+                position[0], position[1], position[2], velocity[0], velocity[1], velocity[2]
+            ));
+        }
+
+        out
+    }
+}
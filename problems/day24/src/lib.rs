@@ -1,213 +1,143 @@
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use exact::{det3, solve2, Rational};
 use tokio::sync::mpsc::Receiver;
+use tracing::{info_span, instrument};
+
+const DAY: &str = "24";
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Hail {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hail {
     position: [isize; 3],
     velocity: [isize; 3],
 }
 
 impl Hail {
-    async fn new(input: &str) -> Self {
-        let parts: Vec<_> = input
+    async fn new(input: &InputLine<'_>) -> Result<Self, ParseError> {
+        let parts: Vec<isize> = input
+            .text
             .split([',', '@'])
-            .map(|s| s.trim().parse::<isize>().unwrap())
-            .collect();
-
-        Hail {
-            position: [parts[0], parts[1], parts[2]],
-            velocity: [parts[3], parts[4], parts[5]],
+            .map(|s| input.parse(s))
+            .collect::<Result<_, _>>()?;
+
+        match parts[..] {
+            [px, py, pz, vx, vy, vz] => Ok(Hail {
+                position: [px, py, pz],
+                velocity: [vx, vy, vz],
+            }),
+            _ => Err(input.error("expected a position @ a velocity, three values each")),
         }
     }
 
-    fn get_slope_constant(&self) -> Option<(f64, f64)> {
-        if self.velocity[0] == 0 {
-            return None;
-        }
-
-        let m = self.velocity[1] as f64 / self.velocity[0] as f64;
-        let c = self.position[1] as f64 - (self.position[0] as f64) * m;
-
-        Some((m, c))
+    fn position(&self) -> [Rational; 3] {
+        self.position.map(Rational::from)
     }
 
-    fn will_collide_xy(&self, other: &Self) -> Option<[f64; 2]> {
-        let mc1 = self.get_slope_constant();
-        let mc2 = other.get_slope_constant();
-
-        match (mc1, mc2) {
-            // neither of the lines are vertical
-            (Some((m1, c1)), Some((m2, c2))) => {
-                if (m1 - m2).abs() < 0.0000000001 {
-                    // lines are parallel
-                    return None;
-                }
+    fn velocity(&self) -> [Rational; 3] {
+        self.velocity.map(Rational::from)
+    }
 
-                let x = (c2 - c1) / (m1 - m2);
-                let y = m1 * x + c1;
+    /// Where the paths of the two stones cross in x and y, if both get there
+    /// in the future. Parallel paths never cross, even when they coincide.
+    fn will_collide_xy(&self, other: &Self) -> Option<[Rational; 2]> {
+        let (p1, v1) = (self.position(), self.velocity());
+        let (p2, v2) = (other.position(), other.velocity());
 
-                let self_future =
-                    (x - self.position[0] as f64).signum() as isize == self.velocity[0].signum();
-                let other_future =
-                    (x - other.position[0] as f64).signum() as isize == other.velocity[0].signum();
+        // p1 + v1 t = p2 + v2 s, on x and y
+        let [t, s] = solve2(
+            [[v1[0], -v2[0]], [v1[1], -v2[1]]],
+            [p2[0] - p1[0], p2[1] - p1[1]],
+        )?;
 
-                if self_future & other_future {
-                    Some([x, y])
-                } else {
-                    None
-                }
-            }
-            // self is the only vertical line
-            (None, Some((m2, c2))) => {
-                let x = self.position[0] as f64;
-                let y = x * m2 + c2;
-
-                let self_future =
-                    (y - self.position[1] as f64).signum() as isize == self.velocity[1].signum();
-                let other_future =
-                    (x - other.position[0] as f64).signum() as isize == other.velocity[0].signum();
-
-                if self_future & other_future {
-                    Some([x, y])
-                } else {
-                    None
-                }
-            }
-            // other is the only vertical line
-            (Some((m1, c1)), None) => {
-                let x = other.position[0] as f64;
-                let y = x * m1 + c1;
-
-                let self_future =
-                    (x - self.position[0] as f64).signum() as isize == self.velocity[0].signum();
-                let other_future =
-                    (y - other.position[1] as f64).signum() as isize == other.velocity[1].signum();
-
-                if self_future & other_future {
-                    Some([x, y])
-                } else {
-                    None
-                }
-            }
-            // both vertical lines
-            (None, None) => None,
-        }
+        ((t > Rational::ZERO) & (s > Rational::ZERO))
+            .then(|| [p1[0] + v1[0] * t, p1[1] + v1[1] * t])
     }
 }
 
-fn wedge2(vec1: &[f64], vec2: &[f64]) -> Vec<f64> {
-    vec![
-        vec1[0] * vec2[1] - vec1[1] * vec2[0],
-        vec1[1] * vec2[2] - vec1[2] * vec2[1],
-        vec1[2] * vec2[0] - vec1[0] * vec2[2],
-    ]
+fn sub(a: [Rational; 3], b: [Rational; 3]) -> [Rational; 3] {
+    std::array::from_fn(|axis| a[axis] - b[axis])
 }
 
-fn to_float(arr: &[isize; 3]) -> Vec<f64> {
-    arr.iter().map(|v| *v as f64).collect()
+/// In the frame where one stone sits still at the origin the rock's path runs
+/// through the origin, so it lies in the plane holding the origin and the path
+/// of `(q, w)`. The rock hits `(p, v)` where that stone crosses the plane.
+fn hit_time(
+    (p, v): ([Rational; 3], [Rational; 3]),
+    (q, w): ([Rational; 3], [Rational; 3]),
+) -> Option<Rational> {
+    let towards = det3([q, w, v]);
+    (!towards.is_zero()).then(|| -det3([q, w, p]) / towards)
 }
 
-fn determinant<I: AsRef<[f64]>>(vec1: I, vec2: I, vec3: I) -> f64 {
-    let vec1 = vec1.as_ref();
-    let vec2 = vec2.as_ref();
-    let vec3 = vec3.as_ref();
-
-    vec1[0] * vec2[1] * vec3[2] + vec1[1] * vec2[2] * vec3[0] + vec1[2] * vec2[0] * vec3[1]
-        - vec1[0] * vec2[2] * vec3[1]
-        - vec1[1] * vec2[0] * vec3[2]
-        - vec1[2] * vec2[1] * vec3[0]
-}
+#[instrument(name = "part2", skip_all)]
+fn find_collider(stones: &[Hail]) -> Result<[i128; 3], SolveError> {
+    let unsolvable = |message: &str| SolveError::Unsolvable {
+        day: DAY,
+        message: message.to_string(),
+    };
+    let first = stones
+        .first()
+        .ok_or_else(|| unsolvable("there are no hailstones to hit"))?;
+    let (origin, still) = (first.position(), first.velocity());
+    let relative = |hail: &Hail| (sub(hail.position(), origin), sub(hail.velocity(), still));
+
+    for (idx, a) in stones.iter().enumerate().skip(1) {
+        for b in stones.iter().skip(idx + 1) {
+            let (Some(ta), Some(tb)) = (
+                hit_time(relative(a), relative(b)),
+                hit_time(relative(b), relative(a)),
+            ) else {
+                continue;
+            };
+            if ta == tb {
+                continue;
+            }
 
-fn subtract(vec1: &[isize; 3], vec2: &[isize; 3]) -> Vec<f64> {
-    vec1.iter()
-        .zip(vec2.iter())
-        .map(|(v1, v2)| (*v1 - *v2) as f64)
-        .collect()
-}
+            let at = |hail: &Hail, t: Rational| -> [Rational; 3] {
+                std::array::from_fn(|axis| hail.position()[axis] + hail.velocity()[axis] * t)
+            };
+            let (hit_a, hit_b) = (at(a, ta), at(b, tb));
+            let velocity: [Rational; 3] =
+                std::array::from_fn(|axis| (hit_b[axis] - hit_a[axis]) / (tb - ta));
+            let mut start = [0; 3];
+            for (axis, coord) in start.iter_mut().enumerate() {
+                *coord = (hit_a[axis] - velocity[axis] * ta)
+                    .to_integer()
+                    .ok_or_else(|| unsolvable("the rock would start between whole positions"))?;
+            }
+            return Ok(start);
+        }
+    }
 
-// with credit to https://github.com/apprenticewiz/adventofcode/blob/main/2023/rust/day24b/src/main.rs
-// and https://openstax.org/books/college-algebra-2e/pages/7-8-solving-systems-with-cramers-rule
-fn find_collider(stones: &[Hail]) -> isize {
-    let row1 = wedge2(
-        &subtract(&stones[0].velocity, &stones[1].velocity),
-        &subtract(&stones[0].position, &stones[1].position),
-    );
-
-    let row2 = wedge2(
-        &subtract(&stones[0].velocity, &stones[2].velocity),
-        &subtract(&stones[0].position, &stones[2].position),
-    );
-
-    let row3 = wedge2(
-        &subtract(&stones[1].velocity, &stones[2].velocity),
-        &subtract(&stones[1].position, &stones[2].position),
-    );
-
-    let lhs: Vec<Vec<f64>> = vec![
-        vec![row1[0], row2[0], row3[0]],
-        vec![row1[1], row2[1], row3[1]],
-        vec![row1[2], row2[2], row3[2]],
-    ];
-
-    let rhs: Vec<f64> = vec![
-        -determinant(
-            &to_float(&stones[0].position),
-            &to_float(&stones[0].velocity),
-            &to_float(&stones[1].position),
-        ) - determinant(
-            &to_float(&stones[1].position),
-            &to_float(&stones[1].velocity),
-            &to_float(&stones[0].position),
-        ),
-        -determinant(
-            &to_float(&stones[0].position),
-            &to_float(&stones[0].velocity),
-            &to_float(&stones[2].position),
-        ) - determinant(
-            &to_float(&stones[2].position),
-            &to_float(&stones[2].velocity),
-            &to_float(&stones[0].position),
-        ),
-        -determinant(
-            &to_float(&stones[1].position),
-            &to_float(&stones[1].velocity),
-            &to_float(&stones[2].position),
-        ) - determinant(
-            &to_float(&stones[2].position),
-            &to_float(&stones[2].velocity),
-            &to_float(&stones[1].position),
-        ),
-    ];
-
-    let lhs_det = determinant(&lhs[0], &lhs[1], &lhs[2]);
-    let x_det = determinant(&rhs, &lhs[1], &lhs[2]);
-    let y_det = determinant(&lhs[0], &rhs, &lhs[2]);
-    let z_det = determinant(&lhs[0], &lhs[1], &rhs);
-
-    let x = (x_det / lhs_det).round() as isize;
-    let y = (y_det / lhs_det).round() as isize;
-    let z = (z_det / lhs_det).round() as isize;
-
-    x + y + z
+    Err(unsolvable("no pair of hailstones pins down the rock"))
 }
 
-pub async fn solve(mut rx: Receiver<String>) {
+pub async fn parse(mut rx: Receiver<String>) -> Result<Vec<Hail>, SolveError> {
     let mut tasks = Vec::new();
+    let mut line_no = 0;
     while let Some(line) = rx.recv().await {
-        if line.is_empty() {
-            continue;
+        if !line.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                Hail::new(&InputLine::new(DAY, line_no, &line)).await
+            }));
         }
-        let task = tokio::spawn(async move { Hail::new(&line).await });
-        tasks.push(task);
+        line_no += 1;
     }
 
     let mut stones = Vec::new();
-    for task in tasks {
-        if let Ok(hailstone) = task.await {
-            stones.push(hailstone);
-        }
+    for result in join_tasks(tasks).await? {
+        stones.push(result?);
     }
+    Ok(stones)
+}
 
-    let test_area = 200000000000000f64..=400000000000000f64;
+pub fn part1(stones: &[Hail]) -> usize {
+    let test_area = Rational::from(200000000000000i64)..=Rational::from(400000000000000i64);
     let mut part1 = 0usize;
     for (idx1, stone1) in stones.iter().enumerate() {
         for (idx2, stone2) in stones.iter().enumerate() {
@@ -221,8 +151,75 @@ pub async fn solve(mut rx: Receiver<String>) {
             }
         }
     }
+    part1
+}
+
+pub fn part2(stones: &[Hail]) -> Result<i128, SolveError> {
+    Ok(find_collider(stones)?.into_iter().sum())
+}
+
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let stones = parse(rx).await?;
+
+    Ok(Answer::from_parts(
+        parts
+            .part1()
+            .then(|| info_span!("part1").in_scope(|| part1(&stones))),
+        parts.part2().then(|| part2(&stones)).transpose()?,
+    ))
+}
+
+pub struct Day24;
+
+impl Solver for Day24 {
+    fn day(&self) -> &'static str {
+        DAY
+    }
+
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn stones(input: &str) -> Vec<Hail> {
+        let (tx, rx) = tokio::sync::mpsc::channel(input.lines().count().max(1));
+        for line in input.lines() {
+            tx.send(line.to_string()).await.unwrap();
+        }
+        drop(tx);
+        parse(rx).await.unwrap()
+    }
 
-    let part2 = find_collider(&stones);
+    #[tokio::test]
+    async fn example() {
+        let stones = stones(
+            "19, 13, 30 @ -2, 1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @ 1, -5, -3",
+        )
+        .await;
+        assert_eq!(find_collider(&stones), Ok([24, 13, 10]));
+        assert_eq!(part2(&stones), Ok(47));
+    }
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+    #[tokio::test]
+    async fn too_few_stones() {
+        let unsolvable = |message: &str| {
+            Err(SolveError::Unsolvable {
+                day: DAY,
+                message: message.to_string(),
+            })
+        };
+        assert_eq!(part2(&[]), unsolvable("there are no hailstones to hit"));
+        assert_eq!(
+            part2(&stones("19, 13, 30 @ -2, 1, -2").await),
+            unsolvable("no pair of hailstones pins down the rock")
+        );
+    }
 }
@@ -0,0 +1,79 @@
+use crate::Day25;
+use common::{GenSize, Generator};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// Two well connected clusters of `lines` components each, joined by exactly
+/// three wires. `width` is the number of extra wires per component.
+impl Generator for Day25 {
+    fn generate(&self, rng: &mut StdRng, size: &GenSize) -> String {
+        let per_cluster = size.lines_or(750).max(5);
+        let extra = size.width_or(3).max(1);
+
+        let mut names: Vec<String> = (0..26 * 26 * 26)
+            .map(|idx: u32| {
+                [idx / 676, (idx / 26) % 26, idx % 26]
+                    .iter()
+                    .map(|&c| (b'a' + c as u8) as char)
+                    .collect()
+            })
+            .collect();
+        assert!(2 * per_cluster <= names.len(), "too many components");
+        names.shuffle(rng);
+        names.truncate(2 * per_cluster);
+
+        let mut wires = HashSet::new();
+        for cluster in 0..2 {
+            let offset = cluster * per_cluster;
+            for node in 0..per_cluster {
+                // wiring each component to the next two round a ring takes
+                // four wires to cut anywhere, so the three between the
+                // clusters are the only cut that small
+                for step in 1..=2 {
+                    let next = (node + step) % per_cluster;
+                    wires.insert((offset + node.min(next), offset + node.max(next)));
+                }
+                for _ in 0..extra {
+                    let other = rng.gen_range(0..per_cluster);
+                    if other != node {
+                        wires.insert((offset + node.min(other), offset + node.max(other)));
+                    }
+                }
+            }
+        }
+
+        let mut left: Vec<usize> = (0..per_cluster).collect();
+        let mut right: Vec<usize> = (per_cluster..2 * per_cluster).collect();
+        left.shuffle(rng);
+        right.shuffle(rng);
+        for (&a, &b) in left.iter().zip(right.iter()).take(3) {
+            wires.insert((a, b));
+        }
+
+        // list each wire once, under either of its components
+        let mut listed: Vec<Vec<usize>> = vec![Vec::new(); 2 * per_cluster];
+        let mut wires: Vec<_> = wires.into_iter().collect();
+        wires.sort_unstable();
+        for (a, b) in wires {
+            if rng.gen_bool(0.5) {
+                listed[a].push(b);
+            } else {
+                listed[b].push(a);
+            }
+        }
+
+        let mut lines: Vec<String> = listed
+            .iter()
+            .enumerate()
+            .filter(|(_, to)| !to.is_empty())
+            .map(|(node, to)| {
+                let to: Vec<&str> = to.iter().map(|&other| names[other].as_str()).collect();
+                format!("{}: {}", names[node], to.join(" "))
+            })
+            .collect();
+        lines.shuffle(rng);
+        lines.join("\n") + "\n"
+    }
+}
@@ -1,178 +1,228 @@
-use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use rand::SeedableRng;
-use std::collections::{HashMap, HashSet, VecDeque};
+mod generate;
+
+use common::{
+    join_tasks, spawn_line, Answer, InputLine, InputStream, ParseError, Parts, SolveError,
+    SolveFuture, Solver,
+};
+use graph::{Cut, Graph, Labels, NodeId};
 use tokio::sync::mpsc::Receiver;
+use tracing::{debug, instrument};
 
-const NODESAMPLE: usize = 35;
-const BRIDGES: usize = 3;
+const DAY: &str = "25";
 
-type Graph = HashMap<String, Vec<String>>;
+/// How many wires have to be disconnected to split the components in two
+const CUT_WIRES: u32 = 3;
 
-async fn parse_line(line: &str) -> (String, Vec<String>) {
-    let (node, edges) = line.split_once(": ").unwrap();
-    (
+async fn parse_line(line_no: usize, line: &str) -> Result<(String, Vec<String>), ParseError> {
+    let input = InputLine::new(DAY, line_no, line);
+    let (node, edges) = input.split_once(line, ": ")?;
+    Ok((
         node.to_string(),
         edges.split(' ').map(|s| s.to_string()).collect(),
-    )
+    ))
 }
 
-fn both_ways(graph: &Graph) -> Graph {
-    let mut undirected_graph = graph.clone();
-
-    for (node, edges) in graph.iter() {
-        for edge in edges.iter() {
-            let new_node = undirected_graph.entry(edge.clone()).or_default();
-            new_node.push(node.clone());
-        }
-    }
-
-    undirected_graph
+/// The components, with every connection recorded on both ends
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wiring {
+    labels: Labels<String>,
+    graph: Graph,
 }
 
-fn find_shortest_path(graph: &Graph, start: &str, end: &str) -> Option<Vec<String>> {
-    if start == end {
-        return Some(vec![start.to_string()]);
+pub async fn parse(mut rx: Receiver<String>) -> Result<Wiring, SolveError> {
+    let mut tasks = Vec::new();
+    let mut line_no = 0;
+    while let Some(line) = rx.recv().await {
+        if !line.is_empty() {
+            tasks.push(spawn_line(line_no, async move {
+                parse_line(line_no, &line).await
+            }));
+        }
+        line_no += 1;
     }
 
-    let mut queue = VecDeque::new();
-    queue.push_back(vec![start.to_string()]);
-
-    while let Some(history) = queue.pop_front() {
-        let current = history.last().unwrap();
-        if let Some(neighbors) = graph.get(current) {
-            for neighbor in neighbors {
-                if !history.contains(neighbor) {
-                    let mut new_hist = history.clone();
-                    new_hist.push(neighbor.clone());
-
-                    if neighbor == end {
-                        return Some(new_hist);
-                    }
-                    queue.push_back(new_hist);
-                }
+    let mut labels = Labels::new();
+    let mut graph = Graph::new();
+    for result in join_tasks(tasks).await? {
+        let (node, edges) = result?;
+        let node = labels.intern(node);
+        for edge in edges {
+            let edge = labels.intern(edge);
+            while graph.len() < labels.len() {
+                graph.add_node();
             }
+            graph.add_undirected_edge(node, edge, ());
         }
     }
 
-    None
+    Ok(Wiring { labels, graph })
 }
 
-fn find_bridges(graph: &Graph, n_bridges: usize) -> Vec<(String, String)> {
-    let seed = [42; 32];
-    let mut rng = StdRng::from_seed(seed);
+/// A copy of the wiring for `min_cut`, each wire weighted by `weight`
+fn weighted(graph: &Graph, weight: impl Fn(NodeId, NodeId) -> u32) -> Graph<u32> {
+    let mut weighted = Graph::with_nodes(graph.len());
+    for node in graph.nodes() {
+        for &(next, ()) in graph.edges(node) {
+            weighted.add_edge(node, next, weight(node, next));
+        }
+    }
+    weighted
+}
 
-    let mut nodes: Vec<_> = graph.keys().cloned().collect();
-    nodes = nodes
-        .choose_multiple(&mut rng, NODESAMPLE)
-        .cloned()
+/// The wires with one end on `side`, each once with the lower id first
+fn crossing(graph: &Graph, side: &[NodeId]) -> Vec<(NodeId, NodeId)> {
+    let mut on_side = vec![false; graph.len()];
+    for node in side {
+        on_side[node.index()] = true;
+    }
+    let mut wires: Vec<_> = side
+        .iter()
+        .flat_map(|&node| graph.neighbours(node).map(move |next| (node, next)))
+        .filter(|(_, next)| !on_side[next.index()])
+        .map(|(a, b)| (a.min(b), a.max(b)))
         .collect();
+    wires.sort_unstable();
+    wires.dedup();
+    wires
+}
 
-    let mut shortest_paths = Vec::new();
+/// The one way to split the components in two by disconnecting `CUT_WIRES`
+/// wires, by Stoer-Wagner. Fails when the smallest cut is any other size, or
+/// when there's more than one cut that small.
+#[instrument(skip(wiring), fields(components = wiring.graph.len()))]
+pub fn find_cut(wiring: &Wiring) -> Result<Cut<u32>, SolveError> {
+    let unsolvable = |message: String| SolveError::Unsolvable { day: DAY, message };
+    let name =
+        |(a, b): (NodeId, NodeId)| format!("{}/{}", wiring.labels.key(a), wiring.labels.key(b));
+    let names = |wires: &[(NodeId, NodeId)]| {
+        wires
+            .iter()
+            .map(|&wire| name(wire))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let cut = weighted(&wiring.graph, |_, _| 1)
+        .min_cut()
+        .ok_or_else(|| unsolvable("there are fewer than two components".to_string()))?;
+    let wires = crossing(&wiring.graph, &cut.side);
+    if cut.weight != CUT_WIRES {
+        return Err(unsolvable(format!(
+            "the smallest cut is {} wires, not {CUT_WIRES}: {}",
+            cut.weight,
+            names(&wires)
+        )));
+    }
 
-    for (idx1, node1) in nodes.iter().enumerate() {
-        for (idx2, node2) in nodes.iter().enumerate() {
-            if idx1 >= idx2 {
-                continue;
-            }
-            if let Some(path) = find_shortest_path(graph, node1, node2) {
-                shortest_paths.push(path);
-            }
+    // any other cut as small leaves out one of these wires, so with each made
+    // too heavy to cut in turn there must be nothing left that small
+    for &wire in wires.iter() {
+        let other = weighted(&wiring.graph, |a, b| match (a.min(b), a.max(b)) == wire {
+            true => CUT_WIRES + 1,
+            false => 1,
+        })
+        .min_cut()
+        .expect("there are at least two components");
+        if other.weight == CUT_WIRES {
+            return Err(unsolvable(format!(
+                "there's more than one cut of {CUT_WIRES} wires: {} and {}",
+                names(&wires),
+                names(&crossing(&wiring.graph, &other.side))
+            )));
         }
     }
 
-    let mut edges = HashMap::new();
-    for path in shortest_paths.into_iter() {
-        for edge in path.windows(2) {
-            let mut edge = edge.to_vec();
-            edge.sort_unstable();
-            let counter = edges.entry(edge).or_insert(0);
-            *counter += 1;
-        }
+    for &wire in wires.iter() {
+        debug!(wire = name(wire), "cutting");
     }
+    Ok(cut)
+}
 
-    let mut edges: Vec<_> = edges.into_iter().collect();
-    edges.sort_by(|(_edge, count), (_edge2, count2)| count.cmp(count2));
+pub fn part1(wiring: &Wiring) -> Result<usize, SolveError> {
+    let cut = find_cut(wiring)?;
+    Ok(cut.side.len() * (wiring.graph.len() - cut.side.len()))
+}
 
-    edges
-        .into_iter()
-        .rev()
-        .take(n_bridges)
-        .map(|(edge, _count)| (edge[0].clone(), edge[1].clone()))
-        .collect()
+pub async fn solve(rx: Receiver<String>, parts: Parts) -> Result<Answer, SolveError> {
+    let wiring = parse(rx).await?;
+    Ok(Answer::from_parts(
+        parts.part1().then(|| part1(&wiring)).transpose()?,
+        None::<usize>,
+    ))
 }
 
-fn remove_edges(graph: &Graph, to_remove: &[(String, String)]) -> Graph {
-    let mut graph = graph.clone();
+pub struct Day25;
 
-    for (node, edges) in graph.iter_mut() {
-        for (node1, node2) in to_remove.iter() {
-            if node == node1 {
-                edges.retain(|e| e != node2);
-            } else if node == node2 {
-                edges.retain(|e| e != node1);
-            }
-        }
+impl Solver for Day25 {
+    fn day(&self) -> &'static str {
+        DAY
     }
 
-    graph
+    fn solve(&self, input: InputStream, parts: Parts) -> SolveFuture {
+        Box::pin(solve(input.into_lines(), parts))
+    }
 }
 
-fn find_networks(graph: &Graph) -> Vec<Vec<String>> {
-    let mut networks = Vec::new();
-    let mut visited = HashSet::new();
-
-    for node in graph.keys() {
-        if !visited.contains(node) {
-            let mut network = Vec::new();
-            let mut stack = VecDeque::new();
-            stack.push_back(node);
-
-            while let Some(current) = stack.pop_back() {
-                if visited.insert(current.clone()) {
-                    network.push(current.clone());
-                    if let Some(neighbors) = graph.get(current) {
-                        for neighbor in neighbors {
-                            if !visited.contains(neighbor) {
-                                stack.push_back(neighbor);
-                            }
-                        }
-                    }
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            networks.push(network);
+    async fn wiring(input: &str) -> Wiring {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        for line in input.lines() {
+            tx.send(line.to_string()).await.unwrap();
         }
+        drop(tx);
+        parse(rx).await.unwrap()
     }
 
-    networks
-}
-
-pub async fn solve(mut rx: Receiver<String>) {
-    let mut tasks = Vec::new();
-    while let Some(line) = rx.recv().await {
-        if line.is_empty() {
-            continue;
-        }
-        let task = tokio::spawn(async move { parse_line(&line).await });
-        tasks.push(task);
+    #[tokio::test]
+    async fn example() {
+        let wiring = wiring(
+            "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr",
+        )
+        .await;
+
+        let cut = find_cut(&wiring).unwrap();
+        let mut wires: Vec<_> = crossing(&wiring.graph, &cut.side)
+            .into_iter()
+            .map(|(a, b)| {
+                let mut ends = [wiring.labels.key(a).as_str(), wiring.labels.key(b)];
+                ends.sort_unstable();
+                ends.join("/")
+            })
+            .collect();
+        wires.sort_unstable();
+        assert_eq!(wires, ["bvb/cmg", "hfx/pzl", "jqt/nvd"]);
+        assert_eq!(part1(&wiring), Ok(54));
     }
 
-    let mut graph = HashMap::new();
-    for task in tasks {
-        if let Ok(node) = task.await {
-            graph.insert(node.0, node.1);
-        }
+    #[tokio::test]
+    async fn wrong_or_ambiguous_cut() {
+        // a ring splits with two wires anywhere
+        let ring = wiring("a: b\nb: c\nc: d\nd: a").await;
+        assert!(matches!(
+            part1(&ring),
+            Err(SolveError::Unsolvable { message, .. }) if message.starts_with("the smallest cut is 2 wires")
+        ));
+
+        // every component of a square with both diagonals has three wires
+        let square = wiring("a: b c d\nb: c d\nc: d").await;
+        assert!(matches!(
+            part1(&square),
+            Err(SolveError::Unsolvable { message, .. }) if message.starts_with("there's more than one cut of 3 wires")
+        ));
     }
-
-    graph = both_ways(&graph);
-    let remove_bridges = find_bridges(&graph, BRIDGES);
-    graph = remove_edges(&graph, &remove_bridges);
-
-    let part1 = find_networks(&graph)
-        .into_iter()
-        .map(|n| n.len())
-        .collect::<Vec<usize>>();
-
-    dbg!(part1);
 }
@@ -0,0 +1,188 @@
+use crate::cli::Variant;
+use crate::peak_alloc;
+use crate::runner::{self, RunOptions};
+use common::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+
+/// Timings for one day on one input, averaged over the iterations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchEntry {
+    pub day: String,
+    pub variant: Variant,
+    pub iterations: usize,
+    pub lines: usize,
+    /// Reading the file and splitting it into the solver's units. Lines are
+    /// parsed on the solver's own tasks, so that part counts as solving.
+    pub parse_ms: f64,
+    /// Streaming the units through `solve` until both answers are in
+    pub solve_ms: f64,
+    pub solve_min_ms: f64,
+    /// Most heap in use during an iteration, above what was live before it
+    pub peak_bytes: usize,
+    pub lines_per_sec: f64,
+}
+
+/// Run the day `iterations` times on the variant's input. `None` when the
+/// day has no input file for that variant.
+pub async fn bench_day(
+    puzzle: &dyn Puzzle,
+    variant: Variant,
+    iterations: usize,
+    options: &RunOptions,
+) -> Result<Option<BenchEntry>, String> {
+    let path = variant.path(puzzle.day());
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let label = path.display().to_string();
+    let mut lines = 0;
+    let mut parse_ms = Vec::with_capacity(iterations);
+    let mut solve_ms = Vec::with_capacity(iterations);
+    let mut peak_bytes = 0;
+
+    for _ in 0..iterations.max(1) {
+        let baseline = peak_alloc::reset();
+
+        let start = Instant::now();
+        let raw = read(&path)?;
+        let units = puzzle.split_input(&raw);
+        parse_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+        lines = raw.lines().count();
+
+        let report = runner::run_units(puzzle, &label, units, options).await;
+        if let Some(error) = report.error {
+            return Err(error);
+        }
+        solve_ms.push(report.elapsed_ms);
+        peak_bytes = peak_bytes.max(peak_alloc::peak().saturating_sub(baseline));
+    }
+
+    let parse_ms = mean(&parse_ms);
+    let solve_min_ms = solve_ms.iter().copied().fold(f64::INFINITY, f64::min);
+    let solve_ms = mean(&solve_ms);
+    Ok(Some(BenchEntry {
+        day: puzzle.day().to_string(),
+        variant,
+        iterations: iterations.max(1),
+        lines,
+        parse_ms,
+        solve_ms,
+        solve_min_ms,
+        peak_bytes,
+        lines_per_sec: lines as f64 / ((parse_ms + solve_ms) / 1000.0),
+    }))
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {e}", path.display()))
+}
+
+fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len().max(1) as f64
+}
+
+pub fn load(path: &Path) -> Result<Vec<BenchEntry>, String> {
+    serde_json::from_str(&read(path)?).map_err(|e| format!("can't parse {}: {e}", path.display()))
+}
+
+pub fn save(entries: &[BenchEntry], path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entries).unwrap() + "\n";
+    std::fs::write(path, json).map_err(|e| format!("can't write {}: {e}", path.display()))
+}
+
+/// Relative change in solve time against the same day and variant in `baseline`
+fn change(entry: &BenchEntry, baseline: &[BenchEntry]) -> Option<f64> {
+    baseline
+        .iter()
+        .find(|b| b.day == entry.day && b.variant == entry.variant)
+        .filter(|b| b.solve_ms > 0.0)
+        .map(|b| (entry.solve_ms - b.solve_ms) / b.solve_ms * 100.0)
+}
+
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+pub fn print_table(entries: &[BenchEntry], baseline: Option<&[BenchEntry]>) {
+    println!(
+        "{:<4} {:<6} {:>8} {:>10} {:>12} {:>12} {:>10} {:>12} {:>8}",
+        "Day", "Input", "Lines", "Parse", "Solve", "Min", "Peak", "Lines/s", "Change"
+    );
+    for entry in entries {
+        let change = baseline
+            .and_then(|baseline| change(entry, baseline))
+            .map(|pct| format!("{pct:+.1}%"))
+            .unwrap_or_default();
+        println!(
+            "{:<4} {:<6} {:>8} {:>8.2}ms {:>10.2}ms {:>10.2}ms {:>8.1}MB {:>12.0} {:>8}",
+            entry.day,
+            entry.variant,
+            entry.lines,
+            entry.parse_ms,
+            entry.solve_ms,
+            entry.solve_min_ms,
+            megabytes(entry.peak_bytes),
+            entry.lines_per_sec,
+            change
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    fn entry(day: &str, variant: Variant, solve_ms: f64) -> BenchEntry {
+        BenchEntry {
+            day: day.to_string(),
+            variant,
+            iterations: 1,
+            lines: 10,
+            parse_ms: 0.0,
+            solve_ms,
+            solve_min_ms: solve_ms,
+            peak_bytes: 0,
+            lines_per_sec: 0.0,
+        }
+    }
+
+    #[test]
+    fn change_matches_day_and_variant() {
+        let baseline = vec![
+            entry("07", Variant::Real, 10.0),
+            entry("07", Variant::Large, 100.0),
+        ];
+        assert_eq!(
+            change(&entry("07", Variant::Large, 150.0), &baseline),
+            Some(50.0)
+        );
+        assert_eq!(
+            change(&entry("07", Variant::Real, 5.0), &baseline),
+            Some(-50.0)
+        );
+        assert_eq!(change(&entry("08", Variant::Real, 5.0), &baseline), None);
+    }
+
+    #[tokio::test]
+    async fn real_input_is_timed() {
+        let day01 = registry::find("01").unwrap();
+        let entry = bench_day(day01.as_ref(), Variant::Real, 2, &RunOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.iterations, 2);
+        assert_eq!(entry.lines, 1000);
+        assert!(entry.solve_min_ms <= entry.solve_ms);
+
+        let json = serde_json::to_string(std::slice::from_ref(&entry)).unwrap();
+        assert!(json.contains(r#""variant":"real""#));
+        assert_eq!(
+            serde_json::from_str::<Vec<BenchEntry>>(&json).unwrap(),
+            vec![entry]
+        );
+    }
+}
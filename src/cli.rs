@@ -0,0 +1,402 @@
+use crate::input_simulator::Profile;
+use crate::input_source::Source;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use common::OnPanic;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[command(about = "Advent of Code 2023 solutions, fed by simulated user input")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Emit tracing spans and events, filtered by RUST_LOG if set
+    #[arg(long, global = true, value_enum)]
+    pub trace: Option<Trace>,
+
+    /// Write the trace here rather than stderr (defaults to trace.json for chrome)
+    #[arg(long, global = true, requires = "trace")]
+    pub trace_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the solver for one day, or for every day with --all
+    Run(RunArgs),
+    /// Write a generated input for one day, or for every day with --all
+    Generate(GenerateArgs),
+    /// Compare answers against the recorded ones in answers.json
+    Check(CheckArgs),
+    /// Time each day on its inputs, optionally against an earlier run
+    Bench(BenchArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Day to run, e.g. 7 or 07
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub day: Option<String>,
+
+    /// Run every day and print a summary
+    #[arg(long)]
+    pub all: bool,
+
+    /// Read the puzzle input from this file instead of the day's directory
+    #[arg(long, conflicts_with = "all")]
+    pub input: Option<PathBuf>,
+
+    /// Stream live input from stdin (or -), tail:PATH, tcp:HOST:PORT or unix:PATH
+    #[arg(long, conflicts_with_all = ["all", "input"])]
+    pub source: Option<Source>,
+
+    /// How long a tailed file may sit unchanged before its input counts as finished
+    #[arg(long, default_value_t = 1000, requires = "source")]
+    pub idle_ms: u64,
+
+    /// Which of the day's input files to use
+    #[arg(long, value_enum, default_value_t = Variant::Real)]
+    pub variant: Variant,
+
+    /// Only run this part
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// What to do when the task for an input line panics
+    #[arg(long, value_enum, default_value_t = PanicPolicy::Abort)]
+    pub on_panic: PanicPolicy,
+
+    /// Change one of the day's knobs, e.g. --set steps=6 for day 21 (repeatable)
+    #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_knob, conflicts_with = "all")]
+    pub knobs: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub simulation: SimulationArgs,
+}
+
+/// How the input is streamed to the solver, by default as fast as it can go
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Input simulation")]
+pub struct SimulationArgs {
+    /// Average lines per second
+    #[arg(long, value_parser = parse_rate)]
+    pub rate: Option<f64>,
+
+    /// Send lines in bursts of this many, keeping the average rate
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub chunk: u64,
+
+    /// Random extra delay of up to this many milliseconds before each burst
+    #[arg(long, default_value_t = 0)]
+    pub jitter_ms: u64,
+
+    /// Channel capacity, a small value forces the sender to wait for the solver
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub capacity: Option<u64>,
+
+    /// Shuffle lines within windows of this size (days that take numbered lines only)
+    #[arg(long, value_name = "WINDOW", value_parser = clap::value_parser!(u64).range(1..))]
+    pub out_of_order: Option<u64>,
+
+    /// Seed for the jitter and shuffling
+    #[arg(long, default_value_t = 0)]
+    pub sim_seed: u64,
+}
+
+impl SimulationArgs {
+    pub fn profile(&self) -> Profile {
+        let default = Profile::default();
+        Profile {
+            rate: self.rate,
+            chunk: self.chunk as usize,
+            jitter: Duration::from_millis(self.jitter_ms),
+            capacity: self.capacity.map_or(default.capacity, |c| c as usize),
+            out_of_order: self.out_of_order.map(|w| w as usize),
+            seed: self.sim_seed,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// Day to generate for, e.g. 7 or 07
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub day: Option<String>,
+
+    /// Generate for every day
+    #[arg(long)]
+    pub all: bool,
+
+    /// Seed for the random generator, a random seed is printed if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Number of lines (or records, hands, bricks...) to generate
+    #[arg(long)]
+    pub lines: Option<usize>,
+
+    /// Line width (or the day's secondary size knob)
+    #[arg(long)]
+    pub width: Option<usize>,
+
+    /// Write here instead of the day's dayNN_big_input.txt
+    #[arg(long, conflicts_with = "all")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// Day to check, e.g. 7 or 07
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub day: Option<String>,
+
+    /// Check every day
+    #[arg(long)]
+    pub all: bool,
+
+    /// Re-record the answers instead of comparing them
+    #[arg(long)]
+    pub update: bool,
+
+    /// Pass even when inputs are missing or have no recorded answers
+    #[arg(long)]
+    pub allow_missing: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Day to benchmark, e.g. 7 or 07
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub day: Option<String>,
+
+    /// Benchmark every day
+    #[arg(long)]
+    pub all: bool,
+
+    /// Inputs to time, those without a file are skipped
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Variant::Test, Variant::Real, Variant::Large])]
+    pub variant: Vec<Variant>,
+
+    /// Runs per input, times are averaged over them
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    pub iterations: u64,
+
+    /// Save the results as JSON, to pass to --baseline later
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Show the change in solve time against results saved by an earlier run
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    /// The puzzle's example input, input_test.txt
+    Test,
+    /// A second example, input_test2.txt, for days whose parts have their own
+    Test2,
+    /// A generated stress input, dayNN_big_input.txt
+    Large,
+    /// The real puzzle input, input.txt
+    Real,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Variant {
+    pub fn path(&self, day: &str) -> PathBuf {
+        let file = match self {
+            Variant::Test => "input_test.txt".to_string(),
+            Variant::Test2 => "input_test2.txt".to_string(),
+            Variant::Large => format!("day{day}_big_input.txt"),
+            Variant::Real => "input.txt".to_string(),
+        };
+        PathBuf::from(format!("problems/day{day}")).join(file)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trace {
+    /// Human readable spans and events on stderr, info level by default
+    Pretty,
+    /// One JSON object per event, info level by default
+    Json,
+    /// A Chrome trace (chrome://tracing or Perfetto), debug level by default
+    Chrome,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Fail the day, listing the lines that panicked
+    Abort,
+    /// Answer from the remaining lines and report the ones that panicked
+    Continue,
+}
+
+impl From<PanicPolicy> for OnPanic {
+    fn from(policy: PanicPolicy) -> Self {
+        match policy {
+            PanicPolicy::Abort => OnPanic::Abort,
+            PanicPolicy::Continue => OnPanic::Continue,
+        }
+    }
+}
+
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!(
+            "'{rate}' is not a positive number of lines per second"
+        )),
+    }
+}
+
+fn parse_knob(knob: &str) -> Result<(String, String), String> {
+    match knob.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("'{knob}' is not NAME=VALUE")),
+    }
+}
+
+/// Accept "7", "07" or "day07" and normalise to the two digit form
+pub fn parse_day(day: &str) -> Result<String, String> {
+    let digits = day.trim().trim_start_matches("day");
+    match digits.parse::<u8>() {
+        Ok(num @ 1..=25) => Ok(format!("{num:02}")),
+        _ => Err(format!("'{day}' is not a day between 1 and 25")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_names() {
+        assert_eq!(parse_day("7"), Ok("07".to_string()));
+        assert_eq!(parse_day("day21"), Ok("21".to_string()));
+        assert!(parse_day("26").is_err());
+        assert!(parse_day("seven").is_err());
+    }
+
+    #[test]
+    fn run_all_conflicts_with_day() {
+        assert!(Cli::try_parse_from(["aoc", "run", "--all"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "run", "05", "--all"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "05", "--part", "3"]).is_err());
+        assert!(
+            Cli::try_parse_from(["aoc", "run", "05", "--source", "tcp:127.0.0.1:7000"]).is_ok()
+        );
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--source", "-"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "05", "--on-panic", "continue"]).is_ok());
+    }
+
+    #[test]
+    fn knobs() {
+        let cli = Cli::try_parse_from([
+            "aoc",
+            "run",
+            "21",
+            "--set",
+            "steps=6",
+            "--set",
+            "target_steps = 10",
+        ])
+        .unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("expected run");
+        };
+        assert_eq!(
+            args.knobs,
+            vec![
+                ("steps".to_string(), "6".to_string()),
+                ("target_steps".to_string(), "10".to_string())
+            ]
+        );
+        assert!(Cli::try_parse_from(["aoc", "run", "21", "--set", "steps"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--set", "steps=6"]).is_err());
+    }
+
+    #[test]
+    fn simulation_profile() {
+        let cli = Cli::try_parse_from([
+            "aoc",
+            "run",
+            "10",
+            "--rate",
+            "500",
+            "--chunk",
+            "20",
+            "--capacity",
+            "4",
+            "--out-of-order",
+            "8",
+        ])
+        .unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("expected run");
+        };
+        let profile = args.simulation.profile();
+        assert_eq!(profile.rate, Some(500.0));
+        assert_eq!(profile.chunk, 20);
+        assert_eq!(profile.capacity, 4);
+        assert_eq!(profile.out_of_order, Some(8));
+
+        assert!(Cli::try_parse_from(["aoc", "run", "10", "--capacity", "0"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "10", "--rate", "0"]).is_err());
+    }
+
+    #[test]
+    fn trace_options() {
+        assert!(Cli::try_parse_from(["aoc", "run", "05", "--trace", "chrome"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "--trace", "json", "run", "--all"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "run", "05", "--trace-file", "t.json"]).is_err());
+    }
+
+    #[test]
+    fn generate_args() {
+        assert!(Cli::try_parse_from(["aoc", "generate", "05", "--seed", "1"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "generate", "--all", "--lines", "10"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "generate", "--all", "--output", "x"]).is_err());
+    }
+
+    #[test]
+    fn bench_args() {
+        let cli = Cli::try_parse_from(["aoc", "bench", "07", "--variant", "real,large"]).unwrap();
+        let Command::Bench(args) = cli.command else {
+            panic!("expected bench");
+        };
+        assert_eq!(args.variant, vec![Variant::Real, Variant::Large]);
+        assert_eq!(args.iterations, 5);
+        assert!(Cli::try_parse_from(["aoc", "bench", "--all", "--iterations", "0"]).is_err());
+    }
+
+    #[test]
+    fn check_args() {
+        assert!(Cli::try_parse_from(["aoc", "check", "--all", "--update"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "check", "21"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "check", "--all", "--allow-missing"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "check"]).is_err());
+    }
+}
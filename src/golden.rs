@@ -0,0 +1,382 @@
+use crate::cli::Variant;
+use crate::runner::{self, DayReport, RunOptions};
+use common::{GenSize, Knobs, Parts, Puzzle};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where the recorded answers live, relative to the repository root
+pub const MANIFEST: &str = "answers.json";
+
+/// Generated inputs are rebuilt from this seed (plus the day number) each check
+const GENERATED_SEED: u64 = 2023;
+const GENERATED_SIZE: GenSize = GenSize {
+    lines: Some(5),
+    width: Some(5),
+};
+
+/// The inputs every day is checked against, when they exist
+const INPUTS: [GoldenInput; 4] = [
+    GoldenInput::Test,
+    GoldenInput::Test2,
+    GoldenInput::Real,
+    GoldenInput::Generated,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GoldenInput {
+    Test,
+    /// Only some days have a second example, it's left out when there's none
+    Test2,
+    Real,
+    /// Small seeded input from the day's generator, always available
+    Generated,
+}
+
+impl GoldenInput {
+    fn name(&self) -> &'static str {
+        match self {
+            GoldenInput::Test => "test",
+            GoldenInput::Test2 => "test2",
+            GoldenInput::Real => "real",
+            GoldenInput::Generated => "generated",
+        }
+    }
+
+    /// The raw input, or the outcome standing in for it: skipped when the
+    /// file isn't there, failed when it can't be made at all
+    fn load(&self, puzzle: &dyn Puzzle) -> Result<(PathBuf, String), Outcome> {
+        let path = match self {
+            GoldenInput::Test => Variant::Test.path(puzzle.day()),
+            GoldenInput::Test2 => Variant::Test2.path(puzzle.day()),
+            GoldenInput::Real => Variant::Real.path(puzzle.day()),
+            GoldenInput::Generated => {
+                let day: u64 = puzzle.day().parse().map_err(|_| {
+                    Outcome::Failed(format!(
+                        "day {:?} isn't a number to seed with",
+                        puzzle.day()
+                    ))
+                })?;
+                let mut rng = StdRng::seed_from_u64(GENERATED_SEED + day);
+                let raw = puzzle.generate(&mut rng, &GENERATED_SIZE);
+                return Ok((PathBuf::from("generated"), raw));
+            }
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => Ok((path, raw)),
+            Err(_) => Err(Outcome::Skipped(format!("no {}", path.display()))),
+        }
+    }
+}
+
+/// Recorded answers, compared in their display form so every answer type fits
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Expected {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part2: Option<String>,
+}
+
+impl From<&DayReport> for Expected {
+    fn from(report: &DayReport) -> Self {
+        Self {
+            part1: report.part1.as_ref().map(|v| v.to_string()),
+            part2: report.part2.as_ref().map(|v| v.to_string()),
+        }
+    }
+}
+
+impl Expected {
+    /// Only the parts recorded are checked, an example may be for just one
+    fn parts(&self) -> Parts {
+        match (&self.part1, &self.part2) {
+            (Some(_), None) => Parts::Part1,
+            (None, Some(_)) => Parts::Part2,
+            _ => Parts::Both,
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |part: &Option<String>| part.clone().unwrap_or_else(|| "-".to_string());
+        write!(f, "{} / {}", show(&self.part1), show(&self.part2))
+    }
+}
+
+/// What's recorded for one input: its answers, and any knobs they were worked
+/// out with. `--update` keeps both the knobs and which parts are recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(default, skip_serializing_if = "Knobs::is_empty")]
+    pub knobs: Knobs,
+    #[serde(flatten)]
+    pub answers: Expected,
+}
+
+/// Day, then input name, then what's recorded for it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Manifest(BTreeMap<String, BTreeMap<String, Entry>>);
+
+impl Manifest {
+    /// A missing manifest is an empty one, so the first `--update` can create it
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .map_err(|e| format!("can't parse {}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("can't read {}: {e}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).unwrap() + "\n";
+        std::fs::write(path, json).map_err(|e| format!("can't write {}: {e}", path.display()))
+    }
+
+    /// Run `input` for `day` as it was recorded, only the parts with answers
+    /// and with the same knobs. Anything unrecorded runs both parts as usual.
+    pub fn run_options(&self, day: &str, input: &str) -> RunOptions {
+        let entry = self.get(day, input);
+        RunOptions {
+            parts: entry.map_or(Parts::Both, |entry| entry.answers.parts()),
+            knobs: entry.map(|entry| entry.knobs.clone()).unwrap_or_default(),
+            ..RunOptions::default()
+        }
+    }
+
+    fn get(&self, day: &str, input: &str) -> Option<&Entry> {
+        self.0.get(day).and_then(|inputs| inputs.get(input))
+    }
+
+    fn record(&mut self, day: &str, input: &str, entry: Entry) {
+        self.0
+            .entry(day.to_string())
+            .or_default()
+            .insert(input.to_string(), entry);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        expected: Expected,
+        actual: Expected,
+    },
+    /// Solved, but there was nothing recorded to compare with
+    Unrecorded(Expected),
+    Recorded(Expected),
+    Skipped(String),
+    Failed(String),
+}
+
+impl Outcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Mismatch { .. } | Outcome::Failed(_))
+    }
+
+    /// Nothing was compared, because the input or its answers are missing
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Outcome::Unrecorded(_) | Outcome::Skipped(_))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "ok"),
+            Outcome::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH expected {expected}, got {actual}")
+            }
+            Outcome::Unrecorded(actual) => write!(f, "not recorded, got {actual}"),
+            Outcome::Recorded(actual) => write!(f, "recorded {actual}"),
+            Outcome::Skipped(reason) => write!(f, "skipped, {reason}"),
+            Outcome::Failed(error) => write!(f, "FAILED {error}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CheckResult {
+    pub day: String,
+    /// The input's name, and any knob it was run with besides the default
+    pub input: String,
+    pub outcome: Outcome,
+}
+
+async fn answers(
+    puzzle: &dyn Puzzle,
+    path: &Path,
+    raw: &str,
+    options: &RunOptions,
+) -> Result<Expected, String> {
+    let report = runner::run_text(puzzle, path, raw, options).await;
+    match &report.error {
+        Some(error) => Err(error.clone()),
+        None => Ok(Expected::from(&report)),
+    }
+}
+
+fn compare(expected: Option<&Expected>, actual: Result<Expected, String>) -> Outcome {
+    match (expected, actual) {
+        (_, Err(error)) => Outcome::Failed(error),
+        (Some(expected), Ok(actual)) if *expected == actual => Outcome::Pass,
+        (Some(expected), Ok(actual)) => Outcome::Mismatch {
+            expected: expected.clone(),
+            actual,
+        },
+        (None, Ok(actual)) => Outcome::Unrecorded(actual),
+    }
+}
+
+/// Run every day on each of its inputs and compare with the manifest. With
+/// `update` the answers are written into the manifest instead. Each value a
+/// knob lists for checking is run too, and has to give the same answers.
+pub async fn check(
+    puzzles: &[Box<dyn Puzzle>],
+    manifest: &mut Manifest,
+    update: bool,
+) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    for puzzle in puzzles {
+        let day = puzzle.day();
+        for input in INPUTS {
+            let mut result = |input: String, outcome| {
+                results.push(CheckResult {
+                    day: day.to_string(),
+                    input,
+                    outcome,
+                })
+            };
+            let recorded = manifest.get(day, input.name()).cloned();
+            let (path, raw) = match input.load(puzzle.as_ref()) {
+                Ok(loaded) => loaded,
+                Err(Outcome::Skipped(_)) if input == GoldenInput::Test2 && recorded.is_none() => {
+                    continue
+                }
+                Err(outcome) => {
+                    result(input.name().to_string(), outcome);
+                    continue;
+                }
+            };
+            let options = manifest.run_options(day, input.name());
+
+            let actual = answers(puzzle.as_ref(), &path, &raw, &options).await;
+            let outcome = match actual {
+                Ok(actual) if update => {
+                    let entry = Entry {
+                        knobs: options.knobs.clone(),
+                        answers: actual.clone(),
+                    };
+                    manifest.record(day, input.name(), entry);
+                    Outcome::Recorded(actual)
+                }
+                actual => compare(recorded.as_ref().map(|r| &r.answers), actual),
+            };
+            result(input.name().to_string(), outcome);
+
+            let expected = manifest.get(day, input.name()).map(|r| r.answers.clone());
+            for knob in puzzle.knobs() {
+                for value in knob.check {
+                    let mut options = options.clone();
+                    options.knobs.set(knob.name, value);
+                    let label = format!("{} ({}={value})", input.name(), knob.name);
+                    let actual = answers(puzzle.as_ref(), &path, &raw, &options).await;
+                    result(label, compare(expected.as_ref(), actual));
+                }
+            }
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use common::{Generator, InputStream, SolveFuture, Solver};
+
+    #[tokio::test]
+    async fn golden_answers() {
+        let mut manifest = Manifest::load(Path::new(MANIFEST)).unwrap();
+        let results = check(&registry::solvers(), &mut manifest, false).await;
+
+        // real inputs aren't committed, every other input has to be there and recorded
+        let failures: Vec<String> = results
+            .iter()
+            .filter(|r| r.outcome.is_failure() || (r.outcome.is_missing() && r.input != "real"))
+            .map(|r| format!("day {} {}: {}", r.day, r.input, r.outcome))
+            .collect();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    struct Unnumbered;
+
+    impl Solver for Unnumbered {
+        fn day(&self) -> &'static str {
+            "xx"
+        }
+
+        fn solve(&self, _input: InputStream, _parts: Parts) -> SolveFuture {
+            unimplemented!("only the input is loaded")
+        }
+    }
+
+    impl Generator for Unnumbered {
+        fn generate(&self, _rng: &mut StdRng, _size: &GenSize) -> String {
+            String::new()
+        }
+    }
+
+    #[test]
+    fn unnumbered_day_fails_to_generate() {
+        assert_eq!(
+            GoldenInput::Generated.load(&Unnumbered),
+            Err(Outcome::Failed(
+                "day \"xx\" isn't a number to seed with".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let mut manifest = Manifest::default();
+        let answers = Entry {
+            knobs: Knobs::default(),
+            answers: Expected {
+                part1: Some("42".to_string()),
+                part2: None,
+            },
+        };
+        manifest.record("06", "real", answers.clone());
+        let mut knobs = Knobs::default();
+        knobs.set("steps", "6");
+        let with_knobs = Entry {
+            knobs,
+            answers: Expected {
+                part1: Some("16".to_string()),
+                part2: None,
+            },
+        };
+        manifest.record("21", "test", with_knobs.clone());
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(
+            json,
+            r#"{"06":{"real":{"part1":"42"}},"21":{"test":{"knobs":{"steps":"6"},"part1":"16"}}}"#
+        );
+        let back: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.get("06", "real"), Some(&answers));
+        assert_eq!(back.get("21", "test"), Some(&with_knobs));
+        assert_eq!(with_knobs.answers.parts(), Parts::Part1);
+    }
+}
@@ -1,21 +1,192 @@
-use tokio::sync::mpsc::Sender;
+use common::{InputShape, InputStream};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::time::{sleep_until, Instant};
+use tracing::{debug, info_span, trace, Instrument};
 
-pub async fn simulate_user_input(tx: Sender<String>, input_data: Vec<String>) {
-    println!("Sending data");
+const CHANNEL_CAPACITY: usize = 100_000;
 
-    for line in input_data {
-        tx.send(line).await.expect("Failed to send input");
+/// How the simulated user types the input in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Average lines per second, unlimited when `None`
+    pub rate: Option<f64>,
+    /// Lines sent back to back before waiting for the rate to catch up
+    pub chunk: usize,
+    /// Extra random delay, up to this much, before each chunk
+    pub jitter: Duration,
+    /// Channel capacity, small values make the sender wait on the solver
+    pub capacity: usize,
+    /// Shuffle enumerated lines within windows of this many lines
+    pub out_of_order: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            rate: None,
+            chunk: 1,
+            jitter: Duration::ZERO,
+            capacity: CHANNEL_CAPACITY,
+            out_of_order: None,
+            seed: 0,
+        }
+    }
+}
+
+impl Profile {
+    /// Gap between chunks needed to hold the average rate
+    fn chunk_interval(&self) -> Option<Duration> {
+        self.rate
+            .map(|rate| Duration::from_secs_f64(self.chunk.max(1) as f64 / rate))
+    }
+}
+
+/// Send every item through `tx`, paced by the profile. Stops early if the
+/// solver hangs up, which it does when it bails out on bad input.
+async fn feed<T>(tx: Sender<T>, input_data: Vec<T>, profile: Profile) {
+    debug!("sending data");
+
+    let mut rng = StdRng::seed_from_u64(profile.seed);
+    let interval = profile.chunk_interval();
+    let mut due = Instant::now();
+    let mut blocked = 0usize;
+
+    for (idx, item) in input_data.into_iter().enumerate() {
+        if idx % profile.chunk.max(1) == 0 {
+            let mut wait_until = due;
+            if !profile.jitter.is_zero() {
+                wait_until += rng.gen_range(Duration::ZERO..=profile.jitter);
+            }
+            if wait_until > Instant::now() {
+                sleep_until(wait_until).await;
+            }
+            if let Some(interval) = interval {
+                due += interval;
+            }
+        }
+
+        let item = match tx.try_send(item) {
+            Ok(()) => None,
+            Err(TrySendError::Full(item)) => Some(item),
+            Err(TrySendError::Closed(_)) => {
+                debug!(line = idx, "receiver closed, stopping");
+                return;
+            }
+        };
+        if let Some(item) = item {
+            blocked += 1;
+            trace!(line = idx, "channel full, waiting on the solver");
+            if tx.send(item).await.is_err() {
+                debug!(line = idx, "receiver closed, stopping");
+                return;
+            }
+        }
+        trace!(line = idx, "sent");
+    }
+
+    debug!(blocked, "finished sending data");
+}
+
+/// Shuffle within consecutive windows, so lines arrive out of order but never
+/// too far from where they belong
+fn reorder<T>(items: &mut [T], window: usize, rng: &mut StdRng) {
+    for chunk in items.chunks_mut(window.max(1)) {
+        chunk.shuffle(rng);
     }
+}
+
+pub async fn simulate_user_input(tx: Sender<String>, input_data: Vec<String>, profile: Profile) {
+    feed(tx, input_data, profile).await;
+}
+
+pub async fn simulate_user_input_enumerated(
+    tx: Sender<(usize, String)>,
+    input_data: Vec<String>,
+    profile: Profile,
+) {
+    let mut items: Vec<_> = input_data.into_iter().enumerate().collect();
+    if let Some(window) = profile.out_of_order {
+        reorder(&mut items, window, &mut StdRng::seed_from_u64(profile.seed));
+    }
+    feed(tx, items, profile).await;
+}
 
-    println!("Finished sending data");
+/// Start streaming the input in the background, in the shape the solver expects
+pub fn simulate(shape: InputShape, input_data: Vec<String>, profile: &Profile) -> InputStream {
+    let span = info_span!("stream", lines = input_data.len());
+    let profile = profile.clone();
+    let capacity = profile.capacity.max(1);
+
+    match shape {
+        InputShape::Lines => {
+            if profile.out_of_order.is_some() {
+                debug!("out of order delivery needs enumerated input, sending in order");
+            }
+            let (tx, rx) = mpsc::channel(capacity);
+            tokio::spawn(simulate_user_input(tx, input_data, profile).instrument(span));
+            InputStream::Lines(rx)
+        }
+        InputShape::Enumerated => {
+            let (tx, rx) = mpsc::channel(capacity);
+            tokio::spawn(simulate_user_input_enumerated(tx, input_data, profile).instrument(span));
+            InputStream::Enumerated(rx)
+        }
+    }
 }
 
-pub async fn simulate_user_input_enumerated(tx: Sender<(usize, String)>, input_data: Vec<String>) {
-    println!("Sending data");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for (idx, line) in input_data.into_iter().enumerate() {
-        tx.send((idx, line)).await.expect("Failed to send input");
+    fn numbered(count: usize) -> Vec<String> {
+        (0..count).map(|n| n.to_string()).collect()
     }
 
-    println!("Finished sending data");
+    #[tokio::test]
+    async fn out_of_order_delivers_every_line_once() {
+        let profile = Profile {
+            out_of_order: Some(8),
+            seed: 7,
+            ..Profile::default()
+        };
+        let mut rx = simulate(InputShape::Enumerated, numbered(40), &profile).into_enumerated();
+
+        let mut received = Vec::new();
+        while let Some((idx, line)) = rx.recv().await {
+            assert_eq!(idx.to_string(), line);
+            received.push(idx);
+        }
+
+        assert_ne!(received, (0..40).collect::<Vec<_>>());
+        for (pos, idx) in received.iter().enumerate() {
+            assert_eq!(pos / 8, idx / 8, "line {idx} left its window");
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_spreads_lines_out() {
+        let profile = Profile {
+            rate: Some(200.0),
+            chunk: 5,
+            capacity: 1,
+            ..Profile::default()
+        };
+        let start = std::time::Instant::now();
+        let mut rx = simulate(InputShape::Lines, numbered(20), &profile).into_lines();
+
+        let mut received = Vec::new();
+        while let Some(line) = rx.recv().await {
+            received.push(line);
+        }
+
+        // four chunks at 25ms apart, the first goes straight away
+        assert_eq!(received, numbered(20));
+        assert!(start.elapsed() >= Duration::from_millis(75));
+    }
 }
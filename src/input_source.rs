@@ -0,0 +1,360 @@
+use common::{Framing, InputShape, InputStream};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::time::{sleep, Instant};
+use tracing::{debug, info_span, trace, Instrument};
+
+const LINE_CAPACITY: usize = 1024;
+
+/// Somewhere live input comes from, one line at a time, until it closes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    /// Follow a file as it is written, until it stops growing
+    Tail(PathBuf),
+    /// Accept one connection on a TCP address and read it to the end
+    Tcp(String),
+    /// Accept one connection on a Unix socket and read it to the end
+    Unix(PathBuf),
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec.split_once(':') {
+            _ if spec == "stdin" || spec == "-" => Ok(Source::Stdin),
+            Some(("tail", path)) if !path.is_empty() => Ok(Source::Tail(path.into())),
+            Some(("tcp", addr)) if !addr.is_empty() => Ok(Source::Tcp(addr.to_string())),
+            Some(("unix", path)) if !path.is_empty() => Ok(Source::Unix(path.into())),
+            _ => Err(format!(
+                "'{spec}' is not stdin, tail:PATH, tcp:HOST:PORT or unix:PATH"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Stdin => write!(f, "stdin"),
+            Source::Tail(path) => write!(f, "tail:{}", path.display()),
+            Source::Tcp(addr) => write!(f, "tcp:{addr}"),
+            Source::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Lines arriving from an opened source
+pub struct Feed {
+    pub lines: Receiver<String>,
+    /// Where a socket source ended up listening, for the other process to connect to
+    pub listening: Option<String>,
+}
+
+impl Source {
+    /// Start reading in the background. Sockets are bound before this
+    /// returns, so a bad address fails here rather than in the reader.
+    pub async fn open(&self, idle: Duration) -> io::Result<Feed> {
+        let (tx, lines) = mpsc::channel(LINE_CAPACITY);
+        let span = info_span!("source", source = %self);
+
+        let listening = match self {
+            Source::Stdin => {
+                let reader = BufReader::new(tokio::io::stdin());
+                tokio::spawn(pump(reader, tx).instrument(span));
+                None
+            }
+            Source::Tail(path) => {
+                let file = tokio::fs::File::open(path).await?;
+                tokio::spawn(tail(BufReader::new(file), tx, idle).instrument(span));
+                None
+            }
+            Source::Tcp(addr) => {
+                let listener = TcpListener::bind(addr).await?;
+                let local = listener.local_addr()?.to_string();
+                let reader = async move {
+                    match listener.accept().await {
+                        Ok((stream, peer)) => {
+                            debug!(%peer, "connected");
+                            pump(BufReader::new(stream), tx).await;
+                        }
+                        Err(e) => debug!("accept failed: {e}"),
+                    }
+                };
+                tokio::spawn(reader.instrument(span));
+                Some(format!("tcp:{local}"))
+            }
+            Source::Unix(path) => {
+                let listener = UnixListener::bind(path)?;
+                let listening = format!("unix:{}", path.display());
+                let path = path.clone();
+                let reader = async move {
+                    match listener.accept().await {
+                        Ok((stream, _)) => pump(BufReader::new(stream), tx).await,
+                        Err(e) => debug!("accept failed: {e}"),
+                    }
+                    // the socket file outlives the listener otherwise
+                    let _ = std::fs::remove_file(&path);
+                };
+                tokio::spawn(reader.instrument(span));
+                Some(listening)
+            }
+        };
+
+        Ok(Feed { lines, listening })
+    }
+}
+
+/// Forward lines until the reader hits the end or the solver hangs up
+async fn pump<R: AsyncBufRead + Unpin>(reader: R, tx: Sender<String>) {
+    let mut reader = reader.lines();
+    let mut count = 0usize;
+    loop {
+        match reader.next_line().await {
+            Ok(Some(line)) => {
+                trace!(line = count, "received");
+                if tx.send(line).await.is_err() {
+                    break;
+                }
+                count += 1;
+            }
+            Ok(None) => break,
+            Err(e) => {
+                debug!("read failed: {e}");
+                break;
+            }
+        }
+    }
+    debug!(lines = count, "input closed");
+}
+
+/// Like `pump`, but an end of file only means nothing has been written yet.
+/// The input is taken as finished once the file has been idle that long.
+async fn tail<R: AsyncBufRead + Unpin>(mut reader: R, tx: Sender<String>, idle: Duration) {
+    let mut partial = String::new();
+    let mut last_data = Instant::now();
+
+    loop {
+        match reader.read_line(&mut partial).await {
+            Ok(0) => {
+                if last_data.elapsed() >= idle {
+                    break;
+                }
+                sleep(Duration::from_millis(20).min(idle)).await;
+            }
+            Ok(_) => {
+                last_data = Instant::now();
+                // a line is only complete once its newline has been written
+                if let Some(line) = partial.strip_suffix('\n') {
+                    let line = line.strip_suffix('\r').unwrap_or(line).to_string();
+                    partial.clear();
+                    if tx.send(line).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("read failed: {e}");
+                break;
+            }
+        }
+    }
+
+    if !partial.is_empty() {
+        let _ = tx.send(std::mem::take(&mut partial)).await;
+    }
+    debug!("file went idle");
+}
+
+enum UnitSender {
+    Lines(Sender<String>),
+    Enumerated(Sender<(usize, String)>),
+}
+
+impl UnitSender {
+    /// False once the solver has stopped listening
+    async fn send(&self, idx: usize, unit: String) -> bool {
+        match self {
+            UnitSender::Lines(tx) => tx.send(unit).await.is_ok(),
+            UnitSender::Enumerated(tx) => tx.send((idx, unit)).await.is_ok(),
+        }
+    }
+}
+
+/// Cut incoming lines into the units a solver expects and number them if it
+/// asked for that. `Framing::Whole` has to be handled by the caller.
+pub fn frame(framing: Framing, shape: InputShape, mut lines: Receiver<String>) -> InputStream {
+    let (units, stream) = match shape {
+        InputShape::Lines => {
+            let (tx, rx) = mpsc::channel(LINE_CAPACITY);
+            (UnitSender::Lines(tx), InputStream::Lines(rx))
+        }
+        InputShape::Enumerated => {
+            let (tx, rx) = mpsc::channel(LINE_CAPACITY);
+            (UnitSender::Enumerated(tx), InputStream::Enumerated(rx))
+        }
+    };
+
+    tokio::spawn(async move {
+        let mut framer = Framer::new(framing);
+        let mut idx = 0usize;
+
+        while let Some(line) = lines.recv().await {
+            for unit in framer.push(&line) {
+                if !units.send(idx, unit).await {
+                    return;
+                }
+                idx += 1;
+            }
+        }
+        if let Some(unit) = framer.finish() {
+            units.send(idx, unit).await;
+        }
+    });
+
+    stream
+}
+
+/// Incremental version of the `split_input` implementations
+struct Framer {
+    framing: Framing,
+    pending: Vec<String>,
+}
+
+impl Framer {
+    fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Take in one line, giving back any units it completed
+    fn push(&mut self, line: &str) -> Vec<String> {
+        match self.framing {
+            Framing::Lines | Framing::Whole => vec![line.to_string()],
+            Framing::Blocks if line.is_empty() => self.finish().into_iter().collect(),
+            Framing::Blocks => {
+                self.pending.push(line.to_string());
+                Vec::new()
+            }
+            Framing::Separator(sep) => {
+                let mut pieces: Vec<String> = line.split(sep).map(|s| s.to_string()).collect();
+                let last = pieces.pop().unwrap_or_default();
+                let mut units = Vec::new();
+                for piece in pieces {
+                    self.pending.push(piece);
+                    units.extend(self.finish());
+                }
+                self.pending.push(last);
+                units
+            }
+        }
+    }
+
+    /// Whatever is left once the input has closed
+    fn finish(&mut self) -> Option<String> {
+        let joiner = match self.framing {
+            Framing::Blocks => "\n",
+            _ => "",
+        };
+        let unit = self.pending.join(joiner);
+        self.pending.clear();
+        (!unit.is_empty()).then_some(unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    fn frame_all(framing: Framing, lines: &[&str]) -> Vec<String> {
+        let mut framer = Framer::new(framing);
+        let mut units: Vec<String> = lines.iter().flat_map(|l| framer.push(l)).collect();
+        units.extend(framer.finish());
+        units
+    }
+
+    #[test]
+    fn source_specs() {
+        assert_eq!("-".parse(), Ok(Source::Stdin));
+        assert_eq!("tail:in.txt".parse(), Ok(Source::Tail("in.txt".into())));
+        assert_eq!(
+            "tcp:127.0.0.1:7000".parse(),
+            Ok(Source::Tcp("127.0.0.1:7000".to_string()))
+        );
+        assert!("tcp:".parse::<Source>().is_err());
+        assert!("ftp:somewhere".parse::<Source>().is_err());
+    }
+
+    #[test]
+    fn framing_matches_split_input() {
+        assert_eq!(
+            frame_all(Framing::Blocks, &["a", "b", "", "c", ""]),
+            vec!["a\nb", "c"]
+        );
+        assert_eq!(
+            frame_all(Framing::Separator(','), &["rn=1,cm-,q", "p=3"]),
+            vec!["rn=1", "cm-", "qp=3"]
+        );
+        assert_eq!(frame_all(Framing::Lines, &["x", ""]), vec!["x", ""]);
+    }
+
+    #[tokio::test]
+    async fn lines_over_tcp_are_numbered() {
+        let source = Source::Tcp("127.0.0.1:0".to_string());
+        let feed = source.open(Duration::ZERO).await.unwrap();
+        let addr = feed.listening.unwrap().replace("tcp:", "");
+
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"first\r\nsecond\nthird").await.unwrap();
+        drop(client);
+
+        let mut rx = frame(Framing::Lines, InputShape::Enumerated, feed.lines).into_enumerated();
+        let mut received = Vec::new();
+        while let Some(unit) = rx.recv().await {
+            received.push(unit);
+        }
+        assert_eq!(
+            received,
+            vec![
+                (0, "first".to_string()),
+                (1, "second".to_string()),
+                (2, "third".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn tail_waits_for_the_rest_of_a_line() {
+        let path = std::env::temp_dir().join(format!("aoc-tail-{}.txt", std::process::id()));
+        std::fs::write(&path, "one\ntw").unwrap();
+
+        let feed = Source::Tail(path.clone())
+            .open(Duration::from_millis(300))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, b"o\nthree\n").unwrap();
+
+        let mut lines = feed.lines;
+        let mut received = Vec::new();
+        while let Some(line) = lines.recv().await {
+            received.push(line);
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(received, vec!["one", "two", "three"]);
+    }
+}
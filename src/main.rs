@@ -1,100 +1,218 @@
+mod bench;
+mod cli;
+mod golden;
 mod input_simulator;
+mod input_source;
+mod peak_alloc;
+mod registry;
+mod runner;
+mod telemetry;
 
-use tokio::sync::mpsc;
+use clap::Parser;
+use cli::{BenchArgs, CheckArgs, Cli, Command, Format, GenerateArgs, RunArgs, Variant};
+use common::{GenSize, Knobs};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use runner::RunOptions;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() {
-    let day = std::env::args().nth(1).expect("Please provide_day number");
-    let test = std::env::args().nth(2).is_some_and(|s| s == "test");
-    let large = std::env::args().nth(2).is_some_and(|s| s == "large");
-
-    let filename = match (test, large) {
-        (true, false) => format!("problems/day{}/input_test.txt", day),
-        (false, true) => format!("problems/day{day}/day{day}_big_input.txt"),
-        (false, false) => format!("problems/day{}/input.txt", day),
-        (true, true) => panic!("incompatible options"),
+#[global_allocator]
+static ALLOC: peak_alloc::PeakAlloc = peak_alloc::PeakAlloc;
+
+async fn run(args: RunArgs) -> Result<bool, String> {
+    let mut knobs = Knobs::default();
+    for (name, value) in &args.knobs {
+        knobs.set(name, value);
+    }
+    let options = RunOptions {
+        parts: args.part.into(),
+        on_panic: args.on_panic.into(),
+        profile: args.simulation.profile(),
+        knobs,
     };
-    dbg!(&filename);
-    let input_data = match day.as_str() {
-        "15" => std::fs::read_to_string(filename)
-            .expect("Can't read input file")
-            .split(',')
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>(),
-        "05" | "13" => std::fs::read_to_string(filename)
-            .expect("Can't read input file")
-            .split("\n\n")
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>(),
-        "06" => {
-            let rows = std::fs::read_to_string(filename)
-                .expect("Can't read input file")
-                .lines()
-                .map(|l| {
-                    let (_feature, values) = l.split_once(':').unwrap();
-                    values
-                        .split(' ')
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.to_string())
-                        .collect::<Vec<String>>()
-                })
-                .collect::<Vec<Vec<String>>>();
-
-            rows[0]
-                .iter()
-                .zip(rows[1].iter())
-                .map(|(s1, s2)| format!("{s1} {s2}"))
-                .collect::<Vec<String>>()
+
+    let reports = if args.all {
+        let mut reports = Vec::new();
+        for solver in registry::solvers() {
+            let input = args.variant.path(solver.day());
+            reports.push(runner::run_day(solver.as_ref(), &input, &options).await);
         }
-        _ => std::fs::read_to_string(filename)
-            .expect("Can't read input file")
-            .lines()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>(),
+        reports
+    } else {
+        let day = cli::parse_day(args.day.as_deref().unwrap_or_default())?;
+        let solver =
+            registry::find(&day).ok_or(format!("solution for day {day} not implemented"))?;
+        let report = match &args.source {
+            Some(source) => {
+                let idle = Duration::from_millis(args.idle_ms);
+                runner::run_source(solver.as_ref(), source, idle, &options).await
+            }
+            None => {
+                let input = args.input.unwrap_or_else(|| args.variant.path(&day));
+                runner::run_day(solver.as_ref(), &input, &options).await
+            }
+        };
+        vec![report]
     };
 
-    let (tx, rx) = mpsc::channel(100_000);
-    let (tx2, rx2) = mpsc::channel(100_000);
+    match (args.format, reports.as_slice()) {
+        (Format::Json, [report]) if !args.all => {
+            println!("{}", serde_json::to_string_pretty(report).unwrap())
+        }
+        (Format::Json, _) => println!("{}", serde_json::to_string_pretty(&reports).unwrap()),
+        (Format::Text, [report]) if !args.all => match &report.error {
+            Some(error) => return Err(error.clone()),
+            None => {
+                for failure in &report.failures {
+                    eprintln!("warning: skipped {failure}");
+                }
+                let answer = common::Answer {
+                    part1: report.part1.clone(),
+                    part2: report.part2.clone(),
+                };
+                println!("{answer}");
+            }
+        },
+        (Format::Text, _) => runner::print_table(&reports),
+    }
+
+    Ok(reports.iter().all(|r| r.error.is_none()))
+}
+
+fn generate(args: GenerateArgs) -> Result<bool, String> {
+    let puzzles = if args.all {
+        registry::solvers()
+    } else {
+        let day = cli::parse_day(args.day.as_deref().unwrap_or_default())?;
+        vec![registry::find(&day).ok_or(format!("solution for day {day} not implemented"))?]
+    };
+
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = rand::random();
+        eprintln!("using seed {seed}");
+        seed
+    });
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = GenSize {
+        lines: args.lines,
+        width: args.width,
+    };
+
+    for puzzle in puzzles {
+        let output = match &args.output {
+            Some(output) => output.clone(),
+            None => Variant::Large.path(puzzle.day()),
+        };
+        std::fs::write(&output, puzzle.generate(&mut rng, &size))
+            .map_err(|e| format!("can't write {}: {e}", output.display()))?;
+        eprintln!("wrote {}", output.display());
+    }
+
+    Ok(true)
+}
+
+async fn check(args: CheckArgs) -> Result<bool, String> {
+    let puzzles = if args.all {
+        registry::solvers()
+    } else {
+        let day = cli::parse_day(args.day.as_deref().unwrap_or_default())?;
+        vec![registry::find(&day).ok_or(format!("solution for day {day} not implemented"))?]
+    };
 
-    match day.as_str() {
-        "03" | "04" | "10" | "11" | "14" | "16" | "17" | "18" | "21" | "22" | "23" => {
-            tokio::spawn(async move {
-                input_simulator::simulate_user_input_enumerated(tx2, input_data).await;
-            });
+    let manifest_path = Path::new(golden::MANIFEST);
+    let mut manifest = golden::Manifest::load(manifest_path)?;
+    let results = golden::check(&puzzles, &mut manifest, args.update).await;
+
+    for result in &results {
+        println!("{:<4} {:<10} {}", result.day, result.input, result.outcome);
+    }
+    let failed = results.iter().filter(|r| r.outcome.is_failure()).count();
+    let missing = results.iter().filter(|r| r.outcome.is_missing()).count();
+    if args.update {
+        manifest.save(manifest_path)?;
+        eprintln!("wrote {}", manifest_path.display());
+    }
+    println!(
+        "{} checked, {failed} failed, {missing} missing",
+        results.len() - missing
+    );
+    if missing > 0 && !args.allow_missing {
+        eprintln!(
+            "warning: {missing} input(s) missing or not recorded, --allow-missing to let that pass"
+        );
+    }
+
+    Ok(failed == 0 && (missing == 0 || args.allow_missing))
+}
+
+async fn bench(args: BenchArgs) -> Result<bool, String> {
+    let puzzles = if args.all {
+        registry::solvers()
+    } else {
+        let day = cli::parse_day(args.day.as_deref().unwrap_or_default())?;
+        vec![registry::find(&day).ok_or(format!("solution for day {day} not implemented"))?]
+    };
+    let baseline = args.baseline.as_deref().map(bench::load).transpose()?;
+    // examples are timed on the parts they have answers for
+    let manifest = golden::Manifest::load(Path::new(golden::MANIFEST))?;
+
+    let mut entries = Vec::new();
+    let mut failed = false;
+    for puzzle in &puzzles {
+        for &variant in &args.variant {
+            let options = manifest.run_options(puzzle.day(), &variant.to_string());
+            let iterations = args.iterations as usize;
+            match bench::bench_day(puzzle.as_ref(), variant, iterations, &options).await {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => eprintln!(
+                    "skipping day {} {variant}, no {}",
+                    puzzle.day(),
+                    variant.path(puzzle.day()).display()
+                ),
+                Err(error) => {
+                    eprintln!("day {} {variant} failed: {error}", puzzle.day());
+                    failed = true;
+                }
+            }
         }
-        _ => {
-            tokio::spawn(async move {
-                input_simulator::simulate_user_input(tx, input_data).await;
-            });
+    }
+
+    bench::print_table(&entries, baseline.as_deref());
+    if let Some(output) = &args.output {
+        bench::save(&entries, output)?;
+        eprintln!("wrote {}", output.display());
+    }
+
+    Ok(!failed)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let _guard = match telemetry::init(cli.trace, cli.trace_file.as_deref()) {
+        Ok(guard) => guard,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
         }
     };
 
-    match day.as_str() {
-        "01" => day01::solve(rx).await,
-        "02" => day02::solve(rx).await,
-        "03" => day03::solve(rx2).await,
-        "04" => day04::solve(rx2).await,
-        "05" => day05::solve(rx).await,
-        "06" => day06::solve(rx).await,
-        "07" => day07::solve(rx).await,
-        "08" => day08::solve(rx).await,
-        "09" => day09::solve(rx).await,
-        "10" => day10::solve(rx2).await,
-        "11" => day11::solve(rx2).await,
-        "12" => day12::solve(rx).await,
-        "13" => day13::solve(rx).await,
-        "14" => day14::solve(rx2).await,
-        "15" => day15::solve(rx).await,
-        "16" => day16::solve(rx2).await,
-        "17" => day17::solve(rx2).await,
-        "18" => day18::solve(rx2).await,
-        "19" => day19::solve(rx).await,
-        "20" => day20::solve(rx).await,
-        "21" => day21::solve(rx2).await,
-        "22" => day22::solve(rx2).await,
-        "23" => day23::solve(rx2).await,
-        "24" => day24::solve(rx).await,
-        "25" => day25::solve(rx).await,
-        _ => eprintln!("Solution for day {} not implemented", day),
+    let outcome = match cli.command {
+        Command::Run(args) => run(args).await,
+        Command::Generate(args) => generate(args),
+        Command::Check(args) => check(args).await,
+        Command::Bench(args) => bench(args).await,
     };
+
+    match outcome {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,108 @@
+use common::Puzzle;
+
+pub fn solvers() -> Vec<Box<dyn Puzzle>> {
+    vec![
+        Box::new(day01::Day01),
+        Box::new(day02::Day02),
+        Box::new(day03::Day03),
+        Box::new(day04::Day04),
+        Box::new(day05::Day05),
+        Box::new(day06::Day06),
+        Box::new(day07::Day07),
+        Box::new(day08::Day08),
+        Box::new(day09::Day09),
+        Box::new(day10::Day10),
+        Box::new(day11::Day11),
+        Box::new(day12::Day12),
+        Box::new(day13::Day13),
+        Box::new(day14::Day14),
+        Box::new(day15::Day15),
+        Box::new(day16::Day16),
+        Box::new(day17::Day17),
+        Box::new(day18::Day18),
+        Box::new(day19::Day19),
+        Box::new(day20::Day20),
+        Box::new(day21::Day21),
+        Box::new(day22::Day22),
+        Box::new(day23::Day23),
+        Box::new(day24::Day24),
+        Box::new(day25::Day25),
+    ]
+}
+
+pub fn find(day: &str) -> Option<Box<dyn Puzzle>> {
+    solvers().into_iter().find(|p| p.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{self, RunOptions};
+    use common::{AnswerValue, GenSize};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn every_day_solves_generated_input() {
+        let mut rng = StdRng::seed_from_u64(2023);
+        let size = GenSize {
+            lines: Some(5),
+            width: Some(5),
+        };
+
+        for puzzle in solvers() {
+            let raw = puzzle.generate(&mut rng, &size);
+            let report = runner::run_text(
+                puzzle.as_ref(),
+                Path::new("generated"),
+                &raw,
+                &RunOptions::default(),
+            )
+            .await;
+            assert_eq!(report.error, None, "day {} failed on:\n{raw}", puzzle.day());
+        }
+    }
+
+    #[tokio::test]
+    async fn knobs_reach_the_day() {
+        let garden = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+        let run = |day: &str, knobs: &[(&str, &str)]| {
+            let solver = find(day).unwrap();
+            let mut options = RunOptions::default();
+            for (name, value) in knobs {
+                options.knobs.set(name, value);
+            }
+            async move {
+                runner::run_text(solver.as_ref(), Path::new("example"), garden, &options).await
+            }
+        };
+
+        let report = run("21", &[("steps", "6"), ("target_steps", "1000")]).await;
+        assert_eq!(report.error, None);
+        assert_eq!(report.part1, Some(AnswerValue::Int(16)));
+        assert_eq!(report.part2, Some(AnswerValue::Int(668697)));
+
+        let error = |report: runner::DayReport| report.error.unwrap_or_default();
+        assert_eq!(
+            error(run("21", &[("step", "6")]).await),
+            "day 21 has no knob step, it has steps, target_steps"
+        );
+        assert!(error(run("21", &[("steps", "six")]).await)
+            .starts_with("day 21: can't set steps to 'six'"));
+        assert_eq!(
+            error(run("01", &[("steps", "6")]).await),
+            "day 01 has no knobs to set steps"
+        );
+    }
+}
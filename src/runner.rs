@@ -0,0 +1,226 @@
+use crate::input_simulator::{self, Profile};
+use crate::input_source::{self, Source};
+use common::join::{self, panic_message};
+use common::{AnswerValue, Framing, InputStream, Knobs, OnPanic, Parts, Solver, TaskFailure};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{info_span, Instrument, Span};
+
+#[derive(Debug, Serialize)]
+pub struct DayReport {
+    pub day: String,
+    pub input: String,
+    pub part1: Option<AnswerValue>,
+    pub part2: Option<AnswerValue>,
+    pub error: Option<String>,
+    /// Input lines whose tasks panicked but were skipped over
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<TaskFailure>,
+    pub elapsed_ms: f64,
+}
+
+/// Everything about a run that isn't the solver or its input
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Only work out these parts
+    pub parts: Parts,
+    pub on_panic: OnPanic,
+    pub profile: Profile,
+    /// Day specific settings, checked against the ones the solver has
+    pub knobs: Knobs,
+}
+
+impl DayReport {
+    fn failed(day: &str, input: &str, error: String) -> Self {
+        Self {
+            day: day.to_string(),
+            input: input.to_string(),
+            part1: None,
+            part2: None,
+            error: Some(error),
+            failures: Vec::new(),
+            elapsed_ms: 0.0,
+        }
+    }
+}
+
+/// Stream the input file through a solver, catching read errors and panics
+pub async fn run_day<S>(solver: &S, input: &Path, options: &RunOptions) -> DayReport
+where
+    S: Solver + ?Sized,
+{
+    match std::fs::read_to_string(input) {
+        Ok(raw) => run_text(solver, input, &raw, options).await,
+        Err(e) => DayReport::failed(
+            solver.day(),
+            &input.display().to_string(),
+            format!("can't read {}: {e}", input.display()),
+        ),
+    }
+}
+
+/// Stream already loaded input through a solver, `input` only labels the report
+pub async fn run_text<S>(solver: &S, input: &Path, raw: &str, options: &RunOptions) -> DayReport
+where
+    S: Solver + ?Sized,
+{
+    let units = solver.split_input(raw);
+    run_units(solver, &input.display().to_string(), units, options).await
+}
+
+/// Stream input that has already been split into the solver's units
+pub async fn run_units<S>(
+    solver: &S,
+    label: &str,
+    units: Vec<String>,
+    options: &RunOptions,
+) -> DayReport
+where
+    S: Solver + ?Sized,
+{
+    let span = info_span!("day", day = solver.day(), input = %label);
+    let start = Instant::now();
+    let stream =
+        span.in_scope(|| input_simulator::simulate(solver.input_shape(), units, &options.profile));
+    run_stream(solver, label, stream, options, start, span).await
+}
+
+/// Feed a solver from a live source, answering once the source closes
+pub async fn run_source<S>(
+    solver: &S,
+    source: &Source,
+    idle: Duration,
+    options: &RunOptions,
+) -> DayReport
+where
+    S: Solver + ?Sized,
+{
+    let label = source.to_string();
+    let span = info_span!("day", day = solver.day(), input = %label);
+    let start = Instant::now();
+
+    let feed = match source.open(idle).await {
+        Ok(feed) => feed,
+        Err(e) => {
+            return DayReport::failed(solver.day(), &label, format!("can't open {label}: {e}"))
+        }
+    };
+    if let Some(listening) = &feed.listening {
+        eprintln!("waiting for input on {listening}");
+    }
+
+    let stream = match solver.framing() {
+        Framing::Whole => {
+            let mut lines = feed.lines;
+            let mut raw = String::new();
+            while let Some(line) = lines.recv().await {
+                raw.push_str(&line);
+                raw.push('\n');
+            }
+            let units = solver.split_input(&raw);
+            span.in_scope(|| {
+                input_simulator::simulate(solver.input_shape(), units, &options.profile)
+            })
+        }
+        framing => input_source::frame(framing, solver.input_shape(), feed.lines),
+    };
+    run_stream(solver, &label, stream, options, start, span).await
+}
+
+async fn run_stream<S>(
+    solver: &S,
+    label: &str,
+    stream: InputStream,
+    options: &RunOptions,
+    start: Instant,
+    span: Span,
+) -> DayReport
+where
+    S: Solver + ?Sized,
+{
+    let day = solver.day();
+    if let Err(error) = check_knobs(solver, &options.knobs) {
+        return DayReport::failed(day, label, error);
+    }
+    let solve = join::with_policy(
+        options.on_panic,
+        solver.solve_with(stream, options.parts, &options.knobs),
+    );
+    let result = tokio::spawn(solve.instrument(span)).await;
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok((Ok(answer), failures)) => DayReport {
+            day: day.to_string(),
+            input: label.to_string(),
+            part1: answer.part1,
+            part2: answer.part2,
+            error: None,
+            failures,
+            elapsed_ms,
+        },
+        Ok((Err(e), _)) => DayReport::failed(day, label, e.to_string()),
+        Err(e) if e.is_panic() => DayReport::failed(
+            day,
+            label,
+            format!("solver panicked: {}", panic_message(e.into_panic())),
+        ),
+        Err(e) => DayReport::failed(day, label, format!("solver was cancelled: {e}")),
+    }
+}
+
+/// Every knob set has to be one the day reads, a typo shouldn't quietly run the defaults
+fn check_knobs<S>(solver: &S, knobs: &Knobs) -> Result<(), String>
+where
+    S: Solver + ?Sized,
+{
+    let known: Vec<&str> = solver.knobs().iter().map(|knob| knob.name).collect();
+    match knobs.names().find(|name| !known.contains(name)) {
+        None => Ok(()),
+        Some(name) if known.is_empty() => {
+            Err(format!("day {} has no knobs to set {name}", solver.day()))
+        }
+        Some(name) => Err(format!(
+            "day {} has no knob {name}, it has {}",
+            solver.day(),
+            known.join(", ")
+        )),
+    }
+}
+
+fn cell(value: &Option<AnswerValue>) -> String {
+    value
+        .as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn print_table(reports: &[DayReport]) {
+    println!(
+        "{:<4} {:>20} {:>20} {:>12}",
+        "Day", "Part 1", "Part 2", "Time"
+    );
+    for report in reports {
+        match &report.error {
+            None => println!(
+                "{:<4} {:>20} {:>20} {:>10.1}ms",
+                report.day,
+                cell(&report.part1),
+                cell(&report.part2),
+                report.elapsed_ms
+            ),
+            Some(error) => println!("{:<4} error: {error}", report.day),
+        }
+        for failure in &report.failures {
+            println!("{:<4} skipped {failure}", "");
+        }
+    }
+
+    let failed = reports.iter().filter(|r| r.error.is_some()).count();
+    let total_ms: f64 = reports.iter().map(|r| r.elapsed_ms).sum();
+    println!(
+        "{} solved, {failed} failed, {total_ms:.1}ms total",
+        reports.len() - failed
+    );
+}
@@ -0,0 +1,58 @@
+use crate::cli::Trace;
+use std::fs::File;
+use std::path::Path;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard, TraceStyle};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+/// `RUST_LOG` wins over the default level when it is set
+fn filter(default: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+}
+
+/// Install the subscriber picked on the command line, writing to `file` when
+/// given, otherwise stderr (or trace.json for Chrome traces). Chrome traces are
+/// only complete once the returned guard is dropped.
+pub fn init(trace: Option<Trace>, file: Option<&Path>) -> Result<Option<FlushGuard>, String> {
+    let open = |path: &Path| {
+        File::create(path).map_err(|e| format!("can't create {}: {e}", path.display()))
+    };
+
+    match trace {
+        None => Ok(None),
+        Some(Trace::Pretty) => {
+            let builder = tracing_subscriber::fmt()
+                .pretty()
+                .with_env_filter(filter("info"));
+            match file {
+                Some(path) => builder.with_ansi(false).with_writer(open(path)?).init(),
+                None => builder.with_writer(std::io::stderr).init(),
+            }
+            Ok(None)
+        }
+        Some(Trace::Json) => {
+            let builder = tracing_subscriber::fmt()
+                .json()
+                .with_span_list(true)
+                .with_env_filter(filter("info"));
+            match file {
+                Some(path) => builder.with_writer(open(path)?).init(),
+                None => builder.with_writer(std::io::stderr).init(),
+            }
+            Ok(None)
+        }
+        Some(Trace::Chrome) => {
+            let path = file.unwrap_or(Path::new("trace.json"));
+            // async style, parse tasks hop between worker threads
+            let (layer, guard) = ChromeLayerBuilder::new()
+                .writer(open(path)?)
+                .include_args(true)
+                .trace_style(TraceStyle::Async)
+                .build();
+            tracing_subscriber::registry()
+                .with(layer.with_filter(filter("debug")))
+                .init();
+            Ok(Some(guard))
+        }
+    }
+}